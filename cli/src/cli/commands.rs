@@ -1,9 +1,12 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueHint};
 
 use crate::record::config::CliRecordConfig;
 
 use super::super::record::Record;
 use super::completion::Shell;
+use super::validate::Validator;
 
 #[derive(Parser, Debug, Eq, PartialEq)]
 pub enum Commands {
@@ -17,6 +20,17 @@ pub enum Commands {
         #[clap(flatten)]
         config: CliRecordConfig,
     },
+    /// Checks every stub under a directory without starting a server
+    ///
+    /// Reports invalid json, regexes or json paths, missing body or protobuf files, templates which do not compile,
+    /// fields not declared in the stub schema... Exits with a non-zero status when any stub is invalid
+    Validate {
+        /// stub files directory or single stub file
+        ///
+        /// Defaults to current directory when not present
+        #[clap(value_parser, value_hint = ValueHint::AnyPath)]
+        dir: Option<PathBuf>,
+    },
 }
 
 impl Commands {
@@ -25,6 +39,7 @@ impl Commands {
         match self {
             Commands::Completion { shell } => shell.generate_and_install(),
             Commands::Record { config } => Record::record(config).await,
+            Commands::Validate { dir } => return Validator::validate(dir),
         }
         Ok(())
     }
@@ -3,23 +3,26 @@ use std::{
     ffi::OsStr,
     fs::DirEntry,
     path::{Path, PathBuf},
+    str::FromStr,
     time::{Duration, Instant},
 };
 
-use clap::{Parser, ValueHint};
+use clap::{ArgGroup, Parser, ValueEnum, ValueHint};
 use colored::Colorize;
-use log::info;
+use log::{info, warn};
 
 use commands::Commands;
-use stubr::{Config, Stubr};
+use stubr::{ClientAuth, Config, DelayDistribution, Fault, GlobalFault, NetworkConfig, RecordOnMiss, Stubr, StubsReload, Tls};
 
 mod commands;
 mod completion;
 pub mod logger;
+mod validate;
 
 /// A Rust implementation of Wiremock
 #[derive(Parser, Debug, Default)]
 #[clap(version, about, name = "stubr", bin_name = "stubr", rename_all = "kebab-case")]
+#[clap(group = ArgGroup::new("https").multiple(true).args(["tls", "tls_cert", "tls_ca_out"]))]
 pub struct Cli {
     /// stub files directory
     ///
@@ -47,12 +50,84 @@ pub struct Cli {
     /// adds this delay to any locally defined delay. Simulates network delays.
     #[clap(short, long, value_parser)]
     latency: Option<String>,
+    /// random latency in milliseconds, same as a stub's 'delayDistribution'
+    ///
+    /// e.g. '{"type": "uniform", "lower": 10, "upper": 50}' or '{"type": "percentiles", "p50": 20, "p99": 300}'.
+    /// Adds it to any locally defined delay, along with '--latency'
+    #[clap(long = "latency-distribution", value_parser = parse_latency_distribution)]
+    latency_distribution: Option<DelayDistribution>,
+    /// keeps at most this many received requests in the request journal
+    ///
+    /// They can then be queried with the '/__admin/requests' endpoints. Disabled when not present
+    #[clap(long = "max-request-journal-entries", value_parser)]
+    max_request_journal_entries: Option<usize>,
+    /// reloads stubs when stub files are created, modified or deleted
+    ///
+    /// A stub file which cannot be loaded is reported and its previous version keeps being served
+    #[clap(short, long, value_parser)]
+    watch: bool,
+    /// fails startup when any stub cannot be loaded
+    ///
+    /// Every rejected stub file is reported along with the reason. Otherwise they are logged as warnings and skipped
+    #[clap(long, value_parser)]
+    strict: bool,
+    /// breaks responses at the connection level
+    ///
+    /// One of CONNECTION_RESET_BY_PEER, EMPTY_RESPONSE, MALFORMED_RESPONSE_CHUNK or RANDOM_DATA_THEN_CLOSE.
+    /// Does not apply to stubs defining their own fault
+    #[clap(long, value_parser = parse_fault)]
+    fault: Option<Fault>,
+    /// share of the responses broken by '--fault', from 0 to 1
+    ///
+    /// Defaults to 1 i.e. every response
+    #[clap(long = "fault-probability", value_parser = parse_probability)]
+    fault_probability: Option<f64>,
+    /// caps the transfer rate of response bodies per second e.g. 512B, 10KB or 1MB
+    ///
+    /// Does not apply to stubs defining a chunked dribble delay
+    #[clap(long, value_parser = parse_bandwidth)]
+    bandwidth: Option<u64>,
+    /// forwards requests no stub matches to this upstream base url e.g. 'http://localhost:8080'
+    ///
+    /// The exchanges are then recorded as new stubs in the stubs directory, which serve the next identical requests
+    #[clap(long = "record-on-miss", value_parser)]
+    record_on_miss: Option<String>,
+    /// serves stubs over https with a certificate generated for 'localhost' and '127.0.0.1'
+    ///
+    /// Clients have to trust the CA which signed it, written to '--tls-ca-out'
+    #[clap(long, value_parser)]
+    tls: bool,
+    /// PEM encoded certificate served over https instead of a generated one, along with '--tls-key'
+    #[clap(long = "tls-cert", value_parser, value_hint = ValueHint::FilePath, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+    /// PEM encoded private key of '--tls-cert'
+    #[clap(long = "tls-key", value_parser, value_hint = ValueHint::FilePath, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+    /// file where the CA which signed the generated certificate is written. Defaults to 'stubr-ca.pem' in the temp directory
+    #[clap(long = "tls-ca-out", value_parser, value_hint = ValueHint::FilePath)]
+    tls_ca_out: Option<PathBuf>,
+    /// asks https clients for a certificate (mutual TLS)
+    ///
+    /// A required certificate fails the handshake of clients without one. Stubs can match the presented certificate
+    #[clap(long = "tls-client-auth", value_enum, requires = "https")]
+    tls_client_auth: Option<ClientAuthMode>,
+    /// PEM encoded CAs client certificates have to be signed by. Otherwise any certificate is accepted
+    #[clap(long = "tls-client-ca", value_parser, value_hint = ValueHint::FilePath, requires = "https")]
+    tls_client_ca: Option<PathBuf>,
     #[clap(subcommand)]
     cmd: Option<Commands>,
 }
 
+/// Whether https clients have to present a certificate
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientAuthMode {
+    Optional,
+    Required,
+}
+
 impl Cli {
     const MAPPINGS_FOLDER: &'static str = "mappings";
+    const CA_FILE: &'static str = "stubr-ca.pem";
     const SLEEP_DURATION: Duration = Duration::from_millis(1000);
 
     // Runs stubr forever until process exits
@@ -60,7 +135,10 @@ impl Cli {
         if let Some(cmd) = self.cmd {
             cmd.exec().await
         } else {
-            Self::run_server(self.stubs_dir(), self.into(), start_time).await
+            let watch = self.watch;
+            let ca_out = self.ca_out();
+            let network = self.network();
+            Self::run_server(self.stubs_dir(), self.into(), network, watch, ca_out, start_time).await
         }
     }
 
@@ -68,19 +146,43 @@ impl Cli {
     /// Mostly used by the cli.
     /// * `stubs` - folder or file containing the stubs
     /// * `config` - global server configuration
-    async fn run_server(stubs: PathBuf, config: Config, start_time: Instant) -> anyhow::Result<()> {
-        let server = Stubr::start_with(stubs, config).await;
+    /// * `network` - https and upstream forwarding configuration
+    /// * `watch` - reloads stubs whenever stub files change
+    /// * `ca_out` - file where the CA of a generated certificate is written
+    async fn run_server(
+        stubs: PathBuf, config: Config, network: NetworkConfig, watch: bool, ca_out: PathBuf, start_time: Instant,
+    ) -> anyhow::Result<()> {
+        let server = Stubr::try_start_with_network(stubs, config, network)
+            .await
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
         info!(
             "Started {} in {}ms on {}",
             "stubr".green().bold(),
             start_time.elapsed().as_millis(),
             server.uri()
         );
+        if let Some(ca) = server.ca_cert() {
+            std::fs::write(&ca_out, ca)?;
+            info!("Wrote the CA to trust to '{}'", ca_out.display());
+        }
         loop {
             async_std::task::sleep(Self::SLEEP_DURATION).await;
+            if watch {
+                Self::log_reload(server.reload_stubs());
+            }
         }
     }
 
+    fn log_reload(reload: StubsReload) {
+        reload.added.iter().for_each(|f| info!("added stub '{}'", f.display()));
+        reload.changed.iter().for_each(|f| info!("changed stub '{}'", f.display()));
+        reload.removed.iter().for_each(|f| info!("removed stub '{}'", f.display()));
+        reload
+            .errors
+            .iter()
+            .for_each(|(f, e)| warn!("could not load stub '{}' because {}", f.display(), e));
+    }
+
     fn stubs_dir(&self) -> PathBuf {
         self.root_dir().or_else(|| self.dir()).expect("Could not find stub directory")
     }
@@ -112,6 +214,46 @@ impl Cli {
         path.is_dir() && path.file_name() == Some(OsStr::new(Self::MAPPINGS_FOLDER))
     }
 
+    fn network(&self) -> NetworkConfig {
+        NetworkConfig {
+            record_on_miss: self
+                .record_on_miss
+                .clone()
+                .map(|upstream| RecordOnMiss { upstream, output: None }),
+            tls: self.tls(),
+            client_auth: self.client_auth(),
+        }
+    }
+
+    fn tls(&self) -> Option<Tls> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Some(Tls::Files {
+                cert: cert.clone(),
+                key: key.clone(),
+            }),
+            _ if self.tls || self.tls_ca_out.is_some() => Some(Tls::SelfSigned),
+            _ => None,
+        }
+    }
+
+    fn client_auth(&self) -> Option<ClientAuth> {
+        let required = match self.tls_client_auth {
+            Some(mode) => mode == ClientAuthMode::Required,
+            None if self.tls_client_ca.is_some() => false,
+            None => return None,
+        };
+        Some(ClientAuth {
+            ca: self.tls_client_ca.clone(),
+            required,
+        })
+    }
+
+    fn ca_out(&self) -> PathBuf {
+        self.tls_ca_out
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join(Self::CA_FILE))
+    }
+
     fn global_delay_milliseconds(&self) -> Option<u64> {
         humantime::parse_duration(self.delay.as_deref()?)
             .ok()?
@@ -120,6 +262,13 @@ impl Cli {
             .ok()
     }
 
+    fn global_fault(&self) -> Option<GlobalFault> {
+        Some(GlobalFault {
+            fault: self.fault?,
+            probability: self.fault_probability.unwrap_or(1.0),
+        })
+    }
+
     fn latency_milliseconds(&self) -> Option<u64> {
         humantime::parse_duration(self.latency.as_deref()?)
             .ok()?
@@ -129,6 +278,40 @@ impl Cli {
     }
 }
 
+fn parse_fault(fault: &str) -> Result<Fault, String> {
+    Fault::from_str(fault).map_err(|e| e.to_string())
+}
+
+fn parse_probability(probability: &str) -> Result<f64, String> {
+    probability
+        .parse::<f64>()
+        .ok()
+        .filter(|p| (0.0..=1.0).contains(p))
+        .ok_or_else(|| format!("'{probability}' is not a probability between 0 and 1"))
+}
+
+fn parse_latency_distribution(distribution: &str) -> Result<DelayDistribution, String> {
+    serde_json::from_str(distribution).map_err(|e| format!("'{distribution}' is not a delay distribution: {e}"))
+}
+
+/// Bytes per second from e.g. '512', '512B', '10KB' or '1MB'
+fn parse_bandwidth(bandwidth: &str) -> Result<u64, String> {
+    let invalid = || format!("'{bandwidth}' is not a bandwidth such as 512B, 10KB or 1MB");
+    let upper = bandwidth.trim().to_uppercase();
+    let (amount, unit) = upper.split_at(upper.find(|c: char| !c.is_ascii_digit()).unwrap_or(upper.len()));
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "KB" => 1024,
+        "MB" => 1024 * 1024,
+        _ => return Err(invalid()),
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(multiplier))
+        .ok_or_else(invalid)
+}
+
 impl From<Cli> for Config {
     fn from(cli: Cli) -> Self {
         Self {
@@ -137,6 +320,13 @@ impl From<Cli> for Config {
             verify: false,
             global_delay: cli.global_delay_milliseconds(),
             latency: cli.latency_milliseconds(),
+            latency_distribution: cli.latency_distribution,
+            request_journal: cli.max_request_journal_entries,
+            // nothing verifies requests received by the cli
+            verified_requests: Some(0),
+            strict: cli.strict,
+            fault: cli.global_fault(),
+            bandwidth: cli.bandwidth,
         }
     }
 }
@@ -145,6 +335,8 @@ impl From<Cli> for Config {
 mod cli_tests {
     use std::{env::current_dir, path::PathBuf};
 
+    use clap::Parser;
+
     use crate::cli::Cli;
 
     #[test]
@@ -192,4 +384,98 @@ mod cli_tests {
         };
         assert_eq!(cli.stubs_dir(), current_dir().unwrap().join(root_dir).join("mappings"))
     }
+
+    #[test]
+    fn global_fault_should_default_to_every_response() {
+        let cli = Cli {
+            fault: Some(stubr::Fault::EmptyResponse),
+            ..Default::default()
+        };
+        let fault = cli.global_fault().unwrap();
+        assert_eq!(fault.fault, stubr::Fault::EmptyResponse);
+        assert_eq!(fault.probability, 1.0);
+        assert!(Cli::default().global_fault().is_none());
+    }
+
+    #[test]
+    fn should_reject_unknown_faults() {
+        let fault = Cli::try_parse_from(["stubr", "--fault", "empty_response"]).unwrap().fault;
+        assert_eq!(fault, Some(stubr::Fault::EmptyResponse));
+        assert!(Cli::try_parse_from(["stubr", "--fault", "EMPTY"]).is_err());
+        assert!(Cli::try_parse_from(["stubr", "--fault-probability", "1.5"]).is_err());
+    }
+
+    #[test]
+    fn bandwidth_should_accept_units() {
+        let bandwidth = |b: &str| Cli::try_parse_from(["stubr", "--bandwidth", b]).map(|cli| cli.bandwidth);
+        assert_eq!(bandwidth("512").unwrap(), Some(512));
+        assert_eq!(bandwidth("512B").unwrap(), Some(512));
+        assert_eq!(bandwidth("10KB").unwrap(), Some(10 * 1024));
+        assert_eq!(bandwidth("1mb").unwrap(), Some(1024 * 1024));
+        assert!(bandwidth("1GB").is_err());
+        assert!(bandwidth("fast").is_err());
+        assert!(bandwidth("KB").is_err());
+    }
+
+    #[test]
+    fn client_auth_should_be_optional_by_default() {
+        assert_eq!(Cli::default().client_auth(), None);
+        let client_auth = |args: &[&str]| Cli::try_parse_from([&["stubr", "--tls"], args].concat()).map(|cli| cli.client_auth());
+        let required = |args: &[&str]| client_auth(args).unwrap().map(|c| c.required);
+        assert_eq!(required(&["--tls-client-auth", "required"]), Some(true));
+        assert_eq!(required(&["--tls-client-auth", "optional"]), Some(false));
+        assert_eq!(required(&["--tls-client-ca", "ca.pem"]), Some(false));
+        assert!(client_auth(&["--tls-client-auth", "reqired"]).is_err());
+    }
+
+    #[test]
+    fn client_auth_should_require_https() {
+        assert!(Cli::try_parse_from(["stubr", "--tls-client-auth", "required"]).is_err());
+        assert!(Cli::try_parse_from(["stubr", "--tls-client-ca", "ca.pem"]).is_err());
+        let provided = [
+            "stubr",
+            "--tls-cert",
+            "cert.pem",
+            "--tls-key",
+            "key.pem",
+            "--tls-client-auth",
+            "required",
+        ];
+        assert!(Cli::try_parse_from(provided).is_ok());
+    }
+
+    #[test]
+    fn tls_should_prefer_provided_certificate() {
+        assert_eq!(Cli::default().tls(), None);
+        let generated = Cli {
+            tls: true,
+            ..Default::default()
+        };
+        assert_eq!(generated.tls(), Some(stubr::Tls::SelfSigned));
+        let provided = Cli {
+            tls: true,
+            tls_cert: Some(PathBuf::from("cert.pem")),
+            tls_key: Some(PathBuf::from("key.pem")),
+            ..Default::default()
+        };
+        assert_eq!(
+            provided.tls(),
+            Some(stubr::Tls::Files {
+                cert: PathBuf::from("cert.pem"),
+                key: PathBuf::from("key.pem"),
+            })
+        );
+    }
+
+    #[test]
+    fn latency_distribution_should_be_parsed_from_json() {
+        let distribution = |d: &str| Cli::try_parse_from(["stubr", "--latency-distribution", d]).map(|cli| cli.latency_distribution);
+        assert_eq!(
+            distribution(r#"{"type": "uniform", "lower": 10, "upper": 50}"#).unwrap(),
+            Some(stubr::DelayDistribution::Uniform { lower: 10, upper: 50 })
+        );
+        assert!(distribution(r#"{"type": "uniform", "lower": 10}"#).is_err());
+        assert!(distribution("10ms").is_err());
+        assert!(Cli::default().latency_distribution.is_none());
+    }
 }
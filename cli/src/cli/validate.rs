@@ -0,0 +1,160 @@
+use std::{
+    env::current_dir,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use colored::Colorize;
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+use stubr::Stubr;
+
+pub struct Validator;
+
+impl Validator {
+    /// Reports every problem of every stub then fails if any
+    /// * `dir` - folder or file containing the stubs. Defaults to current directory
+    pub fn validate(dir: Option<PathBuf>) -> anyhow::Result<()> {
+        let schema = serde_json::from_str::<Value>(stubr::STUB_SCHEMA)?;
+        let compiled = JSONSchema::compile(&schema).map_err(|e| anyhow!("Invalid stub schema because {e}"))?;
+        let dir = dir.map(Ok).unwrap_or_else(current_dir)?;
+        let validations = Stubr::validate(dir.clone());
+        let mut invalid = 0;
+        for validation in &validations {
+            let mut problems = validation.errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+            if let Some(stub) = Self::read(&validation.file) {
+                if let Err(errors) = compiled.validate(&stub) {
+                    problems.extend(errors.map(|e| format!("'{}' {e}", e.instance_path)));
+                }
+                Self::unknown_fields(&stub, &schema, &schema, "", &mut problems);
+            }
+            let file = validation.file.strip_prefix(&dir).unwrap_or(&validation.file);
+            if problems.is_empty() {
+                println!("{} {}", "✓".green(), file.display());
+            } else {
+                invalid += 1;
+                println!("{} {}", "✗".red(), file.display());
+                problems.iter().for_each(|p| println!("    {p}"));
+            }
+        }
+        println!("{} stub(s) checked, {} invalid", validations.len(), invalid);
+        if invalid > 0 {
+            Err(anyhow!("{invalid} invalid stub(s)"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read(file: &Path) -> Option<Value> {
+        File::open(file).ok().and_then(|f| serde_json::from_reader(f).ok())
+    }
+
+    /// The schema does not forbid additional properties since stubs may contain Wiremock fields stubr ignores.
+    /// Reports them so that a typo does not go unnoticed.
+    fn unknown_fields(stub: &Value, schema: &Value, root: &Value, path: &str, problems: &mut Vec<String>) {
+        let schema = Self::resolve(schema, root);
+        let alternatives = ["anyOf", "oneOf", "allOf"]
+            .iter()
+            .filter_map(|k| schema.get(k).and_then(Value::as_array))
+            .flatten()
+            .map(|s| Self::resolve(s, root))
+            .chain(std::iter::once(schema))
+            .collect::<Vec<_>>();
+        match stub {
+            Value::Object(fields) => {
+                if let Some(pattern) = alternatives
+                    .iter()
+                    .find_map(|s| s.get("patternProperties").and_then(Value::as_object))
+                {
+                    for (key, value) in fields {
+                        if let Some(inner) = pattern.values().next() {
+                            Self::unknown_fields(value, inner, root, &format!("{path}/{key}"), problems);
+                        }
+                    }
+                } else if alternatives.iter().all(|s| s.get("additionalProperties").is_none()) {
+                    let declared = alternatives
+                        .iter()
+                        .filter_map(|s| s.get("properties").and_then(Value::as_object))
+                        .collect::<Vec<_>>();
+                    if declared.is_empty() {
+                        return;
+                    }
+                    for (key, value) in fields {
+                        match declared.iter().find_map(|p| p.get(key)) {
+                            Some(inner) => Self::unknown_fields(value, inner, root, &format!("{path}/{key}"), problems),
+                            None => problems.push(format!("'{path}/{key}' is not a known field")),
+                        }
+                    }
+                }
+            },
+            Value::Array(items) => {
+                if let Some(inner) = alternatives.iter().find_map(|s| s.get("items")) {
+                    for (i, item) in items.iter().enumerate() {
+                        Self::unknown_fields(item, inner, root, &format!("{path}/{i}"), problems);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Follows local references e.g. '#/$defs/bodyPatterns'
+    fn resolve<'a>(schema: &'a Value, root: &'a Value) -> &'a Value {
+        schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|pointer| root.pointer(pointer))
+            .unwrap_or(schema)
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn unknown_fields(stub: Value) -> Vec<String> {
+        let schema = serde_json::from_str::<Value>(stubr::STUB_SCHEMA).unwrap();
+        let mut problems = vec![];
+        Validator::unknown_fields(&stub, &schema, &schema, "", &mut problems);
+        problems.sort();
+        problems
+    }
+
+    #[test]
+    fn should_accept_known_fields() {
+        let stub = json!({
+            "request": {
+                "method": "GET",
+                "headers": {"x-a": {"equalTo": "a"}},
+                "bodyPatterns": [{"matchesJsonPath": "$.a"}]
+            },
+            "response": {"status": 200, "jsonBody": {"anything": "goes"}}
+        });
+        assert!(unknown_fields(stub).is_empty());
+    }
+
+    #[test]
+    fn should_report_unknown_fields() {
+        let stub = json!({
+            "request": {
+                "methods": "GET",
+                "headers": {"x-a": {"equalsTo": "a"}},
+                "bodyPatterns": [{"matchesJsonPaths": "$.a"}]
+            },
+            "response": {"status": 200}
+        });
+        assert_eq!(
+            unknown_fields(stub),
+            vec![
+                "'/request/bodyPatterns/0/matchesJsonPaths' is not a known field",
+                "'/request/headers/x-a/equalsTo' is not a known field",
+                "'/request/methods' is not a known field",
+            ]
+        );
+    }
+}
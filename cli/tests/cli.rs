@@ -1,6 +1,8 @@
-use std::time::Duration;
+use std::{process::Command, time::Duration};
 
 use asserhttp::AsserhttpStatus;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
 use surf::{get, post};
 
 use utils::StubrCli;
@@ -35,3 +37,25 @@ async fn should_start_even_without_stubs() {
     std::thread::sleep(Duration::from_millis(500));
     get(format!("{}/healtz", stubr.addr)).await.expect_status_success();
 }
+
+#[test]
+fn should_validate_stubs() {
+    Command::cargo_bin("stubr")
+        .unwrap()
+        .args(["validate", "tests/stubs"])
+        .assert()
+        .success()
+        .stdout(contains("2 stub(s) checked, 0 invalid"));
+}
+
+#[test]
+fn should_fail_validating_invalid_stubs() {
+    Command::cargo_bin("stubr")
+        .unwrap()
+        .args(["validate", "tests/invalid-stubs"])
+        .assert()
+        .failure()
+        .stdout(contains("'/request/methods' is not a known field"))
+        .stdout(contains("regex parse error"))
+        .stdout(contains("1 stub(s) checked, 1 invalid"));
+}
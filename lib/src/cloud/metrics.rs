@@ -0,0 +1,178 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Write},
+    time::Duration,
+};
+
+/// Requests served by the stub server, rendered in the Prometheus text format.
+/// Exposed on '/__admin/metrics' for servers shared by many consumers e.g. the cli or the Docker image
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Requests matched by a stub, by stub name and response status
+    requests: BTreeMap<(String, u16), u64>,
+    unmatched: u64,
+    /// Time to respond to requests matched by a stub, by stub name
+    latencies: BTreeMap<String, Histogram>,
+}
+
+impl Metrics {
+    pub const CONTENT_TYPE: &'static str = "text/plain; version=0.0.4";
+
+    /// * `stub` - name of the stub which matched the request, `None` when none did
+    /// * `status` - status of the response sent, which may not be the stub's one e.g. when proxying
+    /// * `latency` - time to respond, including injected delays and sending the body
+    pub fn observe(&mut self, stub: Option<&str>, status: u16, latency: Duration) {
+        match stub {
+            Some(stub) => {
+                *self.requests.entry((stub.to_string(), status)).or_default() += 1;
+                self.latencies.entry(stub.to_string()).or_default().observe(latency);
+            },
+            None => self.unmatched += 1,
+        }
+    }
+
+    /// * `mounted_stubs` - number of stubs currently mounted on the server
+    pub fn render(&self, mounted_stubs: usize) -> String {
+        let mut out = String::new();
+        // writing into a String never fails
+        let _ = self.write(&mut out, mounted_stubs);
+        out
+    }
+
+    fn write(&self, out: &mut String, mounted_stubs: usize) -> std::fmt::Result {
+        Self::header(
+            out,
+            "stubr_requests_total",
+            "counter",
+            "Requests matched by a stub, by stub and response status",
+        )?;
+        for ((stub, status), count) in &self.requests {
+            writeln!(
+                out,
+                "stubr_requests_total{{stub=\"{}\",status=\"{status}\"}} {count}",
+                Label(stub)
+            )?;
+        }
+        Self::header(out, "stubr_unmatched_requests_total", "counter", "Requests no stub matched")?;
+        writeln!(out, "stubr_unmatched_requests_total {}", self.unmatched)?;
+        Self::header(
+            out,
+            "stubr_request_duration_seconds",
+            "histogram",
+            "Time to respond to requests matched by a stub, including injected delays",
+        )?;
+        for (stub, histogram) in &self.latencies {
+            histogram.write(out, &Label(stub))?;
+        }
+        Self::header(out, "stubr_mounted_stubs", "gauge", "Stubs currently mounted")?;
+        writeln!(out, "stubr_mounted_stubs {mounted_stubs}")
+    }
+
+    fn header(out: &mut String, name: &str, kind: &str, help: &str) -> std::fmt::Result {
+        writeln!(out, "# HELP {name} {help}")?;
+        writeln!(out, "# TYPE {name} {kind}")
+    }
+}
+
+/// Cumulative latency buckets, the default ones of Prometheus clients
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; Histogram::BOUNDS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    /// Upper bounds of the buckets, in seconds
+    const BOUNDS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+    fn observe(&mut self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        Self::BOUNDS
+            .iter()
+            .zip(self.buckets.iter_mut())
+            .filter(|(bound, _)| seconds <= **bound)
+            .for_each(|(_, bucket)| *bucket += 1);
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn write(&self, out: &mut String, stub: &Label) -> std::fmt::Result {
+        for (bound, bucket) in Self::BOUNDS.iter().zip(self.buckets) {
+            writeln!(
+                out,
+                "stubr_request_duration_seconds_bucket{{stub=\"{stub}\",le=\"{bound}\"}} {bucket}"
+            )?;
+        }
+        writeln!(
+            out,
+            "stubr_request_duration_seconds_bucket{{stub=\"{stub}\",le=\"+Inf\"}} {}",
+            self.count
+        )?;
+        writeln!(out, "stubr_request_duration_seconds_sum{{stub=\"{stub}\"}} {}", self.sum)?;
+        writeln!(out, "stubr_request_duration_seconds_count{{stub=\"{stub}\"}} {}", self.count)
+    }
+}
+
+/// Label value escaped as the Prometheus text format requires
+struct Label<'a>(&'a str);
+
+impl Display for Label<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    #[test]
+    fn should_count_requests_by_stub_and_status() {
+        let mut metrics = Metrics::default();
+        metrics.observe(Some("ping"), 200, Duration::from_millis(1));
+        metrics.observe(Some("ping"), 200, Duration::from_millis(1));
+        metrics.observe(Some("ping"), 500, Duration::from_millis(1));
+        metrics.observe(None, 404, Duration::from_millis(1));
+        let rendered = metrics.render(3);
+        assert!(rendered.contains("stubr_requests_total{stub=\"ping\",status=\"200\"} 2\n"));
+        assert!(rendered.contains("stubr_requests_total{stub=\"ping\",status=\"500\"} 1\n"));
+        assert!(rendered.contains("stubr_unmatched_requests_total 1\n"));
+        assert!(rendered.contains("stubr_mounted_stubs 3\n"));
+    }
+
+    #[test]
+    fn should_put_latencies_in_cumulative_buckets() {
+        let mut metrics = Metrics::default();
+        metrics.observe(Some("a"), 200, Duration::from_millis(20));
+        metrics.observe(Some("a"), 200, Duration::from_secs(20));
+        let rendered = metrics.render(1);
+        assert!(rendered.contains("stubr_request_duration_seconds_bucket{stub=\"a\",le=\"0.01\"} 0\n"));
+        assert!(rendered.contains("stubr_request_duration_seconds_bucket{stub=\"a\",le=\"0.025\"} 1\n"));
+        assert!(rendered.contains("stubr_request_duration_seconds_bucket{stub=\"a\",le=\"10\"} 1\n"));
+        assert!(rendered.contains("stubr_request_duration_seconds_bucket{stub=\"a\",le=\"+Inf\"} 2\n"));
+        assert!(rendered.contains("stubr_request_duration_seconds_sum{stub=\"a\"} 20.02\n"));
+        assert!(rendered.contains("stubr_request_duration_seconds_count{stub=\"a\"} 2\n"));
+    }
+
+    #[test]
+    fn should_escape_label_values() {
+        assert_eq!(Label("a \"b\"\\c\nd").to_string(), "a \\\"b\\\"\\\\c\\nd");
+    }
+
+    #[test]
+    fn should_render_empty_metrics() {
+        let rendered = Metrics::default().render(0);
+        assert!(rendered.contains("# TYPE stubr_requests_total counter\n"));
+        assert!(rendered.contains("# TYPE stubr_request_duration_seconds histogram\n"));
+        assert!(rendered.contains("stubr_unmatched_requests_total 0\n"));
+    }
+}
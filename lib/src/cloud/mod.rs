@@ -1,3 +1,4 @@
 pub mod hyper;
+pub mod metrics;
 pub mod opentracing;
 pub mod probe;
@@ -15,5 +15,6 @@ impl HttpProbe {
         Mock::given(method(Method::Get))
             .and(path(Self::PATH))
             .respond_with(ResponseTemplate::new(200))
+            .with_priority(Mock::INTERNAL_PRIORITY)
     }
 }
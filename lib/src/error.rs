@@ -22,6 +22,8 @@ pub enum StubrError {
     #[error(transparent)]
     HandlebarsError(#[from] handlebars::RenderError),
     #[error(transparent)]
+    TemplateError(#[from] Box<handlebars::TemplateError>),
+    #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
@@ -43,6 +45,9 @@ pub enum StubrError {
     IntConversionError(#[from] TryFromIntError),
     #[error(transparent)]
     HttpError(#[from] anyhow::Error),
+    #[cfg(feature = "tls")]
+    #[error(transparent)]
+    CertificateError(#[from] openssl::error::ErrorStack),
     #[error("json path error '{0}'")]
     JsonPathError(String),
     #[error("A request body matcher must contain at least one matcher")]
@@ -57,6 +62,8 @@ pub enum StubrError {
     RecordingError(&'static str),
     #[error("Missing Protobuf file in stub")]
     MissingProtoFile,
+    #[error("Could not find body file at path {0:?}")]
+    BodyFileNotFound(std::path::PathBuf),
     #[error("Could not find protobuf file at path {0:?}")]
     ProtobufFileNotFound(std::path::PathBuf),
     #[error("Provided protobuf message '{0}' not found in file {1:?}")]
@@ -67,6 +74,34 @@ pub enum StubrError {
     InvalidGrpcRequest,
     #[error("Could not convert file {0:?} name to utf-8 string")]
     FileNameError(std::path::PathBuf),
+    #[error("Invalid mapping because {0}")]
+    InvalidMapping(&'static str),
+    #[error("No stub directory to save mappings into")]
+    MissingMappingsDir,
+    #[error("Unknown fault '{0}'")]
+    UnknownFault(String),
+    #[error("No stub belongs to scenario '{0}'")]
+    UnknownScenario(String),
+    #[error("A stub with id '{0}' is already mounted")]
+    DuplicateStubId(String),
+    #[error("No stub with id '{0}'")]
+    UnknownStub(String),
+    #[error("{0}")]
+    VerificationFailed(Box<crate::server::admin::verification::VerificationReport>),
+    #[error("Invalid response sequence because {0}")]
+    InvalidResponseSequence(&'static str),
+    #[error("Invalid WebSocket stub because {0}")]
+    InvalidWebSocket(&'static str),
+    #[error("Invalid resource stub because {0}")]
+    InvalidResource(&'static str),
+    #[error("Could not find resource seed file at path {0:?}")]
+    ResourceSeedNotFound(std::path::PathBuf),
+    #[error("Could not find dataset at path {0:?}")]
+    DatasetNotFound(std::path::PathBuf),
+    #[error("Invalid dataset {0:?}, expected a CSV file with a header or a json array of objects")]
+    InvalidDataset(std::path::PathBuf),
+    #[error("{} stub(s) could not be loaded:{}", .0.len(), .0.iter().map(|(file, e)| format!("\n  {file:?}: {e}")).collect::<String>())]
+    RejectedStubs(Vec<(std::path::PathBuf, StubrError)>),
 }
 
 impl From<StubrError> for handlebars::RenderError {
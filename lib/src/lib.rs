@@ -59,7 +59,7 @@
 //! A [`Stubr`] server can be configured globally thanks to [`Config`] struct.
 //!
 //! ```
-//! use stubr::Config;
+//! use stubr::{Config, DelayDistribution, Fault, GlobalFault};
 //! let config = Config {
 //!     // server port, defaults to random
 //!     port: Some(8080),
@@ -69,13 +69,42 @@
 //!     global_delay: Some(2000),
 //!     // delay in milliseconds added to any locally defined one. Simulates network latencies.
 //!     latency: Some(2000),
+//!     // random delay added to any locally defined one. Here between 10 and 50 milliseconds
+//!     latency_distribution: Some(DelayDistribution::Uniform { lower: 10, upper: 50 }),
 //!     // Enables verification via https://docs.rs/wiremock/latest/wiremock/struct.Mock.html#method.expect
 //!     verify: true,
+//!     // keeps the last 100 received requests. Disabled by default
+//!     request_journal: Some(100),
+//!     // requests kept for verifications, 10 000 by default
+//!     verified_requests: Some(1000),
+//!     // fails startup on any invalid stub instead of skipping it
+//!     strict: false,
+//!     // breaks 10% of the responses
+//!     fault: Some(GlobalFault { fault: Fault::ConnectionResetByPeer, probability: 0.1 }),
+//!     // streams response bodies at 1KB per second
+//!     bandwidth: Some(1024),
+//! };
+//! ```
+//!
+//! Settings holding urls or files are in a separate [`NetworkConfig`], given along with the [`Config`] to
+//! [`Stubr::try_start_with_network`].
+//!
+//! ```
+//! use stubr::{ClientAuth, NetworkConfig, RecordOnMiss, Tls};
+//! let network = NetworkConfig {
+//!     // forwards requests no stub matches to this server then records them as new stubs
+//!     record_on_miss: Some(RecordOnMiss { upstream: "http://localhost:8080".to_string(), output: None }),
+//!     // serves stubs over https with a generated certificate, requires the 'tls' feature
+//!     tls: Some(Tls::SelfSigned),
+//!     // asks clients for a certificate, without requiring one
+//!     client_auth: Some(ClientAuth { ca: None, required: false }),
 //! };
 //! ```
 //!
 //! [`Config`]: Config
+//! [`NetworkConfig`]: NetworkConfig
 //! [`Stubr`]: Stubr
+//! [`Stubr::try_start_with_network`]: Stubr::try_start_with_network
 
 #[macro_use]
 extern crate lazy_static;
@@ -92,7 +121,17 @@ pub use record::core::Record;
 pub use record::record_client::actix::{ActixRecord, ActixRecordMiddleware};
 #[cfg(feature = "record-standalone")]
 pub use record::standalone::StubrRecord;
-pub use server::{config::Config, Stubr};
+pub use server::{
+    admin::{
+        journal::LoggedRequest,
+        near_miss::{MatcherDiff, NearMiss, NearMissReport, RequestPart},
+        reload::StubsReload,
+        verification::{NearMatch, RequestCount, RequestPattern, VerificationReport},
+    },
+    config::{Config, GlobalFault, NetworkConfig, RecordOnMiss},
+    validate::StubValidation,
+    Stubr,
+};
 #[cfg(all(feature = "attributes", feature = "iso"))]
 pub use stubr_attributes::iso;
 #[cfg(all(feature = "record-standalone", feature = "attributes"))]
@@ -108,7 +147,15 @@ pub use verify::{StubrVerify, VerifyExcept};
 #[cfg(feature = "wiremock")]
 pub use wiremock_java::{WiremockExt, WiremockImage};
 
+#[cfg(feature = "tls")]
+pub use server::config::{ClientAuth, Tls};
+
 pub use error::{StubrError, StubrResult};
+pub use model::request::RequestStub;
+
+/// Json schema stub files conform to, a copy of 'schemas/stubr.schema.json' shipped with the crate
+pub const STUB_SCHEMA: &str = include_str!("stubr.schema.json");
+pub use wiremock_rs::{client_cert::ClientCertificate, delay::DelayDistribution, fault::Fault};
 
 mod cloud;
 pub mod error;
@@ -91,6 +91,16 @@ impl GrpcResponseStub {
         Ok(buf)
     }
 
+    /// Response templates which do not compile
+    pub(crate) fn validate(&self) -> Vec<StubrError> {
+        if self.requires_response_templating() {
+            let templates = self.body.iter().flat_map(crate::model::response::template::json_strings);
+            crate::model::response::template::compile_errors(templates)
+        } else {
+            vec![]
+        }
+    }
+
     pub(crate) fn requires_response_templating(&self) -> bool {
         const RESPONSE_TEMPLATE: &str = "response-template";
         self.transformers.iter().any(|it| it == RESPONSE_TEMPLATE)
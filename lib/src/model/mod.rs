@@ -6,18 +6,29 @@ use std::{
 
 use request::RequestStub;
 use response::{
+    sequence::{ResponseSequence, SequenceMode, SequencedResponseStub},
     template::{HandlebarTemplatable, StubTemplate},
     ResponseStub,
 };
 
 use crate::error::{StubrError, StubrResult};
+use crate::server::admin::scenario::Scenario;
 use crate::wiremock_rs::{Mock, MockBuilder, Respond, ResponseTemplate};
 use crate::Config;
+use request::MockRegistrable;
+use resource::{ResourceRespond, ResourceStub};
+use webhook::{PostServeActionStub, WebhookStub};
+#[cfg(feature = "websocket")]
+use websocket::{WebSocketRespond, WebSocketStub};
 
 #[cfg(feature = "grpc")]
 pub mod grpc;
 pub mod request;
+pub mod resource;
 pub mod response;
+pub mod webhook;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -30,10 +41,36 @@ pub struct JsonStub {
     pub priority: Option<u8>,
     #[serde(skip_serializing)]
     pub expect: Option<u32>,
+    #[serde(skip_serializing)]
+    pub scenario_name: Option<String>,
+    #[serde(skip_serializing)]
+    pub required_scenario_state: Option<String>,
+    #[serde(skip_serializing)]
+    pub new_scenario_state: Option<String>,
     #[serde(rename = "request")]
     pub http_request: Option<RequestStub>,
     #[serde(rename = "response")]
     pub http_response: Option<ResponseStub>,
+    /// Served to successive matching requests instead of a single `response`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responses: Vec<SequencedResponseStub>,
+    /// How `responses` are picked, one after the other by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_mode: Option<SequenceMode>,
+    /// Performed once the stub has responded e.g. webhooks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_serve_actions: Vec<PostServeActionStub>,
+    /// Upgrades the connection matching `request` then holds a scripted conversation instead of responding
+    #[cfg(feature = "websocket")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_socket: Option<WebSocketStub>,
+    /// Kept only to reject the stub, instead of serving a plain response to the upgrade request
+    #[cfg(not(feature = "websocket"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_socket: Option<serde_json::Value>,
+    /// Serves a collection with in-memory persistence instead of responding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ResourceStub>,
     #[cfg(feature = "grpc")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grpc_request: Option<grpc::request::GrpcRequestStub>,
@@ -49,8 +86,22 @@ pub struct JsonStub {
 impl JsonStub {
     pub const DEFAULT_PRIORITY: u8 = 5;
 
+    /// Priority of the mock serving this stub. Stubs without priority come first, as they always have been
+    /// mounted before the others, then stub priority `p` becomes `p + 1`. It is capped at 254 so that stubs
+    /// always take precedence over the mocks stubr registers itself e.g. the health probe, at [Mock::INTERNAL_PRIORITY].
+    /// [Self::DEFAULT_PRIORITY] is only the one recorded stubs are written with
+    pub(crate) fn mock_priority(&self) -> u8 {
+        self.priority
+            .map(|p| p.saturating_add(1).min(Mock::INTERNAL_PRIORITY - 1))
+            .unwrap_or(1)
+    }
+
     fn is_http(&self) -> bool {
-        self.http_request.is_some() || self.http_response.is_some()
+        self.http_request.is_some()
+            || self.http_response.is_some()
+            || !self.responses.is_empty()
+            || self.web_socket.is_some()
+            || self.resource.is_some()
     }
 
     #[cfg(feature = "grpc")]
@@ -58,30 +109,84 @@ impl JsonStub {
         self.grpc_request.is_some() || self.grpc_response.is_some()
     }
 
+    fn scenario(&self) -> Option<Scenario> {
+        self.scenario_name.as_ref().map(|name| Scenario {
+            name: name.to_string(),
+            required_state: self.required_scenario_state.clone(),
+            new_state: self.new_scenario_state.clone(),
+        })
+    }
+
+    fn webhooks(&self) -> impl Iterator<Item = &WebhookStub> {
+        self.post_serve_actions.iter().map(|action| match action {
+            PostServeActionStub::Webhook(webhook) => webhook,
+        })
+    }
+
     #[cfg(feature = "grpc")]
     pub fn proto_file(&self) -> Option<&PathBuf> {
         self.proto_file.as_ref().filter(|f| f.exists())
     }
 
+    /// Problems of the response which mounting the stub does not detect
+    pub(crate) fn validate(&self) -> Vec<StubrError> {
+        let mut errors = self
+            .http_response
+            .iter()
+            .chain(self.responses.iter().map(|r| &r.response))
+            .flat_map(ResponseStub::validate)
+            .collect::<Vec<_>>();
+        errors.extend(self.http_request.as_ref().and_then(|req| req.try_validate().err()));
+        errors.extend(self.webhooks().flat_map(WebhookStub::validate));
+        #[cfg(feature = "grpc")]
+        errors.extend(self.grpc_response.iter().flat_map(grpc::response::GrpcResponseStub::validate));
+        errors
+    }
+
     pub(crate) fn try_creating_from(self, config: &Config, file: &std::path::Path) -> StubrResult<Mock> {
         let expect = self.expect;
+        let scenario = self.scenario();
+        let priority = self.mock_priority();
         if self.is_http() {
             let req = self.http_request.clone().unwrap_or_default();
-            let mut mock = MockBuilder::try_from(&req)?.respond_with(self.into_respond(config)?);
+            if let Err(e) = req.try_validate() {
+                if config.strict {
+                    return Err(e);
+                }
+                log::warn!("Ignoring an invalid matcher of stub {file:?} because {e}");
+            }
+            let mock = MockBuilder::try_from(&req)?;
+            let mut mock = if let Some(resource) = self.resource.as_ref() {
+                resource.register(mock).respond_with(self.try_into_resource(resource, file)?)
+            } else if let Some(web_socket) = self.web_socket.as_ref() {
+                mock.respond_with(self.try_into_web_socket(web_socket)?)
+            } else if self.responses.is_empty() {
+                mock.respond_with(self.into_respond(config)?)
+            } else {
+                mock.respond_with(self.try_into_sequence(config)?)
+            }
+            .with_priority(priority);
             if let (true, Some(expect)) = (config.verify, expect) {
                 mock = mock.expect(expect as u64);
             }
+            if let Some(scenario) = scenario {
+                mock = mock.in_scenario(scenario);
+            }
             Ok(mock)
         } else {
             #[cfg(feature = "grpc")]
             {
                 if self.is_grpc() {
                     let req = self.grpc_request.clone().unwrap_or_default();
-                    let mut mock =
-                        grpc::request::GrpcRequestStub::try_new(&req, self.proto_file())?.respond_with(self.into_respond(config)?);
+                    let mut mock = grpc::request::GrpcRequestStub::try_new(&req, self.proto_file())?
+                        .respond_with(self.into_respond(config)?)
+                        .with_priority(priority);
                     if let (true, Some(expect)) = (config.verify, expect) {
                         mock = mock.expect(expect as u64);
                     }
+                    if let Some(scenario) = scenario {
+                        mock = mock.in_scenario(scenario);
+                    }
                     return Ok(mock);
                 }
             }
@@ -118,28 +223,73 @@ impl JsonStub {
         }
     }
 
+    #[cfg(feature = "websocket")]
+    fn try_into_web_socket(&self, web_socket: &WebSocketStub) -> StubrResult<WebSocketRespond> {
+        if self.http_response.is_some() || !self.responses.is_empty() {
+            return Err(StubrError::InvalidWebSocket(
+                "a stub cannot have both 'webSocket' and 'response' or 'responses'",
+            ));
+        }
+        Ok(WebSocketRespond(web_socket.try_into()?))
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    fn try_into_web_socket(&self, _: &serde_json::Value) -> StubrResult<ResponseTemplate> {
+        Err(StubrError::InvalidWebSocket("the 'websocket' feature is not turned on"))
+    }
+
+    fn try_into_resource(&self, resource: &ResourceStub, file: &std::path::Path) -> StubrResult<ResourceRespond> {
+        if self.http_response.is_some() || !self.responses.is_empty() || self.web_socket.is_some() {
+            return Err(StubrError::InvalidResource(
+                "a stub cannot have both 'resource' and 'response', 'responses' or 'webSocket'",
+            ));
+        }
+        ResourceRespond::try_new(resource, file)
+    }
+
+    fn try_into_sequence(self, config: &Config) -> StubrResult<ResponseSequence> {
+        if self.http_response.is_some() {
+            return Err(StubrError::InvalidResponseSequence(
+                "a stub cannot have both 'response' and 'responses'",
+            ));
+        }
+        let responses = self
+            .responses
+            .iter()
+            .map(|r| (self.http_respond(r.response.clone(), config), r.weight))
+            .collect();
+        ResponseSequence::try_new(responses, self.sequence_mode.unwrap_or_default())
+    }
+
     fn http_respond(&self, resp: ResponseStub, config: &Config) -> StubTemplate {
         use crate::model::response::ResponseAppender as _;
 
         let mut template = ResponseTemplate::new(resp.status());
         template = response::default::WiremockIsoResponse(self.uuid.as_deref()).add(template);
         template = response::delay::Delay(resp.fixed_delay_milliseconds, &resp.delay_distribution, config).add(template);
+        template = response::delay::Dribble(resp.chunked_dribble_delay, config).add(template);
+        template = response::fault::StubFault(resp.fault, config).add(template);
+        template = resp.proxy.add(template);
         if resp.requires_response_templating() {
             resp.headers.register_template();
             resp.body.register_template();
+            resp.stream.register_template();
             StubTemplate {
                 template,
                 response: Some(resp),
                 requires_templating: true,
+                webhooks: self.webhooks().cloned().collect(),
                 ..Default::default()
             }
         } else {
             template = resp.headers.add(template);
             template = resp.body.add(template);
+            template = resp.stream.add(template);
             StubTemplate {
                 template,
                 response: Some(resp),
                 requires_templating: false,
+                webhooks: self.webhooks().cloned().collect(),
                 ..Default::default()
             }
         }
@@ -152,9 +302,12 @@ impl JsonStub {
         let mut template = ResponseTemplate::new(resp.status());
         template = response::default::WiremockIsoResponse(self.uuid.as_deref()).add(template);
         template = response::delay::Delay(resp.fixed_delay_milliseconds, &resp.delay_distribution, config).add(template);
+        template = response::delay::Dribble(resp.chunked_dribble_delay, config).add(template);
+        template = response::fault::StubFault(resp.fault, config).add(template);
         StubTemplate {
             template,
             requires_templating: false,
+            webhooks: self.webhooks().cloned().collect(),
             ..Default::default()
         }
     }
@@ -223,8 +376,16 @@ impl Default for JsonStub {
             uuid: Option::default(),
             priority: Some(Self::DEFAULT_PRIORITY),
             expect: Option::default(),
+            scenario_name: Option::default(),
+            required_scenario_state: Option::default(),
+            new_scenario_state: Option::default(),
             http_request: Option::default(),
             http_response: Option::default(),
+            responses: Vec::default(),
+            sequence_mode: Option::default(),
+            post_serve_actions: Vec::default(),
+            web_socket: None,
+            resource: None,
             #[cfg(feature = "grpc")]
             grpc_request: Option::default(),
             #[cfg(feature = "grpc")]
@@ -1,5 +1,6 @@
 use std::hash::{Hash, Hasher};
 
+use crate::error::{StubrError, StubrResult};
 use crate::wiremock_rs::MockBuilder;
 use serde_json::Value;
 
@@ -70,6 +71,14 @@ impl BodyMatcherStub {
         self.binary_equal_to.is_some()
     }
 
+    /// Fails when 'matchesJsonPath' or 'expression' is not a valid json path
+    pub fn try_validate(&self) -> StubrResult<()> {
+        for json_path in [&self.matches_json_path, &self.expression].into_iter().flatten() {
+            jsonpath_lib::Compiled::compile(json_path).map_err(StubrError::JsonPathError)?;
+        }
+        Ok(())
+    }
+
     pub fn is_ignore_extra_elements(&self) -> bool {
         self.ignore_extra_elements.unwrap_or_default()
     }
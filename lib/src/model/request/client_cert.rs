@@ -0,0 +1,182 @@
+use regex::Regex;
+
+use crate::{
+    error::StubrResult,
+    wiremock_rs::{client_cert::ClientCertificate, Match, MockBuilder, Request},
+};
+
+use super::MockRegistrable;
+
+/// Matches the certificate a client presented over mutual TLS
+#[derive(Debug, Clone, Default, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ClientCertStub {
+    /// When true, matches requests sent without certificate. When false, requests with any certificate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub absent: Option<bool>,
+    /// Subject distinguished name e.g. 'CN=client, O=stubr'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<CertValueStub>,
+    /// Matches when any of the subject alternative names does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub san: Option<CertValueStub>,
+    /// SHA-256 fingerprint, hex encoded with or without colons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CertValueStub {
+    EqualTo(String),
+    Contains(String),
+    Matches(String),
+}
+
+impl ClientCertStub {
+    /// Fails when a regex does not compile
+    pub fn try_validate(&self) -> StubrResult<()> {
+        for value in [&self.subject, &self.san].into_iter().flatten() {
+            if let CertValueStub::Matches(regex) = value {
+                Regex::new(regex)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MockRegistrable for ClientCertStub {
+    fn register(&self, mock: MockBuilder) -> MockBuilder {
+        mock.and(ClientCertMatcher::from(self))
+    }
+}
+
+enum CertValueMatcher {
+    EqualTo(String),
+    Contains(String),
+    /// `None` when the regex is invalid, which the stub validation reports beforehand
+    Matches(Option<Regex>),
+}
+
+impl CertValueMatcher {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::EqualTo(expected) => value == expected,
+            Self::Contains(expected) => value.contains(expected.as_str()),
+            Self::Matches(regex) => regex.as_ref().map(|r| r.is_match(value)).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&CertValueStub> for CertValueMatcher {
+    fn from(stub: &CertValueStub) -> Self {
+        match stub {
+            CertValueStub::EqualTo(v) => Self::EqualTo(v.to_string()),
+            CertValueStub::Contains(v) => Self::Contains(v.to_string()),
+            CertValueStub::Matches(v) => Self::Matches(Regex::new(v).ok()),
+        }
+    }
+}
+
+pub struct ClientCertMatcher {
+    absent: bool,
+    subject: Option<CertValueMatcher>,
+    san: Option<CertValueMatcher>,
+    fingerprint: Option<String>,
+}
+
+impl ClientCertMatcher {
+    fn matches_cert(&self, cert: &ClientCertificate) -> bool {
+        let subject = self.subject.as_ref().map(|m| m.matches(&cert.subject)).unwrap_or(true);
+        let san = self
+            .san
+            .as_ref()
+            .map(|m| cert.sans.iter().any(|san| m.matches(san)))
+            .unwrap_or(true);
+        let fingerprint = self.fingerprint.as_ref().map(|f| f == &cert.fingerprint).unwrap_or(true);
+        subject && san && fingerprint
+    }
+}
+
+impl Match for ClientCertMatcher {
+    fn matches(&self, req: &Request) -> bool {
+        match req.client_certificate.as_ref() {
+            Some(cert) => !self.absent && self.matches_cert(cert),
+            None => self.absent,
+        }
+    }
+}
+
+impl From<&ClientCertStub> for ClientCertMatcher {
+    fn from(stub: &ClientCertStub) -> Self {
+        Self {
+            absent: stub.absent.unwrap_or_default(),
+            subject: stub.subject.as_ref().map(CertValueMatcher::from),
+            san: stub.san.as_ref().map(CertValueMatcher::from),
+            fingerprint: stub.fingerprint.as_deref().map(ClientCertificate::normalize_fingerprint),
+        }
+    }
+}
+
+#[cfg(test)]
+mod client_cert_tests {
+    use std::collections::HashMap;
+
+    use http_types::Method;
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(client_certificate: Option<ClientCertificate>) -> Request {
+        Request {
+            url: "https://localhost/".parse().unwrap(),
+            method: Method::Get,
+            headers: HashMap::new(),
+            body: vec![],
+            version: http_types::Version::Http1_1,
+            client_certificate,
+        }
+    }
+
+    fn cert() -> Option<ClientCertificate> {
+        Some(ClientCertificate {
+            subject: "CN=client, O=stubr".to_string(),
+            sans: vec!["client.example.com".to_string(), "127.0.0.1".to_string()],
+            fingerprint: "ab01cd".to_string(),
+        })
+    }
+
+    fn matcher(stub: serde_json::Value) -> ClientCertMatcher {
+        ClientCertMatcher::from(&serde_json::from_value::<ClientCertStub>(stub).unwrap())
+    }
+
+    #[test]
+    fn should_match_subject() {
+        assert!(matcher(json!({"subject": {"equalTo": "CN=client, O=stubr"}})).matches(&request(cert())));
+        assert!(matcher(json!({"subject": {"contains": "CN=client"}})).matches(&request(cert())));
+        assert!(matcher(json!({"subject": {"matches": "^CN=cl.*"}})).matches(&request(cert())));
+        assert!(!matcher(json!({"subject": {"contains": "CN=other"}})).matches(&request(cert())));
+    }
+
+    #[test]
+    fn should_match_any_san() {
+        assert!(matcher(json!({"san": {"equalTo": "127.0.0.1"}})).matches(&request(cert())));
+        assert!(!matcher(json!({"san": {"equalTo": "other.example.com"}})).matches(&request(cert())));
+    }
+
+    #[test]
+    fn should_match_fingerprint_with_or_without_colons() {
+        assert!(matcher(json!({"fingerprint": "AB:01:CD"})).matches(&request(cert())));
+        assert!(matcher(json!({"fingerprint": "ab01cd"})).matches(&request(cert())));
+        assert!(!matcher(json!({"fingerprint": "ab01ce"})).matches(&request(cert())));
+    }
+
+    #[test]
+    fn should_match_absent_certificate() {
+        assert!(matcher(json!({"absent": true})).matches(&request(None)));
+        assert!(!matcher(json!({"absent": true})).matches(&request(cert())));
+        assert!(matcher(json!({"absent": false})).matches(&request(cert())));
+        assert!(!matcher(json!({"absent": false})).matches(&request(None)));
+        assert!(!matcher(json!({"subject": {"contains": "CN"}})).matches(&request(None)));
+    }
+}
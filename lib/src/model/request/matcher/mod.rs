@@ -98,6 +98,20 @@ impl RequestMatcherStub {
             .and_then(|it| Regex::from_str(it).ok())
     }
 
+    /// Fails when 'matches' or 'doesNotMatch' is not a valid regex
+    pub fn try_validate(&self) -> StubrResult<()> {
+        if let Some(value) = self.value.as_ref() {
+            for regex in [&value.matches, &value.does_not_match]
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                Regex::from_str(regex)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn is_absent(&self) -> bool {
         self.value.as_ref().map(|v| v.absent.is_some()).unwrap_or_default()
     }
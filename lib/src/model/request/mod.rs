@@ -1,24 +1,31 @@
 use crate::wiremock_rs::MockBuilder;
+use regex::Regex;
 
 use crate::error::{StubrError, StubrResult};
 use body::BodyMatcherStub;
+use client_cert::ClientCertStub;
 use headers::HttpReqHeadersStub;
 use method::HttpMethodStub;
+use protocol::HttpProtocolStub;
 use query::HttpQueryParamsStub;
+use state::StateMatcherStub;
 use url::HttpUrlStub;
 
 use crate::model::request::auth::AuthStub;
 
 pub mod auth;
 pub mod body;
+pub mod client_cert;
 pub mod headers;
 pub mod json;
 pub mod matcher;
 pub mod method;
+pub mod protocol;
 pub mod query;
+pub mod state;
 pub mod url;
 
-#[derive(Debug, Clone, Default, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct RequestStub {
     #[serde(default)]
@@ -33,11 +40,40 @@ pub struct RequestStub {
     pub body_patterns: Vec<BodyMatcherStub>,
     #[serde(flatten, skip_serializing)]
     pub auth: AuthStub,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<ClientCertStub>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<HttpProtocolStub>,
+    /// Condition on the values response templates stored in the server's state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<StateMatcherStub>,
+}
+
+impl std::hash::Hash for RequestStub {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.method.hash(state);
+        self.url.hash(state);
+        self.headers.hash(state);
+        self.queries.hash(state);
+        self.body_patterns.hash(state);
+        self.auth.hash(state);
+        // recorded stubs are named after this hash, it must not change for stubs without those matchers
+        if let Some(it) = self.client_certificate.as_ref() {
+            it.hash(state);
+        }
+        if let Some(it) = self.protocol.as_ref() {
+            it.hash(state);
+        }
+        if let Some(it) = self.state.as_ref() {
+            it.hash(state);
+        }
+    }
 }
 
 impl TryFrom<&RequestStub> for MockBuilder {
     type Error = StubrError;
 
+    /// Invalid matchers are ignored, check them beforehand with [RequestStub::try_validate]
     fn try_from(request: &RequestStub) -> StubrResult<Self> {
         let mut mock = MockBuilder::from(&request.method);
         mock = request.url.register(mock);
@@ -45,10 +81,44 @@ impl TryFrom<&RequestStub> for MockBuilder {
         mock = request.queries.register(mock);
         mock = request.body_patterns.register(mock);
         mock = request.auth.register(mock);
+        if let Some(client_certificate) = request.client_certificate.as_ref() {
+            mock = client_certificate.register(mock);
+        }
+        if let Some(protocol) = request.protocol.as_ref() {
+            mock = protocol.register(mock);
+        }
+        if let Some(state) = request.state.as_ref() {
+            mock = state.register(mock);
+        }
         Ok(mock)
     }
 }
 
+impl RequestStub {
+    /// Fails on the first regex or json path which does not compile.
+    /// Matchers otherwise ignore them, which would make the stub match more than intended
+    pub fn try_validate(&self) -> StubrResult<()> {
+        for pattern in [&self.url.url_pattern, &self.url.url_path_pattern].into_iter().flatten() {
+            Regex::new(pattern)?;
+        }
+        let headers = self.headers.get_headers().into_iter().flatten();
+        let queries = self.queries.get_queries().into_iter().flatten();
+        for matcher in headers.chain(queries) {
+            matcher.try_validate()?;
+        }
+        for body in &self.body_patterns {
+            body.try_validate()?;
+        }
+        if let Some(client_certificate) = self.client_certificate.as_ref() {
+            client_certificate.try_validate()?;
+        }
+        if let Some(state) = self.state.as_ref() {
+            state.try_validate()?;
+        }
+        Ok(())
+    }
+}
+
 /// Normalizes appending a struct into a Mock
 pub trait MockRegistrable {
     fn register(&self, mock: MockBuilder) -> MockBuilder;
@@ -0,0 +1,80 @@
+use http_types::Version;
+
+use crate::wiremock_rs::{Match, MockBuilder, Request};
+
+use super::MockRegistrable;
+
+/// HTTP version the request has been sent with
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum HttpProtocolStub {
+    #[serde(rename = "HTTP/1.0")]
+    Http1_0,
+    #[serde(rename = "HTTP/1.1")]
+    Http1_1,
+    #[serde(rename = "HTTP/2")]
+    Http2,
+}
+
+impl From<HttpProtocolStub> for Version {
+    fn from(protocol: HttpProtocolStub) -> Self {
+        match protocol {
+            HttpProtocolStub::Http1_0 => Self::Http1_0,
+            HttpProtocolStub::Http1_1 => Self::Http1_1,
+            HttpProtocolStub::Http2 => Self::Http2_0,
+        }
+    }
+}
+
+impl MockRegistrable for HttpProtocolStub {
+    fn register(&self, mock: MockBuilder) -> MockBuilder {
+        mock.and(ProtocolMatcher((*self).into()))
+    }
+}
+
+pub struct ProtocolMatcher(Version);
+
+impl Match for ProtocolMatcher {
+    fn matches(&self, req: &Request) -> bool {
+        req.version == self.0
+    }
+}
+
+#[cfg(test)]
+mod protocol_tests {
+    use std::collections::HashMap;
+
+    use http_types::Method;
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(version: Version) -> Request {
+        Request {
+            url: "http://localhost/".parse().unwrap(),
+            method: Method::Get,
+            headers: HashMap::new(),
+            body: vec![],
+            version,
+            client_certificate: None,
+        }
+    }
+
+    fn matcher(protocol: &str) -> ProtocolMatcher {
+        let stub = serde_json::from_value::<HttpProtocolStub>(json!(protocol)).unwrap();
+        ProtocolMatcher(stub.into())
+    }
+
+    #[test]
+    fn should_match_exact_protocol() {
+        assert!(matcher("HTTP/2").matches(&request(Version::Http2_0)));
+        assert!(!matcher("HTTP/2").matches(&request(Version::Http1_1)));
+        assert!(matcher("HTTP/1.1").matches(&request(Version::Http1_1)));
+        assert!(!matcher("HTTP/1.1").matches(&request(Version::Http1_0)));
+    }
+
+    #[test]
+    fn should_fail_when_unknown_protocol() {
+        assert!(serde_json::from_value::<HttpProtocolStub>(json!("HTTP/3")).is_err());
+        assert!(serde_json::from_value::<HttpProtocolStub>(json!("h2")).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    model::response::template::{
+        data::{HandlebarsData, RequestData},
+        HANDLEBARS,
+    },
+    server::admin::state::StateStore,
+    wiremock_rs::{Match, MockBuilder, Request},
+    StubrResult,
+};
+
+use super::MockRegistrable;
+
+/// Matches depending on what response templates stored in the server's state
+#[derive(Debug, Clone, Default, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateMatcherStub {
+    pub context: String,
+    /// Supports response templating e.g. '{{request.pathSegments.[1]}}'. Any key of the context when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// When true, matches when there is no value instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub absent: Option<bool>,
+}
+
+impl StateMatcherStub {
+    /// Fails when the key template does not compile
+    pub fn try_validate(&self) -> StubrResult<()> {
+        match super::super::response::template::compile_errors(self.key.as_deref().into_iter()).pop() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl MockRegistrable for StateMatcherStub {
+    fn register(&self, mock: MockBuilder) -> MockBuilder {
+        mock.and(StateMatcher(self.clone()))
+    }
+}
+
+#[derive(Debug)]
+pub struct StateMatcher(StateMatcherStub);
+
+impl StateMatcher {
+    pub(crate) fn new(stub: StateMatcherStub) -> Self {
+        Self(stub)
+    }
+
+    /// What the server's state holds for this matcher: the value of the key or, without key, the context when not empty
+    pub(crate) fn stored(&self, req: &Request) -> Option<String> {
+        StateStore::with_current(|state| match self.key(req) {
+            Some(key) => state.get(&self.0.context, &key).map(|value| value.to_string()),
+            None => state
+                .contains(&self.0.context, None)
+                .then(|| format!("values in '{}'", self.0.context)),
+        })
+        .flatten()
+    }
+
+    fn key(&self, req: &Request) -> Option<String> {
+        let key = self.0.key.as_deref()?;
+        if !key.contains("{{") {
+            return Some(key.to_string());
+        }
+        let data = HandlebarsData {
+            request: &RequestData::from(req),
+            response: None,
+            stub_name: None,
+            is_verify: false,
+        };
+        HANDLEBARS.read().ok()?.render_template(key, &data).ok()
+    }
+}
+
+impl Match for StateMatcher {
+    fn matches(&self, req: &Request) -> bool {
+        let key = self.key(req);
+        let exists = StateStore::with_current(|state| state.contains(&self.0.context, key.as_deref())).unwrap_or_default();
+        exists != self.0.absent.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod state_matcher_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(path: &str) -> Request {
+        Request {
+            url: format!("http://localhost{path}").parse().unwrap(),
+            method: http_types::Method::Get,
+            headers: Default::default(),
+            body: vec![],
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    fn matcher(json: serde_json::Value) -> StateMatcher {
+        StateMatcher(serde_json::from_value(json).unwrap())
+    }
+
+    #[test]
+    fn should_match_when_templated_key_exists() {
+        let store = StateStore::default();
+        store.set("user", "1", json!("john"));
+        let _scope = store.enter();
+        let exists = matcher(json!({ "context": "user", "key": "{{request.pathSegments.[1]}}" }));
+        assert!(exists.matches(&request("/users/1")));
+        assert!(!exists.matches(&request("/users/2")));
+    }
+
+    #[test]
+    fn should_match_when_absent() {
+        let store = StateStore::default();
+        store.set("user", "1", json!("john"));
+        let _scope = store.enter();
+        let absent = matcher(json!({ "context": "user", "key": "2", "absent": true }));
+        assert!(absent.matches(&request("/users")));
+        assert!(!matcher(json!({ "context": "user", "absent": true })).matches(&request("/users")));
+        assert!(matcher(json!({ "context": "user" })).matches(&request("/users")));
+    }
+
+    #[test]
+    fn should_describe_stored_values() {
+        let store = StateStore::default();
+        store.set("user", "1", json!("john"));
+        let _scope = store.enter();
+        let stored = |json| matcher(json).stored(&request("/users/1"));
+        assert_eq!(stored(json!({ "context": "user", "key": "1" })).as_deref(), Some("\"john\""));
+        assert_eq!(stored(json!({ "context": "user", "key": "2" })), None);
+        assert_eq!(stored(json!({ "context": "user" })).as_deref(), Some("values in 'user'"));
+        assert_eq!(stored(json!({ "context": "order" })), None);
+    }
+
+    #[test]
+    fn should_not_match_without_state() {
+        assert!(!matcher(json!({ "context": "user" })).matches(&request("/users")));
+    }
+
+    #[test]
+    fn should_fail_when_invalid_key_template() {
+        let stub: StateMatcherStub = serde_json::from_value(json!({ "context": "user", "key": "{{request.path" })).unwrap();
+        assert!(stub.try_validate().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::wiremock_rs::matchers::{path_regex, PathRegexMatcher};
 use crate::{StubrError, StubrResult};
+use regex::Regex;
 
 use super::HttpUrlStub;
 
@@ -10,6 +11,7 @@ impl TryFrom<&HttpUrlStub> for PathRegexMatcher {
         http_url
             .url_path_pattern
             .as_deref()
+            .filter(|pattern| Regex::new(pattern).is_ok())
             .map(path_regex)
             .ok_or_else(|| StubrError::QuietError)
     }
@@ -0,0 +1,336 @@
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use http_types::Method;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde_json::{Map, Value};
+
+use crate::{
+    gen::regex::RegexRndGenerator,
+    model::{request::MockRegistrable, response::template::helpers::any::uuid::AnyUuid},
+    wiremock_rs::{Match, MockBuilder, Request, Respond, ResponseTemplate},
+    StubrError, StubrResult,
+};
+
+/// Collection served with in-memory persistence instead of a response e.g. `GET /users/1` or `POST /users`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceStub {
+    /// Path of the collection e.g. '/users'. Items are served under '/users/{id}'
+    pub base_path: String,
+    /// Field holding the id of an item, 'id' by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_field: Option<String>,
+    /// Json file holding an array of the items the collection starts with.
+    /// Either relative to the current directory or to the stub's directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_file: Option<PathBuf>,
+}
+
+impl ResourceStub {
+    const DEFAULT_ID_FIELD: &'static str = "id";
+
+    fn base_path(&self) -> &str {
+        self.base_path.trim_end_matches('/')
+    }
+
+    fn id_field(&self) -> &str {
+        self.id_field.as_deref().unwrap_or(Self::DEFAULT_ID_FIELD)
+    }
+
+    fn try_seed(&self, stub_file: &Path) -> StubrResult<Vec<Value>> {
+        let Some(seed_file) = self.seed_file.as_ref() else {
+            return Ok(vec![]);
+        };
+        let path = Some(seed_file.to_path_buf())
+            .filter(|f| f.exists())
+            .or_else(|| stub_file.parent().map(|dir| dir.join(seed_file)).filter(|f| f.exists()))
+            .ok_or_else(|| StubrError::ResourceSeedNotFound(seed_file.to_path_buf()))?;
+        let seed = serde_json::from_reader(OpenOptions::new().read(true).open(path)?)?;
+        match seed {
+            Value::Array(items) if items.iter().all(|item| item.get(self.id_field()).and_then(id_of).is_some()) => Ok(items),
+            _ => Err(StubrError::InvalidResource(
+                "its seed file must hold an array of objects, all with an id",
+            )),
+        }
+    }
+}
+
+impl MockRegistrable for ResourceStub {
+    fn register(&self, mock: MockBuilder) -> MockBuilder {
+        mock.and(ResourcePathMatcher(self.base_path().to_string()))
+    }
+}
+
+/// Id as it appears in a path. Only strings and numbers are ids
+fn id_of(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Id in the path, when it targets an item rather than the collection
+fn path_id(base_path: &str, req: &Request) -> Option<Option<String>> {
+    match req.url.path().strip_prefix(base_path)? {
+        "" | "/" => Some(None),
+        rest => {
+            let id = rest.strip_prefix('/').filter(|id| !id.contains('/'))?;
+            Some(Some(percent_decode_str(id).decode_utf8_lossy().to_string()))
+        },
+    }
+}
+
+/// Matches the collection path and the path of any of its items
+pub struct ResourcePathMatcher(String);
+
+impl Match for ResourcePathMatcher {
+    fn matches(&self, req: &Request) -> bool {
+        path_id(&self.0, req).is_some()
+    }
+}
+
+/// Serves the collection, shared by all the requests the server receives
+pub struct ResourceRespond {
+    base_path: String,
+    id_field: String,
+    items: Arc<Mutex<Vec<Value>>>,
+}
+
+impl ResourceRespond {
+    const PAGE: &'static str = "page";
+    const SIZE: &'static str = "size";
+    const TOTAL_COUNT: &'static str = "x-total-count";
+    /// Escaped in the 'location' of a created item
+    const ID_ESCAPED: &'static AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+    pub fn try_new(stub: &ResourceStub, stub_file: &Path) -> StubrResult<Self> {
+        if !stub.base_path.starts_with('/') {
+            return Err(StubrError::InvalidResource("'basePath' must start with '/'"));
+        }
+        Ok(Self {
+            base_path: stub.base_path().to_string(),
+            id_field: stub.id_field().to_string(),
+            items: Arc::new(Mutex::new(stub.try_seed(stub_file)?)),
+        })
+    }
+
+    fn position(&self, items: &[Value], id: &str) -> Option<usize> {
+        items
+            .iter()
+            .position(|item| item.get(&self.id_field).and_then(id_of).as_deref() == Some(id))
+    }
+
+    /// Items have to be json objects
+    fn body(req: &Request) -> Option<Map<String, Value>> {
+        match serde_json::from_slice(&req.body).ok()? {
+            Value::Object(item) => Some(item),
+            _ => None,
+        }
+    }
+
+    /// Following the greatest numeric id, a random one when ids are not all numbers
+    fn next_id(&self, items: &[Value]) -> StubrResult<Value> {
+        let ids = items.iter().map(|item| item.get(&self.id_field).and_then(Value::as_u64));
+        Ok(match ids.collect::<Option<Vec<_>>>() {
+            Some(ids) => Value::from(ids.into_iter().max().unwrap_or_default() + 1),
+            None => Value::from(RegexRndGenerator(AnyUuid::UUID_RGX).try_generate()?),
+        })
+    }
+
+    /// Paginated with 'page' (from 1) and 'size' query parameters, the whole collection when absent
+    fn list(&self, items: &[Value], req: &Request) -> ResponseTemplate {
+        let param = |name: &str| {
+            req.url
+                .query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.parse::<usize>().ok().filter(|n| *n > 0))
+        };
+        let page = param(Self::PAGE).unwrap_or(Some(1));
+        let size = param(Self::SIZE).unwrap_or(Some(items.len().max(1)));
+        let (Some(page), Some(size)) = (page, size) else {
+            return ResponseTemplate::new(400);
+        };
+        let page_items = items
+            .iter()
+            .skip((page - 1).saturating_mul(size))
+            .take(size)
+            .collect::<Vec<_>>();
+        ResponseTemplate::new(200)
+            .insert_header(Self::TOTAL_COUNT, items.len().to_string().as_str())
+            .set_body_json(page_items)
+    }
+
+    fn create(&self, items: &mut Vec<Value>, req: &Request) -> StubrResult<ResponseTemplate> {
+        let Some(mut item) = Self::body(req) else {
+            return Ok(ResponseTemplate::new(400));
+        };
+        let id = match item.get(&self.id_field) {
+            Some(id) => id_of(id),
+            None => {
+                let id = self.next_id(items)?;
+                item.insert(self.id_field.to_string(), id.clone());
+                id_of(&id)
+            },
+        };
+        let Some(id) = id else {
+            return Ok(ResponseTemplate::new(400));
+        };
+        if self.position(items, &id).is_some() {
+            return Ok(ResponseTemplate::new(409));
+        }
+        let location = format!("{}/{}", self.base_path, utf8_percent_encode(&id, Self::ID_ESCAPED));
+        items.push(Value::Object(item.clone()));
+        Ok(ResponseTemplate::new(201)
+            .insert_header("location", location.as_str())
+            .set_body_json(item))
+    }
+
+    /// Replaces (PUT) or merges (PATCH) the item, which keeps its id
+    fn update(&self, items: &mut [Value], id: &str, req: &Request, merge: bool) -> ResponseTemplate {
+        let Some(position) = self.position(items, id) else {
+            return ResponseTemplate::new(404);
+        };
+        let Some(body) = Self::body(req) else {
+            return ResponseTemplate::new(400);
+        };
+        let item = &mut items[position];
+        let id = item.get(&self.id_field).cloned().unwrap_or_default();
+        if merge {
+            merge_patch(item, Value::Object(body));
+        } else {
+            *item = Value::Object(body);
+        }
+        item[&self.id_field] = id;
+        ResponseTemplate::new(200).set_body_json(&item)
+    }
+}
+
+/// As in [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396)
+fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(&key);
+            } else {
+                merge_patch(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+impl Respond for ResourceRespond {
+    fn respond(&self, req: &Request) -> StubrResult<ResponseTemplate> {
+        let (Some(id), Ok(mut items)) = (path_id(&self.base_path, req), self.items.lock()) else {
+            return Ok(ResponseTemplate::new(500));
+        };
+        let resp = match (req.method, id.as_deref()) {
+            (Method::Get, None) => self.list(&items, req),
+            (Method::Post, None) => self.create(&mut items, req)?,
+            (Method::Get, Some(id)) => match self.position(&items, id) {
+                Some(position) => ResponseTemplate::new(200).set_body_json(&items[position]),
+                None => ResponseTemplate::new(404),
+            },
+            (Method::Put, Some(id)) => self.update(&mut items, id, req, false),
+            (Method::Patch, Some(id)) => self.update(&mut items, id, req, true),
+            (Method::Delete, Some(id)) => match self.position(&items, id) {
+                Some(position) => {
+                    items.remove(position);
+                    ResponseTemplate::new(204)
+                },
+                None => ResponseTemplate::new(404),
+            },
+            _ => ResponseTemplate::new(405),
+        };
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod resource_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(method: Method, path: &str, body: Option<Value>) -> Request {
+        Request {
+            url: format!("http://localhost{path}").parse().unwrap(),
+            method,
+            headers: Default::default(),
+            body: body.map(|b| b.to_string().into_bytes()).unwrap_or_default(),
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    fn respond(items: Vec<Value>) -> ResourceRespond {
+        ResourceRespond {
+            base_path: "/users".to_string(),
+            id_field: "id".to_string(),
+            items: Arc::new(Mutex::new(items)),
+        }
+    }
+
+    #[test]
+    fn should_match_collection_and_item_paths() {
+        let matcher = ResourcePathMatcher("/users".to_string());
+        assert!(matcher.matches(&request(Method::Get, "/users", None)));
+        assert!(matcher.matches(&request(Method::Get, "/users/", None)));
+        assert!(matcher.matches(&request(Method::Get, "/users/1", None)));
+        assert!(!matcher.matches(&request(Method::Get, "/users/1/orders", None)));
+        assert!(!matcher.matches(&request(Method::Get, "/usersx", None)));
+        assert!(!matcher.matches(&request(Method::Get, "/orders", None)));
+    }
+
+    #[test]
+    fn should_decode_id_from_path() {
+        let req = request(Method::Get, "/users/a%20b", None);
+        assert_eq!(path_id("/users", &req), Some(Some("a b".to_string())));
+    }
+
+    #[test]
+    fn should_generate_following_numeric_id() {
+        let resource = respond(vec![json!({"id": 3}), json!({"id": 7})]);
+        assert_eq!(resource.next_id(&resource.items.lock().unwrap()).unwrap(), json!(8));
+        assert_eq!(resource.next_id(&[]).unwrap(), json!(1));
+        assert!(resource.next_id(&[json!({"id": "a"})]).unwrap().is_string());
+    }
+
+    #[test]
+    fn should_merge_patch() {
+        let mut target = json!({"name": "john", "address": {"city": "Paris", "zip": "75000"}, "age": 30});
+        merge_patch(&mut target, json!({"address": {"city": "Lyon"}, "age": null, "tags": ["a"]}));
+        assert_eq!(
+            target,
+            json!({"name": "john", "address": {"city": "Lyon", "zip": "75000"}, "tags": ["a"]})
+        );
+    }
+
+    #[test]
+    fn should_keep_id_when_updating() {
+        let resource = respond(vec![json!({"id": 1, "name": "john"})]);
+        let req = request(Method::Put, "/users/1", Some(json!({"id": 2, "name": "jane"})));
+        assert!(resource.respond(&req).is_ok());
+        assert_eq!(*resource.items.lock().unwrap(), vec![json!({"id": 1, "name": "jane"})]);
+    }
+
+    #[test]
+    fn should_fail_when_base_path_not_absolute() {
+        let stub = ResourceStub {
+            base_path: "users".to_string(),
+            ..Default::default()
+        };
+        assert!(ResourceRespond::try_new(&stub, Path::new("stub.json")).is_err());
+    }
+}
@@ -1,37 +1,62 @@
 use std::time::Duration;
 
-use crate::wiremock_rs::ResponseTemplate;
+use crate::wiremock_rs::{delay::DelayDistribution, throttle::Throttle, ResponseTemplate};
 
 use crate::Config;
 
 use super::ResponseAppender;
 
-/// See [https://wiremock.org/docs/simulating-faults/#per-stub-fixed-delays]
-pub struct Delay<'a>(pub Option<u64>, pub &'a Option<RandomDelay>, pub &'a Config);
+/// See [https://wiremock.org/docs/simulating-faults/#per-stub-fixed-delays].
+/// A stub's fixed delay has precedence over its random one. The global latency, fixed or random, is added to both
+pub struct Delay<'a>(pub Option<u64>, pub &'a Option<DelayDistribution>, pub &'a Config);
 
 impl ResponseAppender for Delay<'_> {
     fn add(&self, mut resp: ResponseTemplate) -> ResponseTemplate {
         if let Some(global_delay) = self.2.global_delay {
-            resp = resp.set_delay(Duration::from_millis(global_delay))
-        } else if let Some(latency) = self.2.latency {
-            if let Some(delay) = self.0 {
-                resp = resp.set_delay(Duration::from_millis(latency + delay))
-            } else {
-                resp = resp.set_delay(Duration::from_millis(latency))
-            }
-        } else if let Some(delay) = self.0 {
-            resp = resp.set_delay(Duration::from_millis(delay))
-        } else if let Some(RandomDelay::Lognormal { median, sigma }) = self.1 {
-            resp = resp.set_lognormal_delay(*median, *sigma)
+            return resp.set_delay(Duration::from_millis(global_delay));
+        }
+        match (self.2.latency, self.0) {
+            (Some(latency), Some(delay)) => resp = resp.set_delay(Duration::from_millis(latency + delay)),
+            (Some(delay), None) | (None, Some(delay)) => resp = resp.set_delay(Duration::from_millis(delay)),
+            (None, None) => {},
+        }
+        if let (None, Some(distribution)) = (self.0, self.1) {
+            resp = resp.add_random_delay(*distribution)
+        }
+        if let Some(distribution) = self.2.latency_distribution {
+            resp = resp.add_random_delay(distribution)
         }
         resp
     }
 }
 
-/// See [https://wiremock.org/docs/simulating-faults/#per-stub-random-delays]
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(tag = "type")]
-pub enum RandomDelay {
-    #[serde(rename = "lognormal")]
-    Lognormal { median: u64, sigma: f64 },
+/// See [https://wiremock.org/docs/simulating-faults/#chunked-dribble-delay]
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkedDribbleDelay {
+    /// number of chunks the body is split into
+    pub number_of_chunks: u32,
+    /// time in milliseconds it takes to send the whole body
+    pub total_duration: u64,
+}
+
+/// Streams the body slowly. A chunked dribble delay defined in the stub has precedence over the global bandwidth
+pub struct Dribble<'a>(pub Option<ChunkedDribbleDelay>, pub &'a Config);
+
+impl ResponseAppender for Dribble<'_> {
+    fn add(&self, mut resp: ResponseTemplate) -> ResponseTemplate {
+        if let Some(ChunkedDribbleDelay {
+            number_of_chunks,
+            total_duration,
+        }) = self.0
+        {
+            resp = resp.set_throttle(Throttle::Dribble {
+                chunks: number_of_chunks,
+                total: Duration::from_millis(total_duration),
+            })
+        } else if let Some(bandwidth) = self.1.bandwidth {
+            resp = resp.set_throttle(Throttle::Bandwidth(bandwidth))
+        }
+        resp
+    }
 }
@@ -0,0 +1,20 @@
+use crate::wiremock_rs::{fault::Fault, ResponseTemplate};
+
+use crate::Config;
+
+use super::ResponseAppender;
+
+/// See [https://wiremock.org/docs/simulating-faults/#bad-responses].
+/// A fault defined in the stub is always injected. Otherwise the global one is, with its probability
+pub struct StubFault<'a>(pub Option<Fault>, pub &'a Config);
+
+impl ResponseAppender for StubFault<'_> {
+    fn add(&self, mut resp: ResponseTemplate) -> ResponseTemplate {
+        if let Some(fault) = self.0 {
+            resp = resp.set_fault(fault, 1.0)
+        } else if let Some(global) = self.1.fault {
+            resp = resp.set_fault(global.fault, global.probability)
+        }
+        resp
+    }
+}
@@ -1,16 +1,27 @@
-use std::hash::{Hash, Hasher};
+use std::{
+    hash::{Hash, Hasher},
+    path::PathBuf,
+};
 
-use crate::wiremock_rs::ResponseTemplate;
+use crate::wiremock_rs::{delay::DelayDistribution, fault::Fault, ResponseTemplate};
+use crate::StubrError;
 
-use crate::model::response::delay::RandomDelay;
+use crate::model::response::delay::ChunkedDribbleDelay;
 use body::BodyStub;
 use headers::HttpRespHeadersStub;
+use proxy::ProxyStub;
+use stream::StreamStub;
+use template::helpers::dataset::LookupRowHelper;
 
 pub mod body;
 mod body_file;
 pub mod default;
 pub mod delay;
+pub mod fault;
 pub mod headers;
+pub mod proxy;
+pub mod sequence;
+pub mod stream;
 pub mod template;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
@@ -24,13 +35,28 @@ pub struct ResponseStub {
     pub fixed_delay_milliseconds: Option<u64>,
     /// random delay accepting different distributions
     #[serde(skip_serializing)]
-    pub delay_distribution: Option<RandomDelay>,
+    pub delay_distribution: Option<DelayDistribution>,
+    /// streams the body in chunks spread over a duration
+    #[serde(skip_serializing)]
+    pub chunked_dribble_delay: Option<ChunkedDribbleDelay>,
+    /// breaks the response at the connection level
+    #[serde(skip_serializing)]
+    pub fault: Option<Fault>,
     /// HTTP response body
     #[serde(flatten)]
     pub body: BodyStub,
     /// HTTP response headers
     #[serde(flatten)]
     pub headers: HttpRespHeadersStub,
+    /// Body streamed as server-sent events or chunks
+    #[serde(flatten)]
+    pub stream: StreamStub,
+    /// Forwards the request to an upstream server
+    #[serde(flatten)]
+    pub proxy: ProxyStub,
+    /// Status responded with instead when a template looks a row up with 'lookup-row' but none matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing_row_status: Option<u16>,
     /// Mostly used for enabling response templating
     #[serde(default, skip_serializing)]
     pub transformers: Vec<String>,
@@ -59,6 +85,37 @@ impl ResponseStub {
     pub fn status(&self) -> u16 {
         self.status.unwrap_or(200)
     }
+
+    /// Problems which would otherwise only show up when serving a request:
+    /// a missing body file or dataset or a response template which does not compile
+    pub(crate) fn validate(&self) -> Vec<StubrError> {
+        let mut errors = vec![];
+        if let Some(body_file) = self.body.body_file_name.as_ref() {
+            let is_templated = self.requires_response_templating() && body_file.path.contains("{{");
+            if !body_file.path_exists && !is_templated {
+                errors.push(StubrError::BodyFileNotFound(PathBuf::from(&body_file.path)));
+            }
+        }
+        if self.requires_response_templating() {
+            let stream = self.stream.templates();
+            let headers = self.user_defined_headers().into_iter().flatten().map(|(_, v)| v);
+            let body_file = self
+                .body
+                .body_file_name
+                .iter()
+                .flat_map(|f| [f.path.as_str(), f.content.as_str()]);
+            let templates = headers
+                .chain(self.body.body.as_deref())
+                .chain(self.body.json_body.iter().flat_map(template::json_strings))
+                .chain(body_file)
+                .chain(stream.iter().map(String::as_str))
+                .collect::<Vec<_>>();
+            errors.extend(template::compile_errors(templates.iter().copied()));
+            let datasets = templates.iter().flat_map(|t| LookupRowHelper::datasets(t));
+            errors.extend(datasets.filter(|d| !d.exists()).map(StubrError::DatasetNotFound));
+        }
+        errors
+    }
 }
 
 impl Hash for ResponseStub {
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use crate::wiremock_rs::{proxy::Proxy, ResponseTemplate};
+
+use super::ResponseAppender;
+
+/// See [https://wiremock.org/docs/proxying/]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyStub {
+    /// Upstream base url the request is forwarded to e.g. 'http://localhost:8080'
+    #[serde(skip_serializing)]
+    pub proxy_base_url: Option<String>,
+    /// Headers added to the forwarded request
+    #[serde(skip_serializing)]
+    pub additional_proxy_request_headers: Option<HashMap<String, String>>,
+    /// Removed from the request path before forwarding it
+    #[serde(skip_serializing)]
+    pub proxy_url_prefix_to_remove: Option<String>,
+}
+
+impl ResponseAppender for ProxyStub {
+    fn add(&self, mut resp: ResponseTemplate) -> ResponseTemplate {
+        if let Some(base_url) = self.proxy_base_url.as_ref() {
+            let mut additional_headers = self
+                .additional_proxy_request_headers
+                .iter()
+                .flatten()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>();
+            additional_headers.sort();
+            resp = resp.set_proxy(Proxy {
+                base_url: base_url.to_string(),
+                additional_headers,
+                url_prefix_to_remove: self.proxy_url_prefix_to_remove.clone(),
+            })
+        }
+        resp
+    }
+}
@@ -0,0 +1,136 @@
+use rand::distributions::{Distribution, WeightedIndex};
+
+use crate::{
+    model::response::{template::StubTemplate, ResponseStub},
+    wiremock_rs::{Request, Respond, ResponseTemplate},
+    StubrError, StubrResult,
+};
+
+/// How successive requests matching a stub pick one of its `responses`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SequenceMode {
+    /// One after the other, then the last one forever
+    #[default]
+    Sequential,
+    /// One after the other, then from the first one again
+    Cycle,
+    /// Any of them, according to their weight
+    Random,
+}
+
+/// One of the successive responses of a stub
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+pub struct SequencedResponseStub {
+    /// Relative chance of being picked in 'random' mode, 1 by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+    #[serde(flatten)]
+    pub response: ResponseStub,
+}
+
+impl SequencedResponseStub {
+    const DEFAULT_WEIGHT: u32 = 1;
+}
+
+/// Serves a stub's `responses` to successive matching requests
+pub struct ResponseSequence {
+    responses: Vec<StubTemplate>,
+    mode: SequenceMode,
+    weights: Option<WeightedIndex<u32>>,
+}
+
+impl ResponseSequence {
+    pub(crate) fn try_new(responses: Vec<(StubTemplate, Option<u32>)>, mode: SequenceMode) -> StubrResult<Self> {
+        if responses.is_empty() {
+            return Err(StubrError::InvalidResponseSequence("it has no response"));
+        }
+        let (responses, weights): (Vec<_>, Vec<_>) = responses
+            .into_iter()
+            .map(|(r, w)| (r, w.unwrap_or(SequencedResponseStub::DEFAULT_WEIGHT)))
+            .unzip();
+        let weights = match mode {
+            SequenceMode::Random => Some(
+                WeightedIndex::new(weights).map_err(|_| StubrError::InvalidResponseSequence("at least one weight has to be positive"))?,
+            ),
+            SequenceMode::Sequential | SequenceMode::Cycle => None,
+        };
+        Ok(Self { responses, mode, weights })
+    }
+
+    fn pick(&self, nth: u64) -> Option<&StubTemplate> {
+        let len = self.responses.len() as u64;
+        let index = match self.mode {
+            SequenceMode::Sequential => nth.min(len - 1) as usize,
+            SequenceMode::Cycle => (nth % len) as usize,
+            SequenceMode::Random => self.weights.as_ref()?.sample(&mut rand::thread_rng()),
+        };
+        self.responses.get(index)
+    }
+}
+
+impl Respond for ResponseSequence {
+    fn respond(&self, request: &Request) -> StubrResult<ResponseTemplate> {
+        self.respond_nth(request, 0)
+    }
+
+    fn respond_nth(&self, request: &Request, nth: u64) -> StubrResult<ResponseTemplate> {
+        self.pick(nth)
+            .map(|r| r.respond(request))
+            .unwrap_or_else(|| Ok(ResponseTemplate::new(200)))
+    }
+}
+
+#[cfg(test)]
+mod sequence_tests {
+    use super::*;
+
+    fn sequence(statuses: &[(u16, Option<u32>)], mode: SequenceMode) -> StubrResult<ResponseSequence> {
+        let responses = statuses
+            .iter()
+            .map(|(status, weight)| {
+                let template = StubTemplate {
+                    template: ResponseTemplate::new(*status),
+                    ..Default::default()
+                };
+                (template, *weight)
+            })
+            .collect();
+        ResponseSequence::try_new(responses, mode)
+    }
+
+    fn statuses(sequence: &ResponseSequence, n: u64) -> Vec<u16> {
+        (0..n)
+            .map(|nth| sequence.pick(nth).unwrap().template.clone().generate_response().status() as u16)
+            .collect()
+    }
+
+    #[test]
+    fn sequential_should_stick_on_last() {
+        let sequence = sequence(&[(500, None), (503, None), (200, None)], SequenceMode::Sequential).unwrap();
+        assert_eq!(statuses(&sequence, 5), vec![500, 503, 200, 200, 200]);
+    }
+
+    #[test]
+    fn cycle_should_start_over() {
+        let sequence = sequence(&[(500, None), (200, None)], SequenceMode::Cycle).unwrap();
+        assert_eq!(statuses(&sequence, 5), vec![500, 200, 500, 200, 500]);
+    }
+
+    #[test]
+    fn random_should_never_pick_zero_weight() {
+        let sequence = sequence(&[(500, Some(0)), (200, Some(3))], SequenceMode::Random).unwrap();
+        assert!(statuses(&sequence, 50).into_iter().all(|s| s == 200));
+    }
+
+    #[test]
+    fn should_fail_when_empty() {
+        assert!(sequence(&[], SequenceMode::Sequential).is_err());
+    }
+
+    #[test]
+    fn random_should_fail_when_all_weights_zero() {
+        assert!(sequence(&[(500, Some(0)), (200, Some(0))], SequenceMode::Random).is_err());
+        assert!(sequence(&[(500, Some(0)), (200, Some(0))], SequenceMode::Cycle).is_ok());
+    }
+}
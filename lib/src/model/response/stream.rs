@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{
+    wiremock_rs::{stream::StreamedBody, ResponseTemplate},
+    StubrResult,
+};
+
+use super::{
+    template::{data::HandlebarsData, HandlebarTemplatable},
+    ResponseAppender,
+};
+
+/// A body streamed piece by piece instead of being sent at once.
+/// See [https://html.spec.whatwg.org/multipage/server-sent-events.html]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamStub {
+    /// events sent as 'text/event-stream'
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub server_sent_events: Vec<ServerSentEventStub>,
+    /// raw chunks sent with chunked transfer encoding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<ChunkStub>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSentEventStub {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// event type, 'message' for the client when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// a string as is, any other json value serialized. Spreads over many 'data' fields when multiline
+    #[serde(default)]
+    pub data: Value,
+    /// reconnection time in milliseconds the client should use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<u64>,
+    /// delay in milliseconds before sending the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkStub {
+    pub body: String,
+    /// delay in milliseconds before sending the chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+}
+
+impl ServerSentEventStub {
+    /// The event as written on the wire
+    fn frame(&self) -> String {
+        self.frame_with(self.id.as_deref(), self.event.as_deref(), &self.data())
+    }
+
+    /// The event with its templated fields rendered, then framed so that a rendered multiline data still spreads
+    /// over many 'data' fields
+    fn render_frame(&self, render: impl Fn(&str) -> StubrResult<String>) -> StubrResult<String> {
+        let id = self.id.as_deref().map(&render).transpose()?;
+        let event = self.event.as_deref().map(&render).transpose()?;
+        let data = render(&self.data())?;
+        Ok(self.frame_with(id.as_deref(), event.as_deref(), &data))
+    }
+
+    /// Fields supporting response templating
+    fn templates(&self) -> impl Iterator<Item = String> {
+        self.id.clone().into_iter().chain(self.event.clone()).chain([self.data()])
+    }
+
+    /// A string as is, any other json value serialized
+    fn data(&self) -> String {
+        match &self.data {
+            Value::String(s) => s.to_string(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        }
+    }
+
+    /// A multiline data spreads over many 'data' fields
+    fn frame_with(&self, id: Option<&str>, event: Option<&str>, data: &str) -> String {
+        let mut frame = String::new();
+        if let Some(id) = id {
+            frame.push_str(&format!("id: {id}\n"));
+        }
+        if let Some(event) = event {
+            frame.push_str(&format!("event: {event}\n"));
+        }
+        if let Some(retry) = self.retry {
+            frame.push_str(&format!("retry: {retry}\n"));
+        }
+        for line in data.split('\n') {
+            frame.push_str(&format!("data: {line}\n"));
+        }
+        frame.push('\n');
+        frame
+    }
+}
+
+impl StreamStub {
+    fn is_empty(&self) -> bool {
+        self.server_sent_events.is_empty() && self.chunks.is_empty()
+    }
+
+    /// Every piece of the body along with the delay before sending it.
+    /// Server-sent events come first when both are defined
+    fn pieces(&self) -> Vec<(String, Duration)> {
+        let events = self.server_sent_events.iter().map(|e| (e.frame(), e.delay));
+        let chunks = self.chunks.iter().map(|c| (c.body.clone(), c.delay));
+        events.chain(chunks).map(|(piece, delay)| (piece, Self::delay(delay))).collect()
+    }
+
+    fn delay(delay: Option<u64>) -> Duration {
+        Duration::from_millis(delay.unwrap_or_default())
+    }
+
+    /// Parts of the body supporting response templating: event fields and chunks
+    pub(crate) fn templates(&self) -> Vec<String> {
+        let events = self.server_sent_events.iter().flat_map(ServerSentEventStub::templates);
+        events.chain(self.chunks.iter().map(|c| c.body.clone())).collect()
+    }
+
+    fn set_stream(&self, resp: ResponseTemplate, pieces: impl Iterator<Item = (String, Duration)>) -> ResponseTemplate {
+        let mut stream = StreamedBody::default();
+        pieces.for_each(|(piece, delay)| stream.push(piece, delay));
+        let mut resp = resp.set_stream(stream);
+        if !self.server_sent_events.is_empty() {
+            let has_header = |resp: &ResponseTemplate, name: &str| resp.headers.keys().any(|k| k.as_str().eq_ignore_ascii_case(name));
+            if resp.mime.is_none() && !has_header(&resp, "content-type") {
+                resp.mime = Some(http_types::mime::SSE);
+            }
+            if !has_header(&resp, "cache-control") {
+                resp = resp.insert_header("cache-control", "no-cache");
+            }
+        }
+        resp
+    }
+
+    fn _render_response_template(&self, resp: ResponseTemplate, data: &HandlebarsData) -> StubrResult<ResponseTemplate> {
+        if self.is_empty() {
+            return Ok(resp);
+        }
+        let render = |template: &str| self.try_render(template, data);
+        let events = self
+            .server_sent_events
+            .iter()
+            .map(|e| Ok((e.render_frame(render)?, Self::delay(e.delay))));
+        let chunks = self.chunks.iter().map(|c| Ok((render(&c.body)?, Self::delay(c.delay))));
+        let pieces = events.chain(chunks).collect::<StubrResult<Vec<_>>>()?;
+        Ok(self.set_stream(resp, pieces.into_iter()))
+    }
+}
+
+impl HandlebarTemplatable for StreamStub {
+    fn register_template(&self) {
+        for piece in self.templates() {
+            self.register(&piece, &piece);
+        }
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    fn render_response_template(&self, resp: ResponseTemplate, data: &HandlebarsData) -> StubrResult<ResponseTemplate> {
+        self._render_response_template(resp, data)
+    }
+
+    #[cfg(feature = "grpc")]
+    fn render_response_template(
+        &self, resp: ResponseTemplate, data: &HandlebarsData, _md: Option<&protobuf::reflect::MessageDescriptor>,
+    ) -> StubrResult<ResponseTemplate> {
+        self._render_response_template(resp, data)
+    }
+}
+
+impl ResponseAppender for StreamStub {
+    fn add(&self, resp: ResponseTemplate) -> ResponseTemplate {
+        if self.is_empty() {
+            return resp;
+        }
+        self.set_stream(resp, self.pieces().into_iter())
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::StubrError;
+
+    fn event(json: Value) -> ServerSentEventStub {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn should_write_event_fields() {
+        let frame = event(json!({ "id": "1", "event": "price", "retry": 500, "data": "42" })).frame();
+        assert_eq!(frame, "id: 1\nevent: price\nretry: 500\ndata: 42\n\n");
+    }
+
+    #[test]
+    fn should_split_multiline_data() {
+        assert_eq!(event(json!({ "data": "a\nb" })).frame(), "data: a\ndata: b\n\n");
+    }
+
+    #[test]
+    fn should_serialize_json_data() {
+        assert_eq!(event(json!({ "data": { "price": 42 } })).frame(), "data: {\"price\":42}\n\n");
+        assert_eq!(event(json!({})).frame(), "data: \n\n");
+    }
+
+    #[test]
+    fn should_frame_rendered_fields() {
+        let event = event(json!({ "id": "{{id}}", "data": "{{data}}" }));
+        let rendered = event
+            .render_frame(|t| Ok(t.replace("{{id}}", "1").replace("{{data}}", "a\nb")))
+            .unwrap();
+        assert_eq!(rendered, "id: 1\ndata: a\ndata: b\n\n");
+    }
+
+    #[test]
+    fn should_fail_rendering_a_failing_field() {
+        let event = event(json!({ "event": "{{fail}}", "data": "a" }));
+        assert!(event.render_frame(|_| Err(StubrError::QuietError)).is_err());
+    }
+
+    #[test]
+    fn should_stream_events_then_chunks() {
+        let stub: StreamStub = serde_json::from_value(json!({
+            "serverSentEvents": [{ "data": "a", "delay": 10 }],
+            "chunks": [{ "body": "b" }]
+        }))
+        .unwrap();
+        assert_eq!(
+            stub.pieces(),
+            vec![
+                ("data: a\n\n".to_string(), Duration::from_millis(10)),
+                ("b".to_string(), Duration::ZERO)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_default_to_event_stream_content_type() {
+        let stub = StreamStub {
+            server_sent_events: vec![event(json!({ "data": "a" }))],
+            ..Default::default()
+        };
+        let resp = stub.add(ResponseTemplate::new(200)).generate_response();
+        assert_eq!(resp.content_type(), Some(http_types::mime::SSE));
+        assert_eq!(resp.header("cache-control").unwrap().as_str(), "no-cache");
+        let chunked = StreamStub {
+            chunks: vec![ChunkStub {
+                body: "a".to_string(),
+                delay: None,
+            }],
+            ..Default::default()
+        };
+        assert_ne!(chunked.add(ResponseTemplate::new(200)).mime, Some(http_types::mime::SSE));
+    }
+}
@@ -33,6 +33,8 @@ pub struct RequestData<'a> {
     body: Option<Value>,
     query: Option<Queries<'a>>,
     headers: Option<Headers<'a>>,
+    /// e.g. 'HTTP/1.1' or 'HTTP/2'
+    protocol: Option<http_types::Version>,
     #[serde(rename = "service")]
     #[cfg(feature = "grpc")]
     grpc_service: Option<&'a str>,
@@ -49,6 +51,7 @@ impl Default for RequestData<'_> {
             body: None,
             query: None,
             headers: None,
+            protocol: None,
             #[cfg(feature = "grpc")]
             grpc_service: None,
         }
@@ -70,6 +73,7 @@ impl<'a> RequestData<'a> {
             body: Some(body),
             query: None,
             headers: None,
+            protocol: Some(req.version),
             #[cfg(feature = "grpc")]
             grpc_service: Some(grpc_svc.0),
         })
@@ -87,6 +91,7 @@ impl<'a> From<&'a WiremockRequest> for RequestData<'a> {
             body: req.body(),
             query: req.queries(),
             headers: req.headers(),
+            protocol: Some(req.version),
             ..Default::default()
         }
     }
@@ -101,6 +106,7 @@ impl<'a> From<&'a mut http_types::Request> for RequestData<'a> {
             url: req.uri(),
             port: req.url().port(),
             method: MethodData::Http(req.method()),
+            protocol: req.version(),
             body,
             query: req.queries(),
             headers: req.headers(),
@@ -163,6 +169,14 @@ mod tests {
             assert!(RequestData::from(&req).port.is_none());
         }
 
+        #[test]
+        fn should_take_request_protocol() {
+            let mut req = request("https://localhost", None, &[], None);
+            assert_eq!(RequestData::from(&req).protocol, Some(http_types::Version::Http1_1));
+            req.version = http_types::Version::Http2_0;
+            assert_eq!(RequestData::from(&req).protocol, Some(http_types::Version::Http2_0));
+        }
+
         #[test]
         fn should_take_request_method() {
             let req = request("https://localhost", Some(Method::Get), &[], None);
@@ -264,6 +278,8 @@ mod tests {
                 method,
                 headers,
                 body,
+                version: http_types::Version::Http1_1,
+                client_certificate: None,
             }
         }
     }
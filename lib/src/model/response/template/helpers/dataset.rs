@@ -0,0 +1,250 @@
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use handlebars::{Context, Handlebars, Helper, HelperDef, HelperResult, Output, PathAndJson, RenderContext, RenderError, ScopedJson};
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::{model::response::body::BodyStub, StubrError, StubrResult};
+
+use super::ValueExt;
+
+type Row = Map<String, Value>;
+/// Rows along with when their file was last modified
+type CachedDataset = (Option<SystemTime>, Arc<Vec<Row>>);
+
+thread_local! {
+    /// Whether a lookup found no row while rendering the current response
+    static MISSED: Cell<bool> = const { Cell::new(false) };
+}
+
+lazy_static! {
+    /// Parsed datasets, read again once modified
+    static ref DATASETS: RwLock<HashMap<PathBuf, CachedDataset>> = RwLock::new(HashMap::new());
+    static ref DATASET_REGEX: Regex = Regex::new(r"lookup-row\s+'([^']+)'").expect("Implementation error");
+}
+
+/// Looks a row up in a CSV or json dataset by the value of its fields
+/// e.g. `{{lookup-row 'users.csv' id=request.pathSegments.[1]}}`, or only one of its fields
+/// e.g. `{{lookup-row 'users.csv' 'name' id=request.pathSegments.[1]}}`
+pub struct LookupRowHelper;
+
+impl LookupRowHelper {
+    pub const NAME: &'static str = "lookup-row";
+    const CSV_EXT: &'static str = "csv";
+    const JSON_EXT: &'static str = "json";
+
+    /// Forgets about rows previous renders missed
+    pub(crate) fn track() {
+        MISSED.with(|missed| missed.set(false));
+    }
+
+    /// Whether a lookup found no row since [Self::track]
+    pub(crate) fn missed() -> bool {
+        MISSED.with(Cell::get)
+    }
+
+    /// Datasets referenced by a template
+    pub(crate) fn datasets(template: &str) -> impl Iterator<Item = PathBuf> + '_ {
+        DATASET_REGEX
+            .captures_iter(template)
+            .filter_map(|c| c.get(1))
+            .map(|m| PathBuf::from(m.as_str()))
+    }
+
+    /// Either a quoted literal or the value of an expression
+    fn param_value(param: &PathAndJson) -> Value {
+        match (param.value(), param.relative_path()) {
+            (Value::Null, Some(path)) if path.starts_with(String::QUOTE) => Value::from(path.escape_single_quotes()),
+            (value, _) => value.clone(),
+        }
+    }
+
+    /// Compares CSV cells, always strings, with json values
+    fn as_key(value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(s.to_string()),
+            Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    fn dataset(path: &Path) -> StubrResult<Arc<Vec<Row>>> {
+        let modified = std::fs::metadata(path)
+            .map_err(|_| StubrError::DatasetNotFound(path.to_path_buf()))?
+            .modified()
+            .ok();
+        let cached = DATASETS.read().ok().and_then(|datasets| datasets.get(path).cloned());
+        if let Some((_, rows)) = cached.filter(|(m, _)| *m == modified) {
+            return Ok(rows);
+        }
+        let rows = Arc::new(Self::parse(path)?);
+        if let Ok(mut datasets) = DATASETS.write() {
+            datasets.insert(path.to_path_buf(), (modified, rows.clone()));
+        }
+        Ok(rows)
+    }
+
+    fn parse(path: &Path) -> StubrResult<Vec<Row>> {
+        let content = std::fs::read_to_string(path)?;
+        let invalid = || StubrError::InvalidDataset(path.to_path_buf());
+        match path.extension().and_then(OsStr::to_str) {
+            Some(Self::CSV_EXT) => {
+                let mut records = csv_records(&content).into_iter();
+                let header = records.next().ok_or_else(invalid)?;
+                Ok(records
+                    .map(|record| header.iter().cloned().zip(record.into_iter().map(Value::from)).collect())
+                    .collect())
+            },
+            Some(Self::JSON_EXT) => match serde_json::from_str(&content)? {
+                Value::Array(rows) => rows
+                    .into_iter()
+                    .map(|row| match row {
+                        Value::Object(row) => Ok(row),
+                        _ => Err(invalid()),
+                    })
+                    .collect(),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+
+    /// First row whose fields equal all the hash parameters, `null` when none does.
+    /// Only the given field of the row when there is a second parameter
+    fn lookup(h: &Helper) -> Result<Value, RenderError> {
+        let path = h
+            .param(0)
+            .map(Self::param_value)
+            .and_then(|p| p.as_str().map(PathBuf::from))
+            .ok_or_else(|| RenderError::new(format!("Missing dataset in '{}' helper", Self::NAME)))?;
+        if h.hash().is_empty() {
+            return Err(RenderError::new(format!(
+                "Missing field to look a row up by in '{}' helper",
+                Self::NAME
+            )));
+        }
+        let criteria = h
+            .hash()
+            .iter()
+            .map(|(field, value)| (*field, Self::as_key(&Self::param_value(value))))
+            .collect::<Vec<_>>();
+        let row = Self::dataset(&path)
+            .map_err(|e| RenderError::new(e.to_string()))?
+            .iter()
+            .find(|row| {
+                criteria
+                    .iter()
+                    .all(|(field, expected)| expected.is_some() && row.get(*field).and_then(Self::as_key) == *expected)
+            })
+            .cloned();
+        let field = h.param(1).map(Self::param_value);
+        Ok(match (row, field.as_ref().and_then(Value::as_str)) {
+            (Some(mut row), Some(field)) => row.remove(field).unwrap_or_default(),
+            (row, _) => row.map(Value::Object).unwrap_or_default(),
+        })
+    }
+
+    fn lookup_or_miss(h: &Helper) -> Value {
+        let row = Self::lookup(h).unwrap_or_else(|e| {
+            log::debug!("{e}");
+            Value::Null
+        });
+        if row.is_null() {
+            MISSED.with(|missed| missed.set(true));
+        }
+        row
+    }
+}
+
+/// Records of a CSV file as described in [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180), skipping blank lines
+fn csv_records(content: &str) -> Vec<Vec<String>> {
+    let (mut records, mut record, mut field) = (vec![], vec![], String::new());
+    let (mut chars, mut quoted) = (content.chars().peekable(), false);
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {},
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            },
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| r.len() > 1 || r.iter().any(|f| !f.is_empty()));
+    records
+}
+
+impl HelperDef for LookupRowHelper {
+    /// Strings are written as is, any other value as json. Nothing when missing
+    fn call<'reg: 'rc, 'rc>(
+        &self, h: &Helper<'reg, 'rc>, _: &'reg Handlebars<'reg>, _: &'rc Context, _: &mut RenderContext<'reg, 'rc>, out: &mut dyn Output,
+    ) -> HelperResult {
+        let rendered = match Self::lookup_or_miss(h) {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            Value::Object(o) => serde_json::to_string(&o).unwrap_or_default() + BodyStub::OBJECT_IDENTIFIER,
+            Value::Array(a) => serde_json::to_string(&a).unwrap_or_default() + BodyStub::ARRAY_IDENTIFIER,
+            other => other.to_string(),
+        };
+        out.write(&rendered).map_err(RenderError::from)
+    }
+
+    /// Within a sub expression e.g. `{{#with (lookup-row 'users.csv' id='1')}}{{name}}{{/with}}`
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self, h: &Helper<'reg, 'rc>, _: &'reg Handlebars<'reg>, _: &'rc Context, _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        Ok(ScopedJson::from(Self::lookup_or_miss(h)))
+    }
+}
+
+#[cfg(test)]
+mod dataset_tests {
+    use super::*;
+
+    #[test]
+    fn should_read_csv_records() {
+        let csv = "id,name,bio\r\n1,alice,\"likes \"\"rust\"\", tea\"\n\n2,bob,\"multi\nline\"\n3,carol,";
+        assert_eq!(
+            csv_records(csv),
+            vec![
+                vec!["id", "name", "bio"],
+                vec!["1", "alice", "likes \"rust\", tea"],
+                vec!["2", "bob", "multi\nline"],
+                vec!["3", "carol", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_referenced_datasets() {
+        let template = "{{lookup (lookup-row 'a.csv' id=request.body) 'name'}} {{lookup-row  'dir/b.json' x='1'}}";
+        let datasets = LookupRowHelper::datasets(template).collect::<Vec<_>>();
+        assert_eq!(datasets, vec![PathBuf::from("a.csv"), PathBuf::from("dir/b.json")]);
+    }
+
+    #[test]
+    fn should_compare_keys_as_strings() {
+        assert_eq!(LookupRowHelper::as_key(&Value::from(42)), Some("42".to_string()));
+        assert_eq!(LookupRowHelper::as_key(&Value::from("42")), Some("42".to_string()));
+        assert_eq!(LookupRowHelper::as_key(&Value::Null), None);
+    }
+}
@@ -1,9 +1,11 @@
 pub mod any;
 pub mod base64;
+pub mod dataset;
 pub mod datetime;
 pub mod json_path;
 pub mod numbers;
 pub mod size;
+pub mod state;
 pub mod string;
 pub mod string_replace;
 pub mod trim;
@@ -0,0 +1,68 @@
+use handlebars::{Context, Handlebars, Helper, HelperDef, HelperResult, Output, PathAndJson, RenderContext, RenderError, ScopedJson};
+use serde_json::Value;
+
+use crate::server::admin::state::StateStore;
+
+use super::ValueExt;
+
+/// Stores values in the server's state then reads them back from another stub
+/// e.g. `{{state-set 'user' request.pathSegments.[1] request.body}}` then `{{state-get 'user' request.pathSegments.[1]}}`
+pub struct StateHelper;
+
+impl StateHelper {
+    pub const SET: &'static str = "state-set";
+    pub const GET: &'static str = "state-get";
+    pub const DELETE: &'static str = "state-delete";
+
+    /// Either a quoted literal or the value of an expression
+    fn param_value(param: &PathAndJson) -> Value {
+        match (param.value(), param.relative_path()) {
+            (Value::Null, Some(path)) if path.starts_with(String::QUOTE) => Value::from(path.escape_single_quotes()),
+            (value, _) => value.clone(),
+        }
+    }
+
+    fn str_param(h: &Helper, index: usize) -> Result<String, RenderError> {
+        match h.param(index).map(Self::param_value) {
+            Some(Value::String(s)) => Ok(s),
+            Some(Value::Null) | None => Err(RenderError::new(format!("Missing context or key in '{}' helper", h.name()))),
+            Some(other) => Ok(other.to_string()),
+        }
+    }
+
+    /// Value stored, `null` when there is none
+    fn apply(h: &Helper) -> Result<Value, RenderError> {
+        let (context, key) = (Self::str_param(h, 0)?, Self::str_param(h, 1)?);
+        let value = StateStore::with_current(|state| match h.name() {
+            Self::SET => {
+                let value = h.param(2).map(Self::param_value).unwrap_or_default();
+                state.set(&context, &key, value);
+                None
+            },
+            Self::DELETE => state.delete(&context, &key),
+            _ => state.get(&context, &key),
+        });
+        Ok(value.flatten().unwrap_or_default())
+    }
+}
+
+impl HelperDef for StateHelper {
+    /// Strings are written as is, any other value as json. Setting writes nothing
+    fn call<'reg: 'rc, 'rc>(
+        &self, h: &Helper<'reg, 'rc>, _: &'reg Handlebars<'reg>, _: &'rc Context, _: &mut RenderContext<'reg, 'rc>, out: &mut dyn Output,
+    ) -> HelperResult {
+        let rendered = match Self::apply(h)? {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        out.write(&rendered).map_err(RenderError::from)
+    }
+
+    /// Within a sub expression e.g. `{{#if (state-get 'user' '1')}}`
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self, h: &Helper<'reg, 'rc>, _: &'reg Handlebars<'reg>, _: &'rc Context, _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        Self::apply(h).map(ScopedJson::from)
+    }
+}
@@ -3,7 +3,7 @@ use std::sync::RwLock;
 use crate::{
     model::response::template::data::RequestData,
     wiremock_rs::{Request, Respond, ResponseTemplate},
-    StubrResult,
+    StubrError, StubrResult,
 };
 use handlebars::Handlebars;
 use serde::Serialize;
@@ -17,10 +17,12 @@ use helpers::{
         non_empty::AnyNonEmpty, number::AnyNumber, of::AnyOf, regex::AnyRegex, time::AnyTime, uuid::AnyUuid,
     },
     base64::Base64Helper,
+    dataset::LookupRowHelper,
     datetime::NowHelper,
     json_path::JsonPathHelper,
     numbers::NumberHelper,
     size::SizeHelper,
+    state::StateHelper,
     string::StringHelper,
     string_replace::StringReplaceHelper,
     trim::TrimHelper,
@@ -28,7 +30,7 @@ use helpers::{
 };
 
 pub mod data;
-mod helpers;
+pub(crate) mod helpers;
 mod req_ext;
 pub mod utils;
 pub mod verify;
@@ -50,6 +52,10 @@ lazy_static! {
         handlebars.register_helper(StringHelper::LOWER, Box::new(StringHelper));
         handlebars.register_helper(StringReplaceHelper::REPLACE, Box::new(StringReplaceHelper));
         handlebars.register_helper(SizeHelper::NAME, Box::new(SizeHelper));
+        handlebars.register_helper(StateHelper::SET, Box::new(StateHelper));
+        handlebars.register_helper(StateHelper::GET, Box::new(StateHelper));
+        handlebars.register_helper(StateHelper::DELETE, Box::new(StateHelper));
+        handlebars.register_helper(LookupRowHelper::NAME, Box::new(LookupRowHelper));
         handlebars.register_helper(AnyRegex::NAME, Box::new(AnyRegex));
         handlebars.register_helper(AnyNonBlank::NAME, Box::new(AnyNonBlank));
         handlebars.register_helper(AnyNonEmpty::NAME, Box::new(AnyNonEmpty));
@@ -89,6 +95,8 @@ pub struct StubTemplate {
     pub(crate) md: Option<protobuf::reflect::MessageDescriptor>,
     #[allow(dead_code)]
     pub(crate) requires_templating: bool,
+    /// Rendered for every request then sent once responded
+    pub(crate) webhooks: Vec<crate::model::webhook::WebhookStub>,
 }
 
 impl StubTemplate {
@@ -103,8 +111,13 @@ impl StubTemplate {
                 stub_name: None,
                 is_verify: false,
             };
+            LookupRowHelper::track();
             resp = response.body.render_response_template(resp, &data)?;
             resp = response.headers.render_response_template(resp, &data)?;
+            resp = response.stream.render_response_template(resp, &data)?;
+            if let Some(status) = response.missing_row_status.filter(|_| LookupRowHelper::missed()) {
+                resp = ResponseTemplate::new(status);
+            }
         }
         Ok(resp)
     }
@@ -120,12 +133,37 @@ impl StubTemplate {
                 stub_name: None,
                 is_verify: false,
             };
+            LookupRowHelper::track();
             resp = response.body.render_response_template(resp, &data, None)?;
             resp = response.headers.render_response_template(resp, &data, None)?;
+            resp = response.stream.render_response_template(resp, &data, None)?;
+            if let Some(status) = response.missing_row_status.filter(|_| LookupRowHelper::missed()) {
+                resp = ResponseTemplate::new(status);
+            }
         }
         Ok(resp)
     }
 
+    /// A webhook which cannot be rendered is skipped rather than failing the response
+    fn add_webhooks(&self, mut resp: ResponseTemplate, req: &Request) -> ResponseTemplate {
+        if self.webhooks.is_empty() {
+            return resp;
+        }
+        let data = HandlebarsData {
+            request: &RequestData::from(req),
+            response: None,
+            stub_name: None,
+            is_verify: false,
+        };
+        for webhook in &self.webhooks {
+            match webhook.try_render(&data) {
+                Ok(webhook) => resp = resp.add_webhook(webhook),
+                Err(e) => log::warn!("skipping webhook to '{}' because {e}", webhook.url),
+            }
+        }
+        resp
+    }
+
     #[cfg(feature = "grpc")]
     fn grpc_respond(
         &self, req: &Request, mut resp: ResponseTemplate, response: &crate::model::grpc::response::GrpcResponseStub,
@@ -153,22 +191,40 @@ impl Respond for StubTemplate {
     fn respond(&self, req: &Request) -> StubrResult<ResponseTemplate> {
         let resp = self.template.clone();
         #[cfg(not(feature = "grpc"))]
-        if let Some(response) = self.response.as_ref() {
-            Ok(self.http_respond(resp, req, response)?)
+        let resp = if let Some(response) = self.response.as_ref() {
+            self.http_respond(resp, req, response)?
         } else {
-            Ok(resp)
-        }
+            resp
+        };
         #[cfg(feature = "grpc")]
-        if let Some(response) = self.response.as_ref() {
-            Ok(self.http_respond(resp, req, response)?)
+        let resp = if let Some(response) = self.response.as_ref() {
+            self.http_respond(resp, req, response)?
         } else if let Some(response) = self.grpc_response.as_ref() {
-            Ok(self.grpc_respond(req, resp, response)?)
+            self.grpc_respond(req, resp, response)?
         } else {
-            Ok(resp)
-        }
+            resp
+        };
+        Ok(self.add_webhooks(resp, req))
     }
 }
 
+/// Every string of a json value, however deeply nested
+pub(crate) fn json_strings(value: &serde_json::Value) -> Vec<&str> {
+    match value {
+        serde_json::Value::String(s) => vec![s.as_str()],
+        serde_json::Value::Array(a) => a.iter().flat_map(json_strings).collect(),
+        serde_json::Value::Object(o) => o.values().flat_map(json_strings).collect(),
+        _ => vec![],
+    }
+}
+
+/// Syntax errors of templates which would otherwise be silently ignored when registered
+pub(crate) fn compile_errors<'a>(templates: impl Iterator<Item = &'a str>) -> Vec<StubrError> {
+    templates
+        .filter_map(|t| handlebars::Template::compile(t).err().map(|e| Box::new(e).into()))
+        .collect()
+}
+
 pub trait HandlebarTemplatable {
     fn register_template(&self);
 
@@ -195,4 +251,10 @@ pub trait HandlebarTemplatable {
     fn render<T: Serialize>(&self, name: &str, data: &T) -> Option<String> {
         HANDLEBARS.read().ok().and_then(|it| it.render(name, data).ok())
     }
+
+    /// Like [HandlebarTemplatable::render] but failing instead of rendering nothing
+    fn try_render<T: Serialize>(&self, name: &str, data: &T) -> StubrResult<String> {
+        let handlebars = HANDLEBARS.read().map_err(|e| handlebars::RenderError::new(e.to_string()))?;
+        Ok(handlebars.render(name, data)?)
+    }
 }
@@ -0,0 +1,159 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::{
+    model::response::template::{data::HandlebarsData, HANDLEBARS},
+    wiremock_rs::{delay::DelayDistribution, webhook::Webhook},
+    StubrError, StubrResult,
+};
+
+/// Actions performed once a stub has responded.
+/// See [https://wiremock.org/docs/webhooks-and-callbacks/]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "name", content = "parameters", rename_all = "camelCase")]
+pub enum PostServeActionStub {
+    Webhook(WebhookStub),
+}
+
+/// An outbound request whose url, headers and body support response templating
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookStub {
+    /// Defaults to POST
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Waited for before sending the request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<WebhookDelayStub>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WebhookDelayStub {
+    Fixed {
+        milliseconds: u64,
+    },
+    /// Any of the random delay distributions of a response
+    #[serde(untagged)]
+    Random(DelayDistribution),
+}
+
+impl WebhookDelayStub {
+    fn sample(&self) -> Duration {
+        match self {
+            Self::Fixed { milliseconds } => Duration::from_millis(*milliseconds),
+            Self::Random(distribution) => distribution.new_sample(),
+        }
+    }
+}
+
+impl WebhookStub {
+    const DEFAULT_METHOD: &'static str = "POST";
+
+    fn templates(&self) -> impl Iterator<Item = &str> {
+        let headers = self.headers.iter().flat_map(|h| h.values()).map(String::as_str);
+        std::iter::once(self.url.as_str()).chain(headers).chain(self.body.as_deref())
+    }
+
+    /// Syntax errors of the templates
+    pub(crate) fn validate(&self) -> Vec<StubrError> {
+        super::response::template::compile_errors(self.templates())
+    }
+
+    pub(crate) fn try_render(&self, data: &HandlebarsData) -> StubrResult<Webhook> {
+        let handlebars = HANDLEBARS.read().map_err(|_| StubrError::QuietError)?;
+        let render = |template: &str| handlebars.render_template(template, data);
+        let headers = self
+            .headers
+            .iter()
+            .flatten()
+            .map(|(k, v)| Ok((k.to_string(), render(v)?)))
+            .collect::<StubrResult<_>>()?;
+        Ok(Webhook {
+            method: self.method.as_deref().unwrap_or(Self::DEFAULT_METHOD).to_uppercase(),
+            url: render(&self.url)?,
+            headers,
+            body: self.body.as_deref().map(render).transpose()?,
+            delay: self.delay.as_ref().map(WebhookDelayStub::sample),
+        })
+    }
+}
+
+#[cfg(test)]
+mod webhook_tests {
+    use serde_json::json;
+
+    use crate::{model::response::template::data::RequestData, wiremock_rs::Request};
+
+    use super::*;
+
+    fn webhook(json: serde_json::Value) -> WebhookStub {
+        match serde_json::from_value::<PostServeActionStub>(json!({ "name": "webhook", "parameters": json })).unwrap() {
+            PostServeActionStub::Webhook(webhook) => webhook,
+        }
+    }
+
+    fn render(webhook: &WebhookStub, req: &Request) -> Webhook {
+        let data = HandlebarsData {
+            request: &RequestData::from(req),
+            response: None,
+            stub_name: None,
+            is_verify: false,
+        };
+        webhook.try_render(&data).unwrap()
+    }
+
+    fn request() -> Request {
+        Request {
+            url: "http://localhost/orders/42".parse().unwrap(),
+            method: http_types::Method::Put,
+            headers: Default::default(),
+            body: vec![],
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    #[test]
+    fn should_default_to_post() {
+        let webhook = render(&webhook(json!({ "url": "http://localhost/callback" })), &request());
+        assert_eq!(webhook.method, "POST");
+        assert!(webhook.body.is_none() && webhook.delay.is_none());
+    }
+
+    #[test]
+    fn should_render_templates() {
+        let stub = webhook(json!({
+            "method": "put",
+            "url": "http://localhost/callback/{{request.pathSegments.[1]}}",
+            "headers": { "x-order": "{{request.pathSegments.[1]}}" },
+            "body": "{{request.method}} {{request.path}}"
+        }));
+        let webhook = render(&stub, &request());
+        assert_eq!(webhook.method, "PUT");
+        assert_eq!(webhook.url, "http://localhost/callback/42");
+        assert_eq!(webhook.headers, vec![("x-order".to_string(), "42".to_string())]);
+        assert_eq!(webhook.body.as_deref(), Some("PUT /orders/42"));
+    }
+
+    #[test]
+    fn should_support_fixed_and_random_delays() {
+        let fixed = webhook(json!({ "url": "http://localhost", "delay": { "type": "fixed", "milliseconds": 100 } }));
+        assert_eq!(render(&fixed, &request()).delay, Some(Duration::from_millis(100)));
+        let uniform = webhook(json!({ "url": "http://localhost", "delay": { "type": "uniform", "lower": 10, "upper": 20 } }));
+        let delay = render(&uniform, &request()).delay.unwrap();
+        assert!(delay >= Duration::from_millis(10) && delay <= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn should_report_invalid_templates() {
+        assert_eq!(webhook(json!({ "url": "http://localhost/{{request.path" })).validate().len(), 1);
+        assert!(webhook(json!({ "url": "http://localhost/{{request.path}}" }))
+            .validate()
+            .is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    model::request::{body::BodyMatcherStub, MockRegistrable},
+    wiremock_rs::{
+        websocket::{WebSocketFrame, WebSocketReply, WebSocketScript, WebSocketStep},
+        Match, Mock, Request, Respond, ResponseTemplate,
+    },
+    StubrError, StubrResult,
+};
+
+/// Conversation held once the upgrade request matched the stub's `request`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketStub {
+    /// Sent as soon as the connection is upgraded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_connect: Vec<WebSocketMessageStub>,
+    /// Replies to incoming messages, the first one matching is used
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_message: Vec<WebSocketReplyStub>,
+}
+
+/// A message, or a close frame, sent by the server
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMessageStub {
+    /// text message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// text message serialized from json
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_body: Option<Value>,
+    /// binary message, Base 64 encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_64_body: Option<String>,
+    /// closes the conversation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close: Option<WebSocketCloseStub>,
+    /// delay in milliseconds before sending it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketCloseStub {
+    /// 1000 (normal closure) by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Messages sent when an incoming message matches. Without any matcher, it matches any message
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketReplyStub {
+    /// matches a text message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<MessageTextStub>,
+    /// matches a json message, like a request body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub body_patterns: Vec<BodyMatcherStub>,
+    #[serde(default)]
+    pub reply: Vec<WebSocketMessageStub>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageTextStub {
+    EqualTo(String),
+    Contains(String),
+    Matches(String),
+}
+
+impl WebSocketCloseStub {
+    const NORMAL_CLOSURE: u16 = 1000;
+}
+
+impl TryFrom<&WebSocketMessageStub> for WebSocketStep {
+    type Error = StubrError;
+
+    fn try_from(message: &WebSocketMessageStub) -> StubrResult<Self> {
+        use base64::Engine as _;
+
+        let frame = match message {
+            WebSocketMessageStub {
+                body: Some(body),
+                json_body: None,
+                base_64_body: None,
+                close: None,
+                ..
+            } => WebSocketFrame::Text(body.to_string()),
+            WebSocketMessageStub {
+                body: None,
+                json_body: Some(json),
+                base_64_body: None,
+                close: None,
+                ..
+            } => WebSocketFrame::Text(json.to_string()),
+            WebSocketMessageStub {
+                body: None,
+                json_body: None,
+                base_64_body: Some(binary),
+                close: None,
+                ..
+            } => WebSocketFrame::Binary(
+                base64::prelude::BASE64_STANDARD
+                    .decode(binary)
+                    .map_err(|_| StubrError::InvalidWebSocket("'base64Body' is not Base 64 encoded"))?,
+            ),
+            WebSocketMessageStub {
+                body: None,
+                json_body: None,
+                base_64_body: None,
+                close: Some(close),
+                ..
+            } => WebSocketFrame::Close {
+                code: close.code.unwrap_or(WebSocketCloseStub::NORMAL_CLOSURE),
+                reason: close.reason.clone().unwrap_or_default(),
+            },
+            _ => {
+                return Err(StubrError::InvalidWebSocket(
+                    "a message requires exactly one of 'body', 'jsonBody', 'base64Body' or 'close'",
+                ))
+            },
+        };
+        Ok(Self {
+            frame,
+            delay: Duration::from_millis(message.delay.unwrap_or_default()),
+        })
+    }
+}
+
+fn try_steps(messages: &[WebSocketMessageStub]) -> StubrResult<Vec<WebSocketStep>> {
+    messages.iter().map(WebSocketStep::try_from).collect()
+}
+
+impl TryFrom<&WebSocketReplyStub> for WebSocketReply {
+    type Error = StubrError;
+
+    fn try_from(reply: &WebSocketReplyStub) -> StubrResult<Self> {
+        for body_pattern in &reply.body_patterns {
+            body_pattern.try_validate()?;
+        }
+        let mut matchers = Mock::given(MessageTextMatcher::try_from(reply.message.as_ref())?);
+        matchers = reply.body_patterns.register(matchers);
+        Ok(Self::new(matchers, try_steps(&reply.reply)?))
+    }
+}
+
+impl TryFrom<&WebSocketStub> for WebSocketScript {
+    type Error = StubrError;
+
+    fn try_from(stub: &WebSocketStub) -> StubrResult<Self> {
+        let on_message = stub
+            .on_message
+            .iter()
+            .map(WebSocketReply::try_from)
+            .collect::<StubrResult<_>>()?;
+        Ok(Self::new(try_steps(&stub.on_connect)?, on_message))
+    }
+}
+
+/// Responds to the upgrade request with the conversation
+pub struct WebSocketRespond(pub(crate) WebSocketScript);
+
+impl Respond for WebSocketRespond {
+    fn respond(&self, _: &Request) -> StubrResult<ResponseTemplate> {
+        Ok(ResponseTemplate::new(101).set_web_socket(self.0.clone()))
+    }
+}
+
+/// Matches an incoming text message, any message when there is nothing to match
+enum MessageTextMatcher {
+    Any,
+    EqualTo(String),
+    Contains(String),
+    Matches(Regex),
+}
+
+impl TryFrom<Option<&MessageTextStub>> for MessageTextMatcher {
+    type Error = StubrError;
+
+    fn try_from(stub: Option<&MessageTextStub>) -> StubrResult<Self> {
+        Ok(match stub {
+            None => Self::Any,
+            Some(MessageTextStub::EqualTo(v)) => Self::EqualTo(v.to_string()),
+            Some(MessageTextStub::Contains(v)) => Self::Contains(v.to_string()),
+            Some(MessageTextStub::Matches(v)) => Self::Matches(Regex::new(v)?),
+        })
+    }
+}
+
+impl Match for MessageTextMatcher {
+    fn matches(&self, message: &Request) -> bool {
+        let text = std::str::from_utf8(&message.body);
+        match (self, text) {
+            (Self::Any, _) => true,
+            (Self::EqualTo(expected), Ok(text)) => text == expected,
+            (Self::Contains(expected), Ok(text)) => text.contains(expected.as_str()),
+            (Self::Matches(regex), Ok(text)) => regex.is_match(text),
+            (_, Err(_)) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod websocket_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn step(json: Value) -> StubrResult<WebSocketStep> {
+        WebSocketStep::try_from(&serde_json::from_value::<WebSocketMessageStub>(json).unwrap())
+    }
+
+    fn message(body: &str) -> Request {
+        Request {
+            url: "http://localhost/".parse().unwrap(),
+            method: http_types::Method::Get,
+            headers: Default::default(),
+            body: body.as_bytes().to_vec(),
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    fn text_matcher(json: Value) -> MessageTextMatcher {
+        let stub = serde_json::from_value::<MessageTextStub>(json).unwrap();
+        MessageTextMatcher::try_from(Some(&stub)).unwrap()
+    }
+
+    #[test]
+    fn should_convert_messages_to_frames() {
+        assert_eq!(
+            step(json!({ "body": "hello" })).unwrap().frame,
+            WebSocketFrame::Text("hello".to_string())
+        );
+        assert_eq!(
+            step(json!({ "jsonBody": { "a": 1 } })).unwrap().frame,
+            WebSocketFrame::Text("{\"a\":1}".to_string())
+        );
+        assert_eq!(
+            step(json!({ "base64Body": "AQID" })).unwrap().frame,
+            WebSocketFrame::Binary(vec![1, 2, 3])
+        );
+        let close = step(json!({ "close": { "reason": "bye" }, "delay": 10 })).unwrap();
+        assert_eq!(
+            close.frame,
+            WebSocketFrame::Close {
+                code: 1000,
+                reason: "bye".to_string()
+            }
+        );
+        assert_eq!(close.delay, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn should_fail_when_message_ambiguous_or_empty() {
+        assert!(step(json!({ "body": "a", "jsonBody": {} })).is_err());
+        assert!(step(json!({ "delay": 10 })).is_err());
+        assert!(step(json!({ "base64Body": "%%" })).is_err());
+    }
+
+    #[test]
+    fn should_match_text_messages() {
+        assert!(text_matcher(json!({ "equalTo": "ping" })).matches(&message("ping")));
+        assert!(!text_matcher(json!({ "equalTo": "ping" })).matches(&message("pong")));
+        assert!(text_matcher(json!({ "contains": "in" })).matches(&message("ping")));
+        assert!(text_matcher(json!({ "matches": "^p[a-z]+g$" })).matches(&message("pong")));
+        assert!(MessageTextMatcher::Any.matches(&message("anything")));
+    }
+
+    #[test]
+    fn should_fail_when_invalid_regex() {
+        let stub = MessageTextStub::Matches("(".to_string());
+        assert!(MessageTextMatcher::try_from(Some(&stub)).is_err());
+    }
+}
@@ -13,8 +13,16 @@ impl From<RecordInput<'_>> for JsonStub {
             uuid: None,
             priority: None,
             expect: None,
+            scenario_name: None,
+            required_scenario_state: None,
+            new_scenario_state: None,
             http_request: Some(RequestStub::from((&mut *ex, cfg))),
             http_response: Some(ResponseStub::from((&mut *ex, cfg))),
+            responses: vec![],
+            sequence_mode: None,
+            post_serve_actions: vec![],
+            web_socket: None,
+            resource: None,
             #[cfg(feature = "grpc")]
             grpc_request: None,
             #[cfg(feature = "grpc")]
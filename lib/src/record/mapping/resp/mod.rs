@@ -1,5 +1,5 @@
 use crate::{
-    model::response::{body::BodyStub, headers::HttpRespHeadersStub, ResponseStub},
+    model::response::{body::BodyStub, headers::HttpRespHeadersStub, proxy::ProxyStub, stream::StreamStub, ResponseStub},
     record::RecordInput,
 };
 
@@ -15,6 +15,11 @@ impl From<RecordInput<'_>> for ResponseStub {
             transformers: vec![],
             fixed_delay_milliseconds: None,
             delay_distribution: None,
+            chunked_dribble_delay: None,
+            proxy: ProxyStub::default(),
+            stream: StreamStub::default(),
+            fault: None,
+            missing_row_status: None,
         }
     }
 }
@@ -0,0 +1,63 @@
+use std::{path::PathBuf, str::FromStr};
+
+use http_types::{
+    headers::{HeaderName as HttpHeaderName, HeaderValue as HttpHeaderValue, HeaderValues as HttpHeaderValues},
+    Request as HttpRequest, Response as HttpResponse,
+};
+
+use super::{super::writer::StubWriter, logger::RecordLogger};
+use crate::{
+    model::JsonStub,
+    record::{config::RecordConfig, RecordedExchange, RecordedRequest, RecordedResponse},
+    wiremock_rs::Request,
+};
+
+/// Computed again when the recorded stub is served. Besides, a stub response header takes the value of
+/// the request header with the same name, if any.
+const EXCEPT_HEADERS: [&str; 3] = ["content-length", "date", "transfer-encoding"];
+
+/// Writes an exchange forwarded upstream because no stub matched its request as a new stub
+pub(crate) fn record_miss(
+    upstream: &str, output: Option<&PathBuf>, request: &Request, status: u16, headers: &http::HeaderMap, body: &[u8],
+) -> Option<PathBuf> {
+    let mut http_req = HttpRequest::new(request.method, request.url.clone());
+    // a recorded header is matched exactly, which a header with many values e.g. 'accept-encoding: deflate, gzip' never is
+    for (name, values) in request.headers.iter().filter(|(_, values)| values.iter().count() == 1) {
+        http_req.append_header(name, values);
+    }
+    if !request.body.is_empty() {
+        http_req.set_body(request.body.as_slice());
+    }
+    let mut http_resp = HttpResponse::new(status);
+    http_resp.set_body(body);
+    for name in headers.keys() {
+        let values = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| HttpHeaderValue::from_str(v).ok())
+            .collect::<HttpHeaderValues>();
+        if let Ok(name) = HttpHeaderName::from_str(name.as_str()) {
+            http_resp.insert_header(name, &values);
+        }
+    }
+    let mut exchange = RecordedExchange(RecordedRequest(http_req), RecordedResponse(http_resp));
+    let default = RecordConfig::default();
+    let except_request_headers = default.except_request_headers.map(|mut except| {
+        except.extend(EXCEPT_HEADERS);
+        except
+    });
+    let cfg = RecordConfig {
+        output: output.cloned(),
+        except_request_headers,
+        except_response_headers: Some(EXCEPT_HEADERS.to_vec()),
+        ..default
+    };
+    let stub = JsonStub::from((&mut exchange, &cfg));
+    let method = request.method.to_string();
+    StubWriter { stub }
+        .write(upstream, cfg.output.as_ref())
+        .inspect(|file| RecordLogger::success(file.clone(), status, &method, &request.url))
+        .map_err(|e| RecordLogger::error(e, status, &method, &request.url))
+        .ok()
+}
@@ -12,6 +12,7 @@ use super::writer::StubWriter;
 
 pub mod http;
 pub mod logger;
+pub(crate) mod miss;
 pub mod port;
 pub mod proxy;
 pub mod warp_exchange;
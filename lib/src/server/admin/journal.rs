@@ -0,0 +1,313 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use itertools::Itertools;
+use serde_json::{json, Value};
+
+use crate::wiremock_rs::{mock_set::RequestMatch, Match, MockBuilder, Request};
+
+/// A request received by a running server, as exposed by the request journal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedRequest {
+    pub id: String,
+    pub method: String,
+    /// Path and query of the request
+    pub url: String,
+    pub absolute_url: String,
+    /// Multiple values of a header are comma separated
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+    /// Milliseconds since epoch
+    pub logged_date: u128,
+    /// Whether a stub served this request
+    pub was_matched: bool,
+    /// Id of the stub which served this request, if it had one
+    pub stub_id: Option<String>,
+}
+
+impl LoggedRequest {
+    fn new(entry: &Entry) -> Self {
+        let request = &entry.request;
+        let headers = request
+            .headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|v| v.as_str()).join(", ")))
+            .collect();
+        let (was_matched, stub_id) = match &entry.request_match {
+            RequestMatch::Matched(stub_id) => (true, stub_id.clone()),
+            RequestMatch::Unmatched => (false, None),
+        };
+        Self {
+            id: entry.id.clone(),
+            method: request.method.to_string(),
+            url: request.path_and_query(),
+            absolute_url: request.url.to_string(),
+            headers,
+            body: request.body.clone(),
+            logged_date: entry.logged_date,
+            was_matched,
+            stub_id,
+        }
+    }
+
+    /// The request formatted like Wiremock's serve events
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({
+            "id": self.id,
+            "request": {
+                "url": self.url,
+                "absoluteUrl": self.absolute_url,
+                "method": self.method,
+                "headers": self.headers,
+                "body": String::from_utf8_lossy(&self.body),
+                "loggedDate": self.logged_date,
+            },
+            "wasMatched": self.was_matched,
+        });
+        if let Some(stub_id) = self.stub_id.as_ref() {
+            json["stubMapping"] = json!({ "id": stub_id, "uuid": stub_id });
+        }
+        json
+    }
+}
+
+/// A received request, kept once whatever it is used for. Its [LoggedRequest] view is only built when read
+struct Entry {
+    id: String,
+    /// Milliseconds since epoch
+    logged_date: u128,
+    request_match: RequestMatch,
+    request: Request,
+}
+
+impl Entry {
+    fn new(request: &Request, request_match: RequestMatch) -> Self {
+        let logged_date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        Self {
+            id: super::random_id(),
+            logged_date,
+            request_match,
+            request: request.clone(),
+        }
+    }
+
+    fn is_matched(&self) -> bool {
+        self.request_match != RequestMatch::Unmatched
+    }
+}
+
+/// Keeps the last requests received by a server, oldest first.
+/// They are exposed when the journal has a capacity and verified by [crate::Stubr::verify], each within its own bound
+#[derive(Default)]
+pub struct RequestJournal {
+    capacity: Option<usize>,
+    /// The last requests, as many as the capacity allows
+    entries: VecDeque<Arc<Entry>>,
+    /// Requests kept for verifications, whatever the capacity
+    verified: usize,
+    /// The last requests, as many as verifications need. Shared with `entries` rather than copied
+    received: VecDeque<Arc<Entry>>,
+}
+
+impl RequestJournal {
+    /// Requests kept for verifications by default
+    pub const VERIFIED_REQUESTS: usize = 10_000;
+
+    /// * `capacity` - maximum number of requests exposed. Oldest ones are evicted first
+    /// * `verified` - maximum number of requests kept for verifications, [Self::VERIFIED_REQUESTS] when absent
+    pub fn new(capacity: Option<usize>, verified: Option<usize>) -> Self {
+        Self {
+            capacity,
+            verified: verified.unwrap_or(Self::VERIFIED_REQUESTS),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity.is_some()
+    }
+
+    pub(crate) fn record(&mut self, request: &Request, request_match: RequestMatch) {
+        let capacity = self.capacity.unwrap_or_default();
+        if capacity == 0 && self.verified == 0 {
+            return;
+        }
+        let entry = Arc::new(Entry::new(request, request_match));
+        Self::push(&mut self.entries, entry.clone(), capacity);
+        Self::push(&mut self.received, entry, self.verified);
+    }
+
+    fn push(entries: &mut VecDeque<Arc<Entry>>, entry: Arc<Entry>, bound: usize) {
+        if bound > 0 {
+            entries.push_back(entry);
+            while entries.len() > bound {
+                entries.pop_front();
+            }
+        }
+    }
+
+    /// Requests kept for verifications, whether the journal is enabled or not.
+    /// Removing requests from the journal does not affect them, only clearing it does
+    pub(crate) fn received(&self) -> impl DoubleEndedIterator<Item = &Request> {
+        self.received.iter().map(|entry| &entry.request)
+    }
+
+    pub fn requests(&self) -> impl DoubleEndedIterator<Item = LoggedRequest> + '_ {
+        self.entries.iter().map(|entry| LoggedRequest::new(entry))
+    }
+
+    pub fn unmatched(&self) -> impl DoubleEndedIterator<Item = LoggedRequest> + '_ {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_matched())
+            .map(|entry| LoggedRequest::new(entry))
+    }
+
+    pub fn find(&self, id: &str) -> Option<LoggedRequest> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| LoggedRequest::new(entry))
+    }
+
+    /// Requests satisfying every matcher of the given pattern
+    pub(crate) fn matching<'a>(&'a self, pattern: &'a MockBuilder) -> impl DoubleEndedIterator<Item = LoggedRequest> + 'a {
+        self.entries
+            .iter()
+            .filter(|entry| Self::matches(pattern, &entry.request))
+            .map(|entry| LoggedRequest::new(entry))
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<LoggedRequest> {
+        let position = self.entries.iter().position(|entry| entry.id == id)?;
+        self.entries.remove(position).map(|entry| LoggedRequest::new(&entry))
+    }
+
+    /// Removes then returns every request satisfying the given pattern
+    pub(crate) fn remove_matching(&mut self, pattern: &MockBuilder) -> Vec<LoggedRequest> {
+        let (removed, kept): (Vec<_>, Vec<_>) = self.entries.drain(..).partition(|entry| Self::matches(pattern, &entry.request));
+        self.entries = kept.into();
+        removed.iter().map(|entry| LoggedRequest::new(entry)).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.received.clear();
+    }
+
+    fn matches(pattern: &MockBuilder, request: &Request) -> bool {
+        pattern.matchers.iter().all(|matcher| matcher.matches(request))
+    }
+}
+
+#[cfg(test)]
+mod journal_tests {
+    use http_types::Method;
+
+    use crate::wiremock_rs::matchers::{method, path};
+
+    use super::*;
+
+    fn request(method: Method, url: &str) -> Request {
+        Request {
+            url: url.parse().unwrap(),
+            method,
+            headers: Default::default(),
+            body: vec![],
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    #[test]
+    fn should_not_expose_requests_when_disabled() {
+        let mut journal = RequestJournal::new(None, None);
+        journal.record(&request(Method::Get, "http://localhost/a"), RequestMatch::Unmatched);
+        assert!(!journal.is_enabled());
+        assert_eq!(journal.requests().count(), 0);
+        assert_eq!(journal.received().count(), 1);
+    }
+
+    #[test]
+    fn should_keep_nothing_when_disabled_and_not_verifying() {
+        let mut journal = RequestJournal::new(None, Some(0));
+        journal.record(&request(Method::Get, "http://localhost/a"), RequestMatch::Unmatched);
+        assert_eq!(journal.received().count(), 0);
+    }
+
+    #[test]
+    fn should_evict_oldest_requests() {
+        let mut journal = RequestJournal::new(Some(2), None);
+        journal.record(&request(Method::Get, "http://localhost/a"), RequestMatch::Unmatched);
+        journal.record(&request(Method::Get, "http://localhost/b"), RequestMatch::Unmatched);
+        journal.record(&request(Method::Get, "http://localhost/c?d=e"), RequestMatch::Unmatched);
+        let urls = journal.requests().map(|r| r.url).collect_vec();
+        assert_eq!(urls, vec!["/b", "/c?d=e"]);
+        assert_eq!(journal.received().count(), 3);
+        let pattern = crate::wiremock_rs::Mock::given(method("GET"));
+        assert_eq!(journal.remove_matching(&pattern).len(), 2);
+        assert_eq!(journal.requests().count(), 0);
+        assert_eq!(journal.received().count(), 3);
+        journal.clear();
+        assert_eq!(journal.received().count(), 0);
+    }
+
+    #[test]
+    fn should_bound_journal_and_verifications_apart() {
+        let mut journal = RequestJournal::new(Some(1), Some(3));
+        for url in ["a", "b", "c", "d"] {
+            journal.record(
+                &request(Method::Get, &format!("http://localhost/{url}")),
+                RequestMatch::Unmatched,
+            );
+        }
+        assert_eq!(journal.requests().map(|r| r.url).collect_vec(), vec!["/d"]);
+        let received = journal.received().map(|r| r.url.path()).collect_vec();
+        assert_eq!(received, vec!["/b", "/c", "/d"]);
+        // both keep the same copy of the last request
+        assert!(Arc::ptr_eq(&journal.entries[0], &journal.received[2]));
+    }
+
+    #[test]
+    fn should_filter_unmatched() {
+        let mut journal = RequestJournal::new(Some(10), None);
+        journal.record(
+            &request(Method::Get, "http://localhost/a"),
+            RequestMatch::Matched(Some("abcd".to_string())),
+        );
+        journal.record(&request(Method::Get, "http://localhost/b"), RequestMatch::Unmatched);
+        let unmatched = journal.unmatched().map(|r| r.url).collect_vec();
+        assert_eq!(unmatched, vec!["/b"]);
+    }
+
+    #[test]
+    fn should_find_and_remove_matching() {
+        let mut journal = RequestJournal::new(Some(10), None);
+        journal.record(&request(Method::Get, "http://localhost/a"), RequestMatch::Unmatched);
+        journal.record(&request(Method::Post, "http://localhost/a"), RequestMatch::Unmatched);
+        journal.record(&request(Method::Get, "http://localhost/b"), RequestMatch::Unmatched);
+        let pattern = crate::wiremock_rs::Mock::given(method("GET")).and(path("/a"));
+        assert_eq!(journal.matching(&pattern).count(), 1);
+        let removed = journal.remove_matching(&pattern);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].method, "GET");
+        assert_eq!(journal.requests().count(), 2);
+    }
+
+    #[test]
+    fn should_remove_by_id() {
+        let mut journal = RequestJournal::new(Some(10), None);
+        journal.record(&request(Method::Get, "http://localhost/a"), RequestMatch::Unmatched);
+        let id = journal.requests().next().unwrap().id.clone();
+        assert!(journal.find(&id).is_some());
+        assert!(journal.remove(&id).is_some());
+        assert!(journal.find(&id).is_none());
+        assert!(journal.remove(&id).is_none());
+    }
+}
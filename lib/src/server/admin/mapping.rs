@@ -0,0 +1,202 @@
+use std::{fs::OpenOptions, path::PathBuf, sync::Arc};
+
+use serde_json::Value;
+
+use super::near_miss::StubMatchers;
+use crate::{
+    error::{StubrError, StubrResult},
+    model::JsonStub,
+};
+
+/// A stub mounted on a running server as seen by the admin api.
+/// The original json is kept so that it can be listed or saved without losing any field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub id: String,
+    pub stub: Value,
+    /// File this mapping was loaded from or saved into
+    pub file: Option<PathBuf>,
+    /// Matchers of the stub 'request', explaining near misses without parsing the stub again
+    pub(crate) matchers: Arc<StubMatchers>,
+}
+
+impl Mapping {
+    const ID: &'static str = "id";
+    const UUID: &'static str = "uuid";
+    const PRIORITY: &'static str = "priority";
+    const JSON_EXTENSION: &'static str = "json";
+
+    /// Validates a json stub and identifies it.
+    /// * `id` - supersedes any id defined in the stub. When both are absent, a random one is generated
+    /// * `file` - where the stub comes from, if any
+    pub fn try_new(stub: Value, id: Option<String>, file: Option<PathBuf>) -> StubrResult<Self> {
+        let fields = stub
+            .as_object()
+            .ok_or(StubrError::InvalidMapping("a stub has to be a json object"))?;
+        let id = id
+            .or_else(|| fields.get(Self::UUID).and_then(Value::as_str).map(str::to_string))
+            .or_else(|| fields.get(Self::ID).and_then(Value::as_str).map(str::to_string))
+            .unwrap_or_else(super::random_id);
+        if !Self::is_valid_id(&id) {
+            return Err(StubrError::InvalidMapping("its id has to be a plain name, not a path"));
+        }
+        serde_json::from_value::<JsonStub>(stub.clone())?;
+        let matchers = stub
+            .get("request")
+            .and_then(Value::as_object)
+            .map(StubMatchers::new)
+            .unwrap_or_default();
+        Ok(Self {
+            id,
+            stub,
+            file,
+            matchers: Arc::new(matchers),
+        })
+    }
+
+    /// Ids are supplied by admin api clients and end up in file names: they must not escape the stub directory
+    fn is_valid_id(id: &str) -> bool {
+        !id.is_empty() && !id.contains(['/', '\\', ':', '\0']) && !id.contains("..") && !std::path::Path::new(id).has_root()
+    }
+
+    /// Name of the file this mapping is saved into, only made of characters safe in a file name
+    pub fn file_name(&self) -> String {
+        let name = self
+            .id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        format!("{name}.{}", Self::JSON_EXTENSION)
+    }
+
+    /// The stub as exposed by the admin api, always identified
+    pub fn to_json(&self) -> Value {
+        let mut stub = self.stub.clone();
+        if let Some(fields) = stub.as_object_mut() {
+            fields.insert(Self::ID.to_string(), Value::String(self.id.clone()));
+            fields.insert(Self::UUID.to_string(), Value::String(self.id.clone()));
+        }
+        stub
+    }
+
+    pub fn priority(&self) -> Option<u8> {
+        self.stub
+            .get(Self::PRIORITY)
+            .and_then(Value::as_u64)
+            .and_then(|p| u8::try_from(p).ok())
+    }
+}
+
+impl TryFrom<&PathBuf> for Mapping {
+    type Error = StubrError;
+
+    fn try_from(file: &PathBuf) -> StubrResult<Self> {
+        let content = OpenOptions::new().read(true).open(file)?;
+        Self::try_new(serde_json::from_reader(content)?, None, Some(file.to_path_buf()))
+    }
+}
+
+impl TryFrom<&Mapping> for JsonStub {
+    type Error = StubrError;
+
+    fn try_from(mapping: &Mapping) -> StubrResult<Self> {
+        Ok(serde_json::from_value(mapping.stub.clone())?)
+    }
+}
+
+#[cfg(test)]
+mod mapping_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn should_keep_stub_uuid() {
+        let mapping = Mapping::try_new(json!({"uuid": "abcd", "id": "efgh", "request": {"method": "GET"}}), None, None).unwrap();
+        assert_eq!(mapping.id, "abcd");
+        assert_eq!(mapping.to_json()["id"], json!("abcd"));
+    }
+
+    #[test]
+    fn should_fallback_to_stub_id() {
+        let mapping = Mapping::try_new(json!({"id": "abcd", "request": {"method": "GET"}}), None, None).unwrap();
+        assert_eq!(mapping.id, "abcd");
+        assert_eq!(mapping.to_json()["uuid"], json!("abcd"));
+    }
+
+    #[test]
+    fn supplied_id_should_supersede_stub_one() {
+        let mapping = Mapping::try_new(
+            json!({"id": "abcd", "request": {"method": "GET"}}),
+            Some("efgh".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(mapping.id, "efgh");
+        assert_eq!(mapping.to_json()["id"], json!("efgh"));
+        assert_eq!(mapping.to_json()["uuid"], json!("efgh"));
+    }
+
+    #[test]
+    fn should_not_alter_original_stub() {
+        let stub = json!({"request": {"method": "GET"}});
+        let mapping = Mapping::try_new(stub.clone(), None, None).unwrap();
+        assert_eq!(mapping.stub, stub);
+    }
+
+    #[test]
+    fn should_generate_random_id_when_absent() {
+        let a = Mapping::try_new(json!({"request": {"method": "GET"}}), None, None).unwrap();
+        let b = Mapping::try_new(json!({"request": {"method": "GET"}}), None, None).unwrap();
+        assert_eq!(a.id.len(), 36);
+        assert_eq!(a.id.matches('-').count(), 4);
+        assert_ne!(a.id, b.id);
+    }
+
+    #[test]
+    fn should_fail_when_id_is_a_path() {
+        for id in ["../../.ssh/authorized_keys", "/etc/passwd", "a/b", "a\\b", "..", "C:stub", ""] {
+            let stub = json!({"id": id, "request": {"method": "GET"}});
+            assert!(Mapping::try_new(stub, None, None).is_err(), "{id}");
+        }
+        assert!(Mapping::try_new(json!({"request": {}}), Some("../a".to_string()), None).is_err());
+    }
+
+    #[test]
+    fn file_name_should_only_keep_safe_characters() {
+        let mapping = Mapping::try_new(json!({"id": "a b.c-d_e", "request": {"method": "GET"}}), None, None).unwrap();
+        assert_eq!(mapping.file_name(), "a_b_c-d_e.json");
+    }
+
+    #[test]
+    fn should_build_request_matchers_when_created() {
+        let mapping = Mapping::try_new(json!({"request": {"method": "GET"}}), None, None).unwrap();
+        assert!(!mapping.matchers.is_empty());
+        let mapping = Mapping::try_new(json!({"response": {"status": 200}}), None, None).unwrap();
+        assert!(mapping.matchers.is_empty());
+    }
+
+    #[test]
+    fn should_fail_when_not_an_object() {
+        assert!(Mapping::try_new(json!([]), None, None).is_err());
+    }
+
+    #[test]
+    fn should_fail_when_not_a_stub() {
+        assert!(Mapping::try_new(json!({"request": {"method": 42}}), None, None).is_err());
+    }
+
+    #[test]
+    fn should_read_priority() {
+        let mapping = Mapping::try_new(json!({"priority": 2, "request": {"method": "GET"}}), None, None).unwrap();
+        assert_eq!(mapping.priority(), Some(2));
+        let mapping = Mapping::try_new(json!({"request": {"method": "GET"}}), None, None).unwrap();
+        assert_eq!(mapping.priority(), None);
+    }
+}
@@ -0,0 +1,465 @@
+//! Wiremock compatible admin api.
+//! See [https://wiremock.org/docs/standalone/admin-api-reference/]
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs::{create_dir_all, File},
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError},
+    time::SystemTime,
+};
+
+use http::{header::CONTENT_TYPE, Method, StatusCode};
+use hyper::Body;
+use itertools::Itertools;
+use log::info;
+use serde_json::{json, Value};
+
+use journal::{LoggedRequest, RequestJournal};
+use mapping::Mapping;
+use near_miss::NearMissReport;
+use scenario::Scenarios;
+
+use crate::{
+    cloud::metrics::Metrics,
+    error::{StubrError, StubrResult},
+    model::{request::RequestStub, JsonStub},
+    server::{any_stub::AnyStubs, record_on_miss::MissRecorder, stub_finder::StubFinder},
+    wiremock_rs::{
+        mock_server::{bare_server::MockServerState, hyper::DynError},
+        mock_set::RequestMatch,
+        Mock, MockBuilder, Request,
+    },
+    Config, RecordOnMiss,
+};
+
+pub mod journal;
+pub mod mapping;
+pub mod near_miss;
+pub mod reload;
+pub mod scenario;
+pub mod state;
+pub mod verification;
+
+type MockSet = crate::wiremock_rs::mock_set::MountedMockSet;
+
+/// Keeps track of the stubs mounted on a server so that they can be managed at runtime
+#[derive(Default)]
+pub struct StubrAdmin {
+    config: Config,
+    /// Upstream requests no stub matches are forwarded to
+    record_on_miss: Option<RecordOnMiss>,
+    /// Directory where mappings created at runtime are saved
+    root: Option<PathBuf>,
+    /// Stub files or directories the server was started with
+    sources: Vec<PathBuf>,
+    /// Last modification time of every stub file found in sources, valid or not
+    modified: HashMap<PathBuf, Option<SystemTime>>,
+    /// Stub files to restore when the server is reset
+    files: Vec<PathBuf>,
+    /// Mounted mappings, in registration order
+    mappings: Vec<Mapping>,
+    /// Near misses of the last unmatched requests, oldest first
+    near_misses: VecDeque<NearMissReport>,
+    /// Requests matched by each stub file since startup, even those since unmounted
+    hits: HashMap<PathBuf, u64>,
+}
+
+impl StubrAdmin {
+    pub const PATH: &'static str = "/__admin";
+    const NAME: &'static str = "name";
+    /// Near miss reports kept for later, so that a long running server does not grow endlessly
+    const MAX_NEAR_MISS_REPORTS: usize = 100;
+
+    pub fn new(config: Config, record_on_miss: Option<RecordOnMiss>, stubs: &AnyStubs) -> Self {
+        Self {
+            config,
+            record_on_miss,
+            root: stubs.root(),
+            sources: stubs.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_admin(path: &str) -> bool {
+        path.strip_prefix(Self::PATH)
+            .map(|rest| rest.is_empty() || rest.starts_with('/'))
+            .unwrap_or_default()
+    }
+
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Mapping> {
+        self.mappings.iter().find(|m| m.id == id)
+    }
+
+    pub fn near_misses(&self) -> impl DoubleEndedIterator<Item = &NearMissReport> {
+        self.near_misses.iter()
+    }
+
+    pub fn clear_near_misses(&mut self) {
+        self.near_misses.clear();
+    }
+
+    /// Counts a request matched by a stub loaded from a file
+    pub(crate) fn record_hit(&mut self, request_match: &RequestMatch) {
+        if let RequestMatch::Matched(Some(id)) = request_match {
+            if let Some(file) = self.find(id).and_then(|m| m.file.clone()) {
+                *self.hits.entry(file).or_default() += 1;
+            }
+        }
+    }
+
+    /// Name of the stub which matched a request: its 'name', otherwise its file name or its id
+    pub(crate) fn stub_name(&self, request_match: &RequestMatch) -> Option<String> {
+        let RequestMatch::Matched(Some(id)) = request_match else {
+            return None;
+        };
+        let mapping = self.find(id);
+        let name = mapping
+            .and_then(|m| m.stub.get(Self::NAME))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let file_name = || {
+            mapping
+                .and_then(|m| m.file.as_deref())
+                .and_then(Path::file_stem)
+                .map(|stem| stem.to_string_lossy().to_string())
+        };
+        Some(name.or_else(file_name).unwrap_or_else(|| id.to_string()))
+    }
+
+    /// Requests matched by each stub file, including the mounted ones no request matched
+    pub fn hits(&self) -> BTreeMap<PathBuf, u64> {
+        let mounted = self.mappings.iter().filter_map(|m| m.file.clone()).map(|file| (file, 0));
+        mounted
+            .chain(self.hits.iter().map(|(file, hits)| (file.clone(), *hits)))
+            .collect()
+    }
+
+    /// Like [Self::hits] but also counting the stub files found in the directories of the sources, mounted or not
+    pub(crate) fn coverage(&self) -> BTreeMap<PathBuf, u64> {
+        let dirs = self
+            .sources
+            .iter()
+            .map(|source| {
+                if source.is_dir() {
+                    source.as_path()
+                } else {
+                    source.parent().unwrap_or(source)
+                }
+            })
+            .unique();
+        dirs.flat_map(StubFinder::find_all_stubs)
+            .map(|file| (file, 0))
+            .chain(self.hits())
+            .collect()
+    }
+
+    /// Explains why no stub matched a request: in the response body, in the logs and keeps it for later
+    pub(crate) fn report_near_misses(&mut self, request: &Request, response: &mut http_types::Response) {
+        let report = NearMissReport::new(request, &self.mappings);
+        if self.config.verbose {
+            info!("{report}");
+        }
+        if !report.near_misses.is_empty() {
+            response.set_body(report.to_string());
+        }
+        self.near_misses.push_back(report);
+        if self.near_misses.len() > Self::MAX_NEAR_MISS_REPORTS {
+            self.near_misses.pop_front();
+        }
+    }
+
+    /// Forwards a request no stub matched upstream then records it, when configured to
+    pub(crate) fn record_on_miss(&self, response: &mut http_types::Response) {
+        if let Some(record_on_miss) = self.record_on_miss.as_ref() {
+            let recorder = MissRecorder::new(record_on_miss, self.root.as_ref());
+            response.ext_mut().insert(recorder.proxy());
+            response.ext_mut().insert(recorder);
+        }
+    }
+
+    /// Converts then mounts a mapping
+    /// Fails when a stub with the same id is already mounted, instead of serving both
+    pub(crate) fn try_mount(&mut self, mock_set: &mut MockSet, mapping: Mapping) -> StubrResult<&Mapping> {
+        if self.find(&mapping.id).is_some() {
+            return Err(StubrError::DuplicateStubId(mapping.id));
+        }
+        let mock = self.try_mock(&mapping)?;
+        Ok(self.mount(mock_set, mapping, mock))
+    }
+
+    /// Replaces an existing mapping. Returns `None` when there is no mapping with this id
+    pub(crate) fn try_update(&mut self, mock_set: &mut MockSet, id: &str, stub: Value) -> StubrResult<Option<&Mapping>> {
+        if let Some(position) = self.mappings.iter().position(|m| m.id == id) {
+            let file = self.mappings[position].file.clone();
+            let mapping = Mapping::try_new(stub, Some(id.to_string()), file)?;
+            let mock = self.try_mock(&mapping)?;
+            mock_set.remove_by_id(id);
+            mock_set.register(mock);
+            self.mappings[position] = mapping;
+            Ok(self.mappings.get(position))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub(crate) fn remove(&mut self, mock_set: &mut MockSet, id: &str) -> Option<Mapping> {
+        let position = self.mappings.iter().position(|m| m.id == id)?;
+        mock_set.remove_by_id(id);
+        Some(self.mappings.remove(position))
+    }
+
+    pub(crate) fn remove_all(&mut self, mock_set: &mut MockSet) {
+        for mapping in self.mappings.drain(..) {
+            mock_set.remove_by_id(&mapping.id);
+        }
+    }
+
+    /// Drops every mapping then mounts back the ones defined in stub files
+    pub(crate) fn reset(&mut self, mock_set: &mut MockSet) {
+        self.remove_all(mock_set);
+        let mappings = self
+            .files
+            .iter()
+            .filter_map(|file| Mapping::try_from(file).ok())
+            .sorted_by(|a, b| a.priority().cmp(&b.priority()))
+            .collect_vec();
+        for mapping in mappings {
+            let _ = self.try_mount(mock_set, mapping);
+        }
+    }
+
+    /// Writes every mapping which does not come from a file in the stub directory.
+    /// Returns the number of saved mappings
+    pub(crate) fn try_save(&mut self) -> StubrResult<usize> {
+        let root = self.root.clone().ok_or(StubrError::MissingMappingsDir)?;
+        let mut saved = 0;
+        for mapping in self.mappings.iter_mut().filter(|m| m.file.is_none()) {
+            create_dir_all(&root)?;
+            let file = root.join(mapping.file_name());
+            serde_json::to_writer_pretty(File::create(&file)?, &mapping.to_json())?;
+            self.files.push(file.clone());
+            mapping.file = Some(file);
+            saved += 1;
+        }
+        Ok(saved)
+    }
+
+    fn try_mock(&self, mapping: &Mapping) -> StubrResult<Mock> {
+        let file = mapping.file.as_deref().unwrap_or_else(|| Path::new(&mapping.id));
+        Ok(JsonStub::try_from(mapping)?
+            .try_creating_from(&self.config, file)?
+            .identified(&mapping.id))
+    }
+
+    fn mount(&mut self, mock_set: &mut MockSet, mapping: Mapping, mock: Mock) -> &Mapping {
+        mock_set.register(mock);
+        if let Some(file) = mapping.file.as_ref().filter(|f| !self.files.contains(f)) {
+            self.files.push(file.clone());
+        }
+        self.mappings.push(mapping);
+        &self.mappings[self.mappings.len() - 1]
+    }
+}
+
+/// Http layer
+impl StubrAdmin {
+    pub(crate) async fn handle(
+        request: hyper::Request<Body>, server_state: Arc<tokio::sync::RwLock<MockServerState>>,
+    ) -> Result<hyper::Response<Body>, DynError> {
+        let (parts, body) = request.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+        let path = parts.uri.path().trim_start_matches(Self::PATH).trim_matches('/');
+        let segments = path.split('/').collect_vec();
+
+        let mut state = server_state.write().await;
+        let MockServerState {
+            mock_set,
+            admin,
+            journal,
+            metrics,
+            ..
+        } = &mut *state;
+        match (parts.method, segments.as_slice()) {
+            (Method::GET, ["metrics"]) => Ok(hyper::Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, Metrics::CONTENT_TYPE)
+                .body(Body::from(
+                    metrics
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .render(admin.mappings().len()),
+                ))?),
+            (Method::GET, ["mappings"]) => {
+                let mappings = admin.mappings().iter().map(Mapping::to_json).collect_vec();
+                Self::json(
+                    StatusCode::OK,
+                    &json!({ "mappings": mappings, "meta": { "total": mappings.len() } }),
+                )
+            },
+            (Method::POST, ["mappings"]) => match Self::try_parse(&body).and_then(|stub| Mapping::try_new(stub, None, None)) {
+                Ok(mapping) => match admin.try_mount(mock_set, mapping) {
+                    Ok(mapping) => Self::json(StatusCode::CREATED, &mapping.to_json()),
+                    Err(e) => Self::error(StatusCode::UNPROCESSABLE_ENTITY, e),
+                },
+                Err(e) => Self::error(StatusCode::UNPROCESSABLE_ENTITY, e),
+            },
+            (Method::DELETE, ["mappings"]) => {
+                admin.remove_all(mock_set);
+                Self::empty(StatusCode::OK)
+            },
+            (Method::POST, ["mappings", "save"]) => match admin.try_save() {
+                Ok(_) => Self::empty(StatusCode::OK),
+                Err(e) => Self::error(StatusCode::INTERNAL_SERVER_ERROR, e),
+            },
+            (Method::POST, ["reset"]) => {
+                admin.reset(mock_set);
+                mock_set.scenarios.reset();
+                admin.clear_near_misses();
+                journal.clear();
+                Self::empty(StatusCode::OK)
+            },
+            (Method::POST, ["mappings", "reset"]) => {
+                admin.reset(mock_set);
+                Self::empty(StatusCode::OK)
+            },
+            (Method::GET, ["mappings", id]) => match admin.find(id) {
+                Some(mapping) => Self::json(StatusCode::OK, &mapping.to_json()),
+                None => Self::empty(StatusCode::NOT_FOUND),
+            },
+            (Method::PUT, ["mappings", id]) => match Self::try_parse(&body).and_then(|stub| admin.try_update(mock_set, id, stub)) {
+                Ok(Some(mapping)) => Self::json(StatusCode::OK, &mapping.to_json()),
+                Ok(None) => Self::empty(StatusCode::NOT_FOUND),
+                Err(e) => Self::error(StatusCode::UNPROCESSABLE_ENTITY, e),
+            },
+            (Method::DELETE, ["mappings", id]) => match admin.remove(mock_set, id) {
+                Some(_) => Self::empty(StatusCode::OK),
+                None => Self::empty(StatusCode::NOT_FOUND),
+            },
+            (Method::GET, ["scenarios"]) => Self::json(StatusCode::OK, &mock_set.scenarios.to_json()),
+            (Method::POST, ["scenarios", "reset"]) => {
+                mock_set.scenarios.reset();
+                Self::empty(StatusCode::OK)
+            },
+            (Method::PUT, ["scenarios", name, "state"]) => {
+                // like Wiremock, an absent state moves the scenario back to its initial state
+                let state = serde_json::from_slice::<Value>(&body).ok();
+                let state = state
+                    .as_ref()
+                    .and_then(|s| s.get("state")?.as_str())
+                    .unwrap_or(Scenarios::STARTED);
+                if mock_set.scenarios.set(name, state) {
+                    Self::empty(StatusCode::OK)
+                } else {
+                    Self::empty(StatusCode::NOT_FOUND)
+                }
+            },
+            (Method::GET, ["requests"]) => {
+                let requests = journal.requests().rev().map(|r| r.to_json()).collect_vec();
+                Self::json(
+                    StatusCode::OK,
+                    &json!({
+                        "requests": requests,
+                        "meta": { "total": requests.len() },
+                        "requestJournalDisabled": !journal.is_enabled(),
+                    }),
+                )
+            },
+            (Method::DELETE, ["requests"]) | (Method::POST, ["requests", "reset"]) => {
+                admin.clear_near_misses();
+                journal.clear();
+                Self::empty(StatusCode::OK)
+            },
+            (Method::GET, ["requests", "unmatched"]) => Self::requests(journal, journal.unmatched().rev().collect()),
+            (Method::GET, ["requests", "unmatched", "near-misses"]) => {
+                let near_misses = admin.near_misses().rev().map(NearMissReport::to_json).collect_vec();
+                Self::json(StatusCode::OK, &json!({ "nearMisses": near_misses }))
+            },
+            (Method::POST, ["requests", "count"]) => match Self::try_parse_pattern(&body) {
+                Ok(pattern) => Self::json(StatusCode::OK, &json!({ "count": journal.matching(&pattern).count() })),
+                Err(e) => Self::error(StatusCode::UNPROCESSABLE_ENTITY, e),
+            },
+            (Method::POST, ["requests", "find"]) => match Self::try_parse_pattern(&body) {
+                Ok(pattern) => Self::requests(journal, journal.matching(&pattern).collect()),
+                Err(e) => Self::error(StatusCode::UNPROCESSABLE_ENTITY, e),
+            },
+            (Method::POST, ["requests", "remove"]) => match Self::try_parse_pattern(&body) {
+                Ok(pattern) => {
+                    let removed = journal.remove_matching(&pattern);
+                    Self::requests(journal, removed)
+                },
+                Err(e) => Self::error(StatusCode::UNPROCESSABLE_ENTITY, e),
+            },
+            (Method::GET, ["requests", id]) => match journal.find(id) {
+                Some(request) => Self::json(StatusCode::OK, &request.to_json()),
+                None => Self::empty(StatusCode::NOT_FOUND),
+            },
+            (Method::DELETE, ["requests", id]) => match journal.remove(id) {
+                Some(_) => Self::empty(StatusCode::OK),
+                None => Self::empty(StatusCode::NOT_FOUND),
+            },
+            _ => Self::empty(StatusCode::NOT_FOUND),
+        }
+    }
+
+    /// A request pattern has the same shape as a stub's 'request'
+    fn try_parse_pattern(body: &[u8]) -> StubrResult<MockBuilder> {
+        let pattern = if body.is_empty() {
+            RequestStub::default()
+        } else {
+            serde_json::from_slice::<RequestStub>(body)?
+        };
+        MockBuilder::try_from(&pattern)
+    }
+
+    fn requests(journal: &RequestJournal, requests: Vec<LoggedRequest>) -> Result<hyper::Response<Body>, DynError> {
+        let requests = requests.iter().map(LoggedRequest::to_json).collect_vec();
+        Self::json(
+            StatusCode::OK,
+            &json!({ "requests": requests, "requestJournalDisabled": !journal.is_enabled() }),
+        )
+    }
+
+    fn try_parse(body: &[u8]) -> StubrResult<Value> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    fn json(status: StatusCode, body: &Value) -> Result<hyper::Response<Body>, DynError> {
+        Ok(hyper::Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(body)?))?)
+    }
+
+    /// Errors are formatted like Wiremock's ones
+    fn error(status: StatusCode, error: StubrError) -> Result<hyper::Response<Body>, DynError> {
+        Self::json(status, &json!({ "errors": [{ "title": error.to_string() }] }))
+    }
+
+    fn empty(status: StatusCode) -> Result<hyper::Response<Body>, DynError> {
+        Ok(hyper::Response::builder().status(status).body(Body::empty())?)
+    }
+}
+
+/// Random uuid formatted like the ones Wiremock generates
+pub(crate) fn random_id() -> String {
+    let hex = format!("{:032x}", rand::random::<u128>());
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+#[cfg(test)]
+mod admin_tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_admin_paths() {
+        assert!(StubrAdmin::is_admin("/__admin"));
+        assert!(StubrAdmin::is_admin("/__admin/"));
+        assert!(StubrAdmin::is_admin("/__admin/mappings"));
+        assert!(!StubrAdmin::is_admin("/__administrator"));
+        assert!(!StubrAdmin::is_admin("/api/__admin"));
+    }
+}
@@ -0,0 +1,439 @@
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+use serde_json::{json, Map, Value};
+
+use super::mapping::Mapping;
+use crate::{
+    model::request::{state::StateMatcher, RequestStub},
+    wiremock_rs::{Match, MockBuilder, Request},
+};
+
+/// Part of a request a stub matcher applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestPart {
+    Method,
+    Url,
+    Header,
+    Query,
+    Body,
+    Auth,
+    ClientCertificate,
+    Protocol,
+    State,
+}
+
+impl RequestPart {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Method => "method",
+            Self::Url => "url",
+            Self::Header => "header",
+            Self::Query => "query",
+            Self::Body => "body",
+            Self::Auth => "auth",
+            Self::ClientCertificate => "clientCertificate",
+            Self::Protocol => "protocol",
+            Self::State => "state",
+        }
+    }
+}
+
+/// Outcome of a single stub matcher against a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatcherDiff {
+    pub part: RequestPart,
+    /// Matcher key in the stub e.g. 'urlPath' or a header name
+    pub name: String,
+    /// Matcher definition as written in the stub
+    pub expected: Value,
+    /// Matching part of the request, absent when the request does not have it
+    pub actual: Option<String>,
+    pub matched: bool,
+}
+
+impl MatcherDiff {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "part": self.part.as_str(),
+            "name": self.name,
+            "expected": self.expected,
+            "actual": self.actual,
+            "matched": self.matched,
+        })
+    }
+}
+
+impl Display for MatcherDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.matched { "==" } else { "!=" };
+        let actual = self.actual.as_deref().unwrap_or("<absent>");
+        write!(
+            f,
+            "{sign} {} '{}': expected {} got '{actual}'",
+            self.part.as_str(),
+            self.name,
+            self.expected
+        )
+    }
+}
+
+/// A single matcher of a stub 'request', built once when the stub is mounted rather than on every unmatched request
+#[derive(Debug)]
+struct PartMatcher {
+    part: RequestPart,
+    /// Matcher key in the stub e.g. 'urlPath' or a header name
+    name: String,
+    /// Matcher definition as written in the stub
+    expected: Value,
+    pattern: MockBuilder,
+    /// Reads the server's state, for a state matcher
+    state: Option<StateMatcher>,
+}
+
+impl PartMatcher {
+    fn actual(&self, request: &Request) -> Option<String> {
+        let header = |name: &str| {
+            request
+                .headers
+                .iter()
+                .find(|(k, _)| k.as_str().eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.iter().map(|v| v.as_str()).join(", "))
+        };
+        let name = self.name.as_str();
+        match self.part {
+            RequestPart::Method => Some(request.method.to_string()),
+            RequestPart::Url if name == "urlPath" || name == "urlPathPattern" => Some(request.url.path().to_string()),
+            RequestPart::Url => Some(request.path_and_query()),
+            RequestPart::Header => header(name),
+            RequestPart::Query => request.url.query_pairs().find(|(k, _)| k == name).map(|(_, v)| v.to_string()),
+            RequestPart::Body => Some(String::from_utf8_lossy(&request.body).to_string()),
+            RequestPart::Auth => header("authorization"),
+            RequestPart::ClientCertificate => request.client_certificate.as_ref().map(|c| c.subject.clone()),
+            RequestPart::Protocol => Some(request.version.to_string()),
+            RequestPart::State => self.state.as_ref().and_then(|state| state.stored(request)),
+        }
+    }
+}
+
+/// Every matcher of a stub 'request' on its own, to explain why a request did not match the stub
+#[derive(Debug, Default)]
+pub(crate) struct StubMatchers(Vec<PartMatcher>);
+
+impl StubMatchers {
+    /// * `fields` - the stub 'request'. Matchers which cannot be built are left out
+    pub(crate) fn new(fields: &Map<String, Value>) -> Self {
+        let matchers = fields
+            .iter()
+            .flat_map(|(key, value)| Self::split(key, value))
+            .filter_map(|(part, name, expected, pattern)| {
+                let pattern = serde_json::from_value::<RequestStub>(pattern).ok()?;
+                let state = pattern.state.clone().map(StateMatcher::new);
+                let pattern = MockBuilder::try_from(&pattern).ok()?;
+                Some(PartMatcher {
+                    part,
+                    name,
+                    expected,
+                    pattern,
+                    state,
+                })
+            })
+            .collect_vec();
+        Self(matchers)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Outcome of every matcher against a request
+    pub(crate) fn diffs(&self, request: &Request) -> Vec<MatcherDiff> {
+        self.0
+            .iter()
+            .map(|matcher| MatcherDiff {
+                part: matcher.part,
+                name: matcher.name.clone(),
+                expected: matcher.expected.clone(),
+                actual: matcher.actual(request),
+                matched: matcher.pattern.matchers.iter().all(|m| m.matches(request)),
+            })
+            .collect_vec()
+    }
+
+    /// Splits a field of the stub 'request' into standalone request patterns, one per matcher.
+    /// Also returns the matcher name and definition.
+    fn split(key: &str, value: &Value) -> Vec<(RequestPart, String, Value, Value)> {
+        let single = |part: RequestPart| vec![(part, key.to_string(), value.clone(), json!({ key: value }))];
+        match key {
+            "method" => single(RequestPart::Method),
+            "url" | "urlPath" | "urlPattern" | "urlPathPattern" => single(RequestPart::Url),
+            "basicAuth" | "jwtAuth" => single(RequestPart::Auth),
+            "clientCertificate" => single(RequestPart::ClientCertificate),
+            "protocol" => single(RequestPart::Protocol),
+            "state" => single(RequestPart::State),
+            "headers" | "queryParameters" => {
+                let part = if key == "headers" {
+                    RequestPart::Header
+                } else {
+                    RequestPart::Query
+                };
+                value
+                    .as_object()
+                    .map(|all| {
+                        all.iter()
+                            .map(|(name, matcher)| (part, name.to_string(), matcher.clone(), json!({ key: { name: matcher } })))
+                            .collect_vec()
+                    })
+                    .unwrap_or_default()
+            },
+            "bodyPatterns" => value
+                .as_array()
+                .map(|all| {
+                    all.iter()
+                        .map(|pattern| (RequestPart::Body, key.to_string(), pattern.clone(), json!({ key: [pattern] })))
+                        .collect_vec()
+                })
+                .unwrap_or_default(),
+            // not a matcher
+            _ => vec![],
+        }
+    }
+}
+
+/// Matchers are built from their definition, comparing the latter is enough
+impl PartialEq for StubMatchers {
+    fn eq(&self, other: &Self) -> bool {
+        let definitions = |matchers: &Self| {
+            matchers
+                .0
+                .iter()
+                .map(|m| (m.part, m.name.clone(), m.expected.clone()))
+                .collect_vec()
+        };
+        definitions(self) == definitions(other)
+    }
+}
+
+/// A stub which did not match a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearMiss {
+    pub stub_id: String,
+    /// Share of the stub matchers which did not match, from 0 (all matched) to 1 (none matched)
+    pub distance: f64,
+    /// Every matcher of the stub, matched or not
+    pub diffs: Vec<MatcherDiff>,
+}
+
+impl NearMiss {
+    /// `None` when the stub has no http request matcher
+    fn try_new(mapping: &Mapping, request: &Request) -> Option<Self> {
+        if mapping.matchers.is_empty() {
+            return None;
+        }
+        let diffs = mapping.matchers.diffs(request);
+        Some(Self {
+            stub_id: mapping.id.clone(),
+            distance: Self::distance(&diffs),
+            diffs,
+        })
+    }
+
+    /// Share of the matchers which did not match
+    pub(crate) fn distance(diffs: &[MatcherDiff]) -> f64 {
+        let unmatched = diffs.iter().filter(|d| !d.matched).count();
+        unmatched as f64 / diffs.len().max(1) as f64
+    }
+}
+
+impl Display for NearMiss {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "stub '{}' at distance {:.2}", self.stub_id, self.distance)?;
+        for diff in &self.diffs {
+            writeln!(f, "    {diff}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Closest stubs of a request no stub matched, closest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearMissReport {
+    pub method: String,
+    /// Path and query of the unmatched request
+    pub url: String,
+    pub near_misses: Vec<NearMiss>,
+}
+
+impl NearMissReport {
+    /// Number of stubs reported
+    const MAX_NEAR_MISSES: usize = 3;
+
+    pub(crate) fn new(request: &Request, mappings: &[Mapping]) -> Self {
+        let near_misses = mappings
+            .iter()
+            .filter_map(|mapping| NearMiss::try_new(mapping, request))
+            .sorted_by(|a, b| a.distance.total_cmp(&b.distance))
+            .take(Self::MAX_NEAR_MISSES)
+            .collect_vec();
+        Self {
+            method: request.method.to_string(),
+            url: request.path_and_query(),
+            near_misses,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "request": { "method": self.method, "url": self.url },
+            "nearMisses": self.near_misses.iter().map(|n| json!({
+                "stubId": n.stub_id,
+                "distance": n.distance,
+                "diffs": n.diffs.iter().map(MatcherDiff::to_json).collect_vec(),
+            })).collect_vec(),
+        })
+    }
+}
+
+impl Display for NearMissReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No stub matched request '{} {}'", self.method, self.url)?;
+        if self.near_misses.is_empty() {
+            return writeln!(f);
+        }
+        writeln!(f, ". Closest stubs:")?;
+        for near_miss in &self.near_misses {
+            write!(f, "  {near_miss}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod near_miss_tests {
+    use http_types::{
+        headers::{HeaderName, HeaderValue},
+        Method,
+    };
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn mapping(id: &str, request: Value) -> Mapping {
+        Mapping::try_new(json!({ "request": request }), Some(id.to_string()), None).unwrap()
+    }
+
+    fn request(method: Method, url: &str, headers: &[(&str, &str)], body: &str) -> Request {
+        Request {
+            url: format!("http://localhost{url}").parse().unwrap(),
+            method,
+            headers: headers
+                .iter()
+                .map(|(k, v)| (HeaderName::from_str(k).unwrap(), HeaderValue::from_str(v).unwrap().into()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    #[test]
+    fn should_rank_stubs_by_distance() {
+        let mappings = vec![
+            mapping("far", json!({"method": "POST", "urlPath": "/other"})),
+            mapping("near", json!({"method": "GET", "urlPath": "/pong"})),
+        ];
+        let report = NearMissReport::new(&request(Method::Get, "/ping", &[], ""), &mappings);
+        let ids = report.near_misses.iter().map(|n| n.stub_id.as_str()).collect_vec();
+        assert_eq!(ids, vec!["near", "far"]);
+        assert_eq!(report.near_misses[0].distance, 0.5);
+        assert_eq!(report.near_misses[1].distance, 1.0);
+    }
+
+    #[test]
+    fn should_keep_only_closest_stubs() {
+        let mappings = (0..5)
+            .map(|i| mapping(&i.to_string(), json!({"urlPath": format!("/{i}")})))
+            .collect_vec();
+        let report = NearMissReport::new(&request(Method::Get, "/ping", &[], ""), &mappings);
+        assert_eq!(report.near_misses.len(), NearMissReport::MAX_NEAR_MISSES);
+    }
+
+    #[test]
+    fn should_diff_every_matcher() {
+        let mappings = vec![mapping(
+            "a",
+            json!({
+                "method": "GET",
+                "url": "/ping?a=b",
+                "headers": {"x-a": {"equalTo": "a"}, "x-b": {"equalTo": "b"}},
+                "queryParameters": {"a": {"equalTo": "c"}},
+                "bodyPatterns": [{"equalToJson": {"a": "b"}}],
+                "basicAuth": {"username": "user", "password": "pwd"}
+            }),
+        )];
+        let req = request(Method::Get, "/ping?a=b", &[("x-a", "a")], r#"{"a":"c"}"#);
+        let report = NearMissReport::new(&req, &mappings);
+        let diffs = &report.near_misses[0].diffs;
+        let find = |part: RequestPart, name: &str| diffs.iter().find(|d| d.part == part && d.name == name).unwrap();
+        assert!(find(RequestPart::Method, "method").matched);
+        assert!(find(RequestPart::Url, "url").matched);
+        assert!(find(RequestPart::Header, "x-a").matched);
+        let missing_header = find(RequestPart::Header, "x-b");
+        assert!(!missing_header.matched);
+        assert_eq!(missing_header.expected, json!({"equalTo": "b"}));
+        assert_eq!(missing_header.actual, None);
+        let query = find(RequestPart::Query, "a");
+        assert!(!query.matched);
+        assert_eq!(query.actual.as_deref(), Some("b"));
+        let body = find(RequestPart::Body, "bodyPatterns");
+        assert!(!body.matched);
+        assert_eq!(body.actual.as_deref(), Some(r#"{"a":"c"}"#));
+        assert!(!find(RequestPart::Auth, "basicAuth").matched);
+        assert_eq!(diffs.len(), 7);
+    }
+
+    #[test]
+    fn should_diff_client_certificate_protocol_and_state() {
+        let mappings = vec![mapping(
+            "a",
+            json!({
+                "method": "GET",
+                "clientCertificate": {"subject": {"equalTo": "CN=client"}},
+                "protocol": "HTTP/2",
+                "state": {"context": "user", "key": "1"}
+            }),
+        )];
+        let state = crate::server::admin::state::StateStore::default();
+        state.set("user", "2", json!("john"));
+        let _scope = state.enter();
+        let report = NearMissReport::new(&request(Method::Get, "/ping", &[], ""), &mappings);
+        let near_miss = &report.near_misses[0];
+        assert_eq!(near_miss.distance, 0.75);
+        let find = |part: RequestPart| near_miss.diffs.iter().find(|d| d.part == part).unwrap();
+        let certificate = find(RequestPart::ClientCertificate);
+        assert!(!certificate.matched);
+        assert_eq!(certificate.actual, None);
+        let protocol = find(RequestPart::Protocol);
+        assert!(!protocol.matched);
+        assert_eq!(protocol.actual.as_deref(), Some("HTTP/1.1"));
+        let state = find(RequestPart::State);
+        assert!(!state.matched);
+        assert_eq!(state.expected, json!({"context": "user", "key": "1"}));
+        assert_eq!(state.actual, None);
+    }
+
+    #[test]
+    fn should_ignore_stubs_without_request_matchers() {
+        let mappings = vec![Mapping::try_new(json!({"response": {"status": 200}}), None, None).unwrap()];
+        let report = NearMissReport::new(&request(Method::Get, "/ping", &[], ""), &mappings);
+        assert!(report.near_misses.is_empty());
+    }
+
+    #[test]
+    fn should_display_report() {
+        let mappings = vec![mapping("a", json!({"method": "GET", "urlPath": "/pong"}))];
+        let report = NearMissReport::new(&request(Method::Get, "/ping", &[], ""), &mappings);
+        let expected = "No stub matched request 'GET /ping'. Closest stubs:\n  stub 'a' at distance 0.50\n    == method 'method': expected \"GET\" got 'GET'\n    != url 'urlPath': expected \"/pong\" got '/ping'\n";
+        assert_eq!(report.to_string(), expected);
+    }
+}
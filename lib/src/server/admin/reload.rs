@@ -0,0 +1,98 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use itertools::Itertools;
+
+use super::{mapping::Mapping, MockSet, StubrAdmin};
+use crate::{
+    error::{StubrError, StubrResult},
+    server::stub_finder::StubFinder,
+};
+
+/// What changed in stub files since they were last loaded
+#[derive(Debug, Default)]
+pub struct StubsReload {
+    pub added: Vec<PathBuf>,
+    pub changed: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    /// Stub files which could not be loaded. The previous version of those stubs, if any, keeps being served
+    pub errors: Vec<(PathBuf, StubrError)>,
+}
+
+impl StubsReload {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty() && self.errors.is_empty()
+    }
+}
+
+impl StubrAdmin {
+    /// Remembers when every stub file was last modified so that only the modified ones get reloaded
+    pub(crate) fn track_sources(&mut self) {
+        self.modified = self
+            .find_sources()
+            .map(|file| {
+                let modified = Self::modified(&file);
+                (file, modified)
+            })
+            .collect();
+    }
+
+    pub(crate) fn reload(&mut self, mock_set: &mut MockSet) -> StubsReload {
+        let mut reload = StubsReload::default();
+        let found = self.find_sources().collect_vec();
+
+        let deleted = self.modified.keys().filter(|f| !found.contains(f)).cloned().collect_vec();
+        for file in deleted {
+            self.modified.remove(&file);
+            self.files.retain(|f| f != &file);
+            if let Some(id) = self.find_by_file(&file).map(|m| m.id.clone()) {
+                self.remove(mock_set, &id);
+                reload.removed.push(file);
+            }
+        }
+
+        for file in found {
+            let modified = Self::modified(&file);
+            if self.modified.get(&file) == Some(&modified) {
+                continue;
+            }
+            self.modified.insert(file.clone(), modified);
+            let outcome = match self.find_by_file(&file).map(|m| m.id.clone()) {
+                Some(id) => Mapping::try_from(&file)
+                    .and_then(|mapping| self.try_update(mock_set, &id, mapping.stub))
+                    .map(|_| &mut reload.changed),
+                None => Mapping::try_from(&file)
+                    .and_then(|mapping| self.try_mount(mock_set, mapping))
+                    .map(|_| &mut reload.added),
+            };
+            match outcome {
+                Ok(files) => files.push(file),
+                Err(e) => reload.errors.push((file, e)),
+            }
+        }
+        reload
+    }
+
+    /// Mounts a stub file written at runtime, tracking it so that reloading does not mount it twice
+    pub(crate) fn try_mount_file(&mut self, mock_set: &mut MockSet, file: PathBuf) -> StubrResult<()> {
+        let mapping = Mapping::try_from(&file)?;
+        self.try_mount(mock_set, mapping)?;
+        let modified = Self::modified(&file);
+        self.modified.insert(file, modified);
+        Ok(())
+    }
+
+    fn find_sources(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.sources.iter().flat_map(|source| StubFinder::find_all_stubs(source))
+    }
+
+    fn find_by_file(&self, file: &Path) -> Option<&Mapping> {
+        self.mappings.iter().find(|m| m.file.as_deref() == Some(file))
+    }
+
+    fn modified(file: &Path) -> Option<SystemTime> {
+        file.metadata().and_then(|m| m.modified()).ok()
+    }
+}
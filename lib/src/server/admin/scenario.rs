@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::wiremock_rs::Mock;
+
+/// Scenario fields of a stub, following Wiremock's
+/// [stateful behaviour](https://wiremock.org/docs/stateful-behaviour/)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    pub name: String,
+    /// The stub only matches when the scenario is in this state. Matches in any state when absent
+    pub required_state: Option<String>,
+    /// State the scenario moves to once the stub has matched
+    pub new_state: Option<String>,
+}
+
+/// Current state of every scenario mounted on a server
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Scenarios(BTreeMap<String, String>);
+
+impl Scenarios {
+    /// State every scenario starts in
+    pub const STARTED: &'static str = "Started";
+
+    /// Makes a scenario known, in its initial state if it was not already
+    pub(crate) fn register(&mut self, scenario: &Scenario) {
+        self.0.entry(scenario.name.clone()).or_insert_with(|| Self::STARTED.to_string());
+    }
+
+    pub fn state(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Returns `false` when no stub belongs to this scenario
+    pub fn set(&mut self, name: &str, state: &str) -> bool {
+        self.0.get_mut(name).map(|current| *current = state.to_string()).is_some()
+    }
+
+    /// Moves every scenario back to its initial state
+    pub fn reset(&mut self) {
+        self.0.values_mut().for_each(|state| *state = Self::STARTED.to_string());
+    }
+
+    /// Whether a stub of this scenario can match in the current state
+    pub(crate) fn allows(&self, scenario: &Scenario) -> bool {
+        scenario
+            .required_state
+            .as_deref()
+            .map(|required| self.state(&scenario.name).unwrap_or(Self::STARTED) == required)
+            .unwrap_or(true)
+    }
+
+    pub(crate) fn allows_mock(&self, mock: &Mock) -> bool {
+        mock.scenario.as_ref().map(|s| self.allows(s)).unwrap_or(true)
+    }
+
+    /// Once a stub of this scenario has matched
+    pub(crate) fn transition(&mut self, scenario: &Scenario) {
+        if let Some(new_state) = scenario.new_state.as_ref() {
+            self.0.insert(scenario.name.clone(), new_state.clone());
+        }
+    }
+
+    /// Formatted like Wiremock's admin api
+    pub fn to_json(&self) -> Value {
+        let scenarios = self
+            .0
+            .iter()
+            .map(|(name, state)| json!({ "id": name, "name": name, "state": state }))
+            .collect::<Vec<_>>();
+        json!({ "scenarios": scenarios })
+    }
+}
+
+#[cfg(test)]
+mod scenario_tests {
+    use super::*;
+
+    fn scenario(required: Option<&str>, new: Option<&str>) -> Scenario {
+        Scenario {
+            name: "flow".to_string(),
+            required_state: required.map(str::to_string),
+            new_state: new.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn should_start_in_started_state() {
+        let mut scenarios = Scenarios::default();
+        assert_eq!(scenarios.state("flow"), None);
+        scenarios.register(&scenario(None, None));
+        assert_eq!(scenarios.state("flow"), Some(Scenarios::STARTED));
+    }
+
+    #[test]
+    fn should_gate_on_required_state() {
+        let mut scenarios = Scenarios::default();
+        scenarios.register(&scenario(None, None));
+        assert!(scenarios.allows(&scenario(None, None)));
+        assert!(scenarios.allows(&scenario(Some(Scenarios::STARTED), None)));
+        assert!(!scenarios.allows(&scenario(Some("done"), None)));
+    }
+
+    #[test]
+    fn should_transition_then_reset() {
+        let mut scenarios = Scenarios::default();
+        let started = scenario(Some(Scenarios::STARTED), Some("done"));
+        scenarios.register(&started);
+        scenarios.transition(&started);
+        assert_eq!(scenarios.state("flow"), Some("done"));
+        assert!(!scenarios.allows(&started));
+        scenarios.reset();
+        assert_eq!(scenarios.state("flow"), Some(Scenarios::STARTED));
+    }
+
+    #[test]
+    fn should_only_set_known_scenarios() {
+        let mut scenarios = Scenarios::default();
+        assert!(!scenarios.set("flow", "done"));
+        scenarios.register(&scenario(None, None));
+        assert!(scenarios.set("flow", "done"));
+        assert_eq!(scenarios.state("flow"), Some("done"));
+    }
+}
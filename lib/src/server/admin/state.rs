@@ -0,0 +1,115 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::Value;
+
+thread_local! {
+    /// Store of the server currently handling a request on this thread
+    static CURRENT: RefCell<Option<StateStore>> = const { RefCell::new(None) };
+}
+
+/// Values response templates store and read back, grouped by context e.g. 'user', then by key.
+/// Every server has its own, shared by all its stubs
+#[derive(Debug, Default, Clone)]
+pub struct StateStore(Arc<Mutex<BTreeMap<String, BTreeMap<String, Value>>>>);
+
+/// Restores the previous store once the request has been handled
+pub(crate) struct StateScope(Option<StateStore>);
+
+impl Drop for StateScope {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+impl StateStore {
+    /// Makes this store the one templates and matchers use, on this thread, until the scope is dropped.
+    /// The scope must not be held across an `.await` since other tasks of the thread would then use this store
+    #[must_use]
+    pub(crate) fn enter(&self) -> StateScope {
+        StateScope(CURRENT.with(|current| current.borrow_mut().replace(self.clone())))
+    }
+
+    /// Runs on the store of the server handling the request, if any
+    pub(crate) fn with_current<T>(f: impl FnOnce(&StateStore) -> T) -> Option<T> {
+        CURRENT.with(|current| current.borrow().as_ref().map(f))
+    }
+
+    pub fn get(&self, context: &str, key: &str) -> Option<Value> {
+        self.0.lock().ok()?.get(context)?.get(key).cloned()
+    }
+
+    pub fn set(&self, context: &str, key: &str, value: Value) {
+        if let Ok(mut state) = self.0.lock() {
+            state.entry(context.to_string()).or_default().insert(key.to_string(), value);
+        }
+    }
+
+    pub fn delete(&self, context: &str, key: &str) -> Option<Value> {
+        self.0.lock().ok()?.get_mut(context)?.remove(key)
+    }
+
+    /// Whether the key, or any key when none, holds a value in this context
+    pub fn contains(&self, context: &str, key: Option<&str>) -> bool {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|state| {
+                state
+                    .get(context)
+                    .map(|values| key.map(|k| values.contains_key(k)).unwrap_or(!values.is_empty()))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.0.lock() {
+            state.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod state_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn should_set_get_then_delete() {
+        let store = StateStore::default();
+        assert!(store.get("user", "1").is_none());
+        store.set("user", "1", json!({ "name": "john" }));
+        assert_eq!(store.get("user", "1"), Some(json!({ "name": "john" })));
+        assert!(store.contains("user", Some("1")));
+        assert!(store.contains("user", None));
+        assert!(!store.contains("user", Some("2")));
+        assert_eq!(store.delete("user", "1"), Some(json!({ "name": "john" })));
+        assert!(!store.contains("user", None));
+    }
+
+    #[test]
+    fn should_clear_every_context() {
+        let store = StateStore::default();
+        store.set("user", "1", json!("a"));
+        store.set("order", "1", json!("b"));
+        store.clear();
+        assert!(!store.contains("user", None) && !store.contains("order", None));
+    }
+
+    #[test]
+    fn should_only_be_current_within_scope() {
+        let store = StateStore::default();
+        store.set("user", "1", json!("a"));
+        assert!(StateStore::with_current(|_| ()).is_none());
+        {
+            let _scope = store.enter();
+            assert_eq!(StateStore::with_current(|s| s.get("user", "1")).flatten(), Some(json!("a")));
+        }
+        assert!(StateStore::with_current(|_| ()).is_none());
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+use super::near_miss::{MatcherDiff, NearMiss, StubMatchers};
+use crate::{
+    model::request::RequestStub,
+    wiremock_rs::{Match, MockBuilder, Request},
+    StubrResult,
+};
+
+/// Number of requests a verification expects, like Wiremock's `exactly`, `moreThanOrExactly`...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestCount {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+    Never,
+}
+
+impl RequestCount {
+    fn contains(&self, count: usize) -> bool {
+        match *self {
+            Self::Exactly(n) => count == n,
+            Self::AtLeast(n) => count >= n,
+            Self::AtMost(n) => count <= n,
+            Self::Never => count == 0,
+        }
+    }
+}
+
+impl From<usize> for RequestCount {
+    fn from(count: usize) -> Self {
+        Self::Exactly(count)
+    }
+}
+
+impl Display for RequestCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exactly(n) => write!(f, "exactly {n}"),
+            Self::AtLeast(n) => write!(f, "at least {n}"),
+            Self::AtMost(n) => write!(f, "at most {n}"),
+            Self::Never => write!(f, "no"),
+        }
+    }
+}
+
+/// What received requests are verified against
+#[derive(Debug, Clone)]
+pub enum RequestPattern {
+    /// The 'request' of a mounted stub, by id
+    Stub(String),
+    Request(Box<RequestStub>),
+}
+
+impl From<&str> for RequestPattern {
+    fn from(stub_id: &str) -> Self {
+        Self::Stub(stub_id.to_string())
+    }
+}
+
+impl From<String> for RequestPattern {
+    fn from(stub_id: String) -> Self {
+        Self::Stub(stub_id)
+    }
+}
+
+impl From<RequestStub> for RequestPattern {
+    fn from(request: RequestStub) -> Self {
+        Self::Request(Box::new(request))
+    }
+}
+
+/// A received request which did not match, along with why
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearMatch {
+    /// Method, path and query of the request e.g. 'GET /users?page=1'
+    pub request: String,
+    /// Share of the matchers which did not match, from 0 (all matched) to 1 (none matched)
+    pub distance: f64,
+    /// Every matcher of the pattern, matched or not
+    pub diffs: Vec<MatcherDiff>,
+}
+
+impl Display for NearMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "'{}' at distance {:.2}", self.request, self.distance)?;
+        for diff in &self.diffs {
+            writeln!(f, "      {diff}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of verifying how many received requests matched a pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationReport {
+    pub expected: RequestCount,
+    /// What requests were verified against e.g. "stub 'ping'"
+    pub pattern: String,
+    /// Method, path and query of the matching requests, oldest first
+    pub matching: Vec<String>,
+    /// Closest requests which did not match, closest first
+    pub near_matching: Vec<NearMatch>,
+}
+
+impl VerificationReport {
+    /// Number of near matching requests reported
+    const MAX_NEAR_MATCHES: usize = 3;
+
+    /// * `explain` - matchers of `stub` on their own, explaining why requests did not match
+    pub(crate) fn try_new<'a>(
+        expected: RequestCount, pattern: String, stub: &RequestStub, explain: &StubMatchers,
+        requests: impl IntoIterator<Item = &'a Request>,
+    ) -> StubrResult<Self> {
+        let matchers = MockBuilder::try_from(stub)?.matchers;
+        let (matching, unmatched): (Vec<_>, Vec<_>) = requests
+            .into_iter()
+            .partition(|request| matchers.iter().all(|m| m.matches(request)));
+        let near_matching = unmatched
+            .into_iter()
+            .map(|request| {
+                let diffs = explain.diffs(request);
+                NearMatch {
+                    request: Self::describe(request),
+                    distance: NearMiss::distance(&diffs),
+                    diffs,
+                }
+            })
+            .filter(|near_match| near_match.distance < 1.0)
+            .sorted_by(|a, b| a.distance.total_cmp(&b.distance))
+            .take(Self::MAX_NEAR_MATCHES)
+            .collect_vec();
+        Ok(Self {
+            expected,
+            pattern,
+            matching: matching.into_iter().map(Self::describe).collect_vec(),
+            near_matching,
+        })
+    }
+
+    fn describe(request: &Request) -> String {
+        format!("{} {}", request.method, request.path_and_query())
+    }
+
+    /// Whether as many requests as expected matched
+    pub fn is_satisfied(&self) -> bool {
+        self.expected.contains(self.matching.len())
+    }
+}
+
+impl Display for VerificationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let received = self.matching.len();
+        if self.is_satisfied() {
+            writeln!(f, "Received {received} request(s) matching {}, as expected", self.pattern)?;
+        } else {
+            writeln!(
+                f,
+                "Expected {} request(s) matching {} but received {received}",
+                self.expected, self.pattern
+            )?;
+        }
+        if !self.matching.is_empty() {
+            writeln!(f, "  matching:")?;
+            for request in &self.matching {
+                writeln!(f, "    '{request}'")?;
+            }
+        }
+        if !self.near_matching.is_empty() {
+            writeln!(f, "  closest not matching:")?;
+            for near_match in &self.near_matching {
+                write!(f, "    {near_match}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod verification_tests {
+    use http_types::Method;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn request(method: Method, url: &str) -> Request {
+        Request {
+            url: format!("http://localhost{url}").parse().unwrap(),
+            method,
+            headers: Default::default(),
+            body: vec![],
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    fn report(expected: RequestCount, fields: Value, requests: &[Request]) -> VerificationReport {
+        let stub = serde_json::from_value::<RequestStub>(fields.clone()).unwrap();
+        let explain = StubMatchers::new(fields.as_object().unwrap());
+        VerificationReport::try_new(expected, "stub 'a'".to_string(), &stub, &explain, requests).unwrap()
+    }
+
+    #[test]
+    fn should_count_requests() {
+        assert!(RequestCount::Exactly(2).contains(2) && !RequestCount::Exactly(2).contains(3));
+        assert!(RequestCount::AtLeast(2).contains(3) && !RequestCount::AtLeast(2).contains(1));
+        assert!(RequestCount::AtMost(2).contains(0) && !RequestCount::AtMost(2).contains(3));
+        assert!(RequestCount::Never.contains(0) && !RequestCount::Never.contains(1));
+    }
+
+    #[test]
+    fn should_split_matching_and_near_matching_requests() {
+        let requests = [
+            request(Method::Get, "/ping"),
+            request(Method::Post, "/ping"),
+            request(Method::Post, "/other"),
+            request(Method::Get, "/ping?a=b"),
+        ];
+        let report = report(
+            RequestCount::AtLeast(1),
+            json!({"method": "GET", "urlPath": "/ping"}),
+            &requests,
+        );
+        assert!(report.is_satisfied());
+        assert_eq!(report.matching, vec!["GET /ping", "GET /ping?a=b"]);
+        assert_eq!(report.near_matching.len(), 1);
+        assert_eq!(report.near_matching[0].request, "POST /ping");
+        assert_eq!(report.near_matching[0].distance, 0.5);
+    }
+
+    #[test]
+    fn should_display_failed_verification() {
+        let requests = [request(Method::Get, "/ping"), request(Method::Get, "/pong")];
+        let report = report(
+            RequestCount::Exactly(2),
+            json!({"method": "GET", "urlPath": "/ping"}),
+            &requests,
+        );
+        assert!(!report.is_satisfied());
+        let expected = "Expected exactly 2 request(s) matching stub 'a' but received 1\n  matching:\n    'GET /ping'\n  closest not matching:\n    'GET /pong' at distance 0.50\n      == method 'method': expected \"GET\" got 'GET'\n      != url 'urlPath': expected \"/ping\" got '/pong'\n";
+        assert_eq!(report.to_string(), expected);
+    }
+
+    #[test]
+    fn empty_pattern_should_match_any_request() {
+        let requests = [request(Method::Get, "/ping"), request(Method::Delete, "/pong")];
+        assert!(report(RequestCount::Exactly(2), json!({}), &requests).is_satisfied());
+        assert!(!report(RequestCount::Never, json!({}), &requests).is_satisfied());
+    }
+}
@@ -2,6 +2,19 @@ use std::path::PathBuf;
 
 pub struct AnyStubs(pub Vec<PathBuf>);
 
+impl AnyStubs {
+    /// First stub directory, where mappings created at runtime get saved
+    pub fn root(&self) -> Option<PathBuf> {
+        self.0.first().and_then(|p| {
+            if p.is_dir() {
+                Some(p.to_path_buf())
+            } else {
+                p.parent().filter(|d| d.is_dir()).map(|d| d.to_path_buf())
+            }
+        })
+    }
+}
+
 impl std::ops::Deref for AnyStubs {
     type Target = Vec<PathBuf>;
 
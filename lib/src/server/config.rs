@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use crate::wiremock_rs::{delay::DelayDistribution, fault::Fault};
+
 /// Stubr server configuration.
 #[derive(Default, Copy, Clone)]
 pub struct Config {
@@ -14,4 +18,84 @@ pub struct Config {
     /// Contrary to [global_delay], this one is added to any locally defined delay.
     /// Use it to simulate network delays.
     pub latency: Option<u64>,
+    /// Random delay in milliseconds, added like [latency] to any locally defined delay.
+    /// Use it to reproduce a latency profile e.g. measured percentiles
+    pub latency_distribution: Option<DelayDistribution>,
+    /// Enables the request journal, keeping at most this many requests.
+    /// Received requests are then exposed by [crate::Stubr::received_requests] and the admin api
+    pub request_journal: Option<usize>,
+    /// Requests kept for [crate::Stubr::verify], the oldest ones being dropped first. 10 000 when absent.
+    /// Set it to `Some(0)` when never verifying e.g. for a long running server, requests are then only kept by the journal
+    pub verified_requests: Option<usize>,
+    /// Fails startup when any stub cannot be loaded, reporting every rejected file and why.
+    /// Otherwise unreadable stubs are logged as warnings and skipped, invalid matchers are logged and ignored
+    pub strict: bool,
+    /// Breaks responses of stubs which do not define their own fault
+    pub fault: Option<GlobalFault>,
+    /// Caps the transfer rate of response bodies, in bytes per second.
+    /// Does not apply to stubs defining a chunked dribble delay
+    pub bandwidth: Option<u64>,
+}
+
+/// How the server talks to the outside world: https and upstream forwarding.
+/// Unlike [Config] it is not [Copy] since it holds urls and files.
+/// Pass it along with a [Config] to [crate::Stubr::try_start_with_network]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Forwards requests no stub matches to an upstream server then records the exchange as a new stub.
+    /// Recording requires the 'record-standalone' feature, otherwise requests are only forwarded
+    pub record_on_miss: Option<RecordOnMiss>,
+    /// Serves stubs over https instead of http
+    #[cfg(feature = "tls")]
+    pub tls: Option<Tls>,
+    /// Asks https clients for a certificate, which stubs can then match (mutual TLS).
+    /// Only applies along with [NetworkConfig::tls]
+    #[cfg(feature = "tls")]
+    pub client_auth: Option<ClientAuth>,
+}
+
+/// A fault injected in the responses of every stub
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlobalFault {
+    pub fault: Fault,
+    /// Share of the responses broken, from 0 (none) to 1 (all)
+    pub probability: f64,
+}
+
+/// Bootstraps stubs incrementally: what no stub matches is forwarded upstream then recorded
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RecordOnMiss {
+    /// Base url of the upstream server e.g. 'http://localhost:8080'
+    pub upstream: String,
+    /// Directory where recorded stubs are written, in a subdirectory named after the upstream.
+    /// Defaults to the first stub directory the server was started with
+    pub output: Option<PathBuf>,
+}
+
+/// Certificate the server presents when serving stubs over https
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tls {
+    /// Generates a certificate valid for 'localhost' and '127.0.0.1' at startup.
+    /// It is signed by a CA, also generated, which clients have to trust. Get it with [crate::Stubr::ca_cert]
+    SelfSigned,
+    /// PEM encoded files
+    Files {
+        /// Certificate, optionally followed by the intermediate ones
+        cert: PathBuf,
+        /// Private key of the certificate
+        key: PathBuf,
+    },
+}
+
+/// How clients are authenticated with their certificate over mutual TLS
+#[cfg(feature = "tls")]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClientAuth {
+    /// PEM encoded CAs client certificates have to be signed by, failing the handshake otherwise.
+    /// When absent, any certificate is accepted and left for stubs to match
+    pub ca: Option<PathBuf>,
+    /// Fails the handshake of clients presenting no certificate, like a service enforcing mutual TLS.
+    /// Otherwise they are served and stubs can match requests without certificate
+    pub required: bool,
 }
@@ -0,0 +1,149 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use super::stub_finder::StubFinder;
+use crate::StubrResult;
+
+lazy_static! {
+    /// Servers of a test binary dropping concurrently must not lose each other's hits
+    static ref COVERAGE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Requests matched by every stub file, aggregated across the servers of a test run
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CoverageReport {
+    /// Identifies the test run, hits of another one are discarded
+    run: String,
+    /// Hits by stub file
+    stubs: BTreeMap<String, u64>,
+    /// Stub files no request matched
+    never_matched: Vec<String>,
+}
+
+impl CoverageReport {
+    const DIR: &'static str = "stubr";
+    const JSON_FILE: &'static str = "coverage.json";
+    const TEXT_FILE: &'static str = "coverage.txt";
+    /// Turns coverage on and identifies the test run e.g. `STUBR_COVERAGE_RUN=$(date +%s) cargo test`
+    const RUN_ENV_VAR: &'static str = "STUBR_COVERAGE_RUN";
+
+    /// The test run servers report their hits to, `None` when coverage is off
+    pub(crate) fn current_run() -> Option<String> {
+        env::var(Self::RUN_ENV_VAR).ok().filter(|run| !run.is_empty())
+    }
+
+    /// Adds the hits of a server to 'target/stubr/coverage.json' and 'target/stubr/coverage.txt'.
+    /// Does nothing outside of cargo, where there is no '/target' directory
+    pub(crate) fn aggregate(run: String, hits: BTreeMap<PathBuf, u64>) -> StubrResult<()> {
+        if let Some(dir) = StubFinder::try_output_dir().map(|target| target.join(Self::DIR)) {
+            let _lock = COVERAGE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            Self::aggregate_into(&dir, run, hits)?;
+        }
+        Ok(())
+    }
+
+    fn aggregate_into(dir: &Path, run: String, hits: BTreeMap<PathBuf, u64>) -> StubrResult<Self> {
+        let json = dir.join(Self::JSON_FILE);
+        let mut report = fs::read(&json)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Self>(&content).ok())
+            .filter(|report| report.run == run)
+            .unwrap_or_else(|| Self { run, ..Default::default() });
+        for (file, hits) in hits {
+            *report.stubs.entry(file.display().to_string()).or_default() += hits;
+        }
+        report.never_matched = report
+            .stubs
+            .iter()
+            .filter(|(_, hits)| **hits == 0)
+            .map(|(file, _)| file.to_string())
+            .collect();
+        fs::create_dir_all(dir)?;
+        Self::write(&json, serde_json::to_string_pretty(&report)?)?;
+        Self::write(&dir.join(Self::TEXT_FILE), report.to_string())?;
+        Ok(report)
+    }
+
+    /// Writes through a temporary file so that test binaries running concurrently never read a partial report
+    fn write(path: &Path, content: String) -> StubrResult<()> {
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, content)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} stub(s), {} never matched", self.stubs.len(), self.never_matched.len())?;
+        if !self.never_matched.is_empty() {
+            writeln!(f, "never matched:")?;
+            for file in &self.never_matched {
+                writeln!(f, "  {file}")?;
+            }
+        }
+        writeln!(f, "hits:")?;
+        for (file, hits) in &self.stubs {
+            writeln!(f, "  {hits:>6}  {file}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod coverage_tests {
+    use super::*;
+
+    fn hits(hits: &[(&str, u64)]) -> BTreeMap<PathBuf, u64> {
+        hits.iter().map(|(file, hits)| (PathBuf::from(file), *hits)).collect()
+    }
+
+    #[test]
+    fn should_aggregate_hits_of_servers_of_a_run() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(CoverageReport::DIR);
+        CoverageReport::aggregate_into(&dir, "1".to_string(), hits(&[("a.json", 2), ("b.json", 0)])).unwrap();
+        let report = CoverageReport::aggregate_into(&dir, "1".to_string(), hits(&[("a.json", 1), ("c.json", 0)])).unwrap();
+        let expected = [("a.json", 3), ("b.json", 0), ("c.json", 0)].map(|(file, hits)| (file.to_string(), hits));
+        assert_eq!(report.stubs, BTreeMap::from(expected));
+        assert_eq!(report.never_matched, vec!["b.json", "c.json"]);
+        let written: CoverageReport = serde_json::from_slice(&fs::read(dir.join(CoverageReport::JSON_FILE)).unwrap()).unwrap();
+        assert_eq!(written, report);
+        let text = fs::read_to_string(dir.join(CoverageReport::TEXT_FILE)).unwrap();
+        assert_eq!(
+            text,
+            "3 stub(s), 2 never matched\nnever matched:\n  b.json\n  c.json\nhits:\n       3  a.json\n       0  b.json\n       0  c.json\n"
+        );
+    }
+
+    #[test]
+    fn should_discard_hits_of_an_earlier_run() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(CoverageReport::DIR);
+        CoverageReport::aggregate_into(&dir, "1".to_string(), hits(&[("a.json", 2)])).unwrap();
+        let report = CoverageReport::aggregate_into(&dir, "2".to_string(), hits(&[("b.json", 1)])).unwrap();
+        assert_eq!(report.run, "2");
+        assert_eq!(report.stubs, BTreeMap::from([(String::from("b.json"), 1)]));
+        assert!(report.never_matched.is_empty());
+    }
+
+    #[async_std::test]
+    async fn should_report_hits_of_a_server_including_unmounted_stubs() {
+        let (hit, dead) = ("tests/stubs/coverage/hit.json", "tests/stubs/coverage/dead.json");
+        let stubr = crate::Stubr::start(hit).await;
+        surf::get(stubr.path("/coverage/hit")).await.unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(CoverageReport::DIR);
+        let report = CoverageReport::aggregate_into(&dir, "1".to_string(), stubr.coverage()).unwrap();
+        let expected = [(hit, 1), (dead, 0)].map(|(file, hits)| (file.to_string(), hits));
+        assert_eq!(report.stubs, BTreeMap::from(expected));
+        assert_eq!(report.never_matched, vec![dead]);
+    }
+}
@@ -1,30 +1,66 @@
 use std::{
+    collections::BTreeMap,
     net::TcpListener,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use async_std::task::block_on;
 use futures::future::join_all;
-use itertools::Itertools;
-use log::info;
-
+use itertools::{Either, Itertools};
+use log::{info, warn};
+
+use admin::{
+    journal::{LoggedRequest, RequestJournal},
+    mapping::Mapping,
+    near_miss::{NearMissReport, StubMatchers},
+    reload::StubsReload,
+    verification::{RequestCount, RequestPattern, VerificationReport},
+    StubrAdmin,
+};
 use any_stub::AnyStubs;
+use coverage::CoverageReport;
 use stub_finder::StubFinder;
+#[cfg(feature = "tls")]
+use tls::ServerTls;
 
-use crate::error::StubrResult;
+use crate::error::{StubrError, StubrResult};
 #[cfg(feature = "record-standalone")]
 use crate::record::{config::RecordConfig, standalone::StubrRecord};
-use crate::wiremock_rs::MockServer;
-use crate::{model::JsonStub, Config};
+use crate::wiremock_rs::{
+    mock_server::{bare_server::MockServerState, TlsAcceptor},
+    MockServer, MockServerBuilder,
+};
+use crate::{Config, NetworkConfig, RecordOnMiss};
 
+pub mod admin;
 pub mod any_stub;
 pub mod config;
+pub(crate) mod coverage;
+pub(crate) mod record_on_miss;
 pub mod stub_finder;
+#[cfg(feature = "tls")]
+pub(crate) mod tls;
+pub mod validate;
 
 /// Allows running a Wiremock mock server from Wiremock stubs.
 /// Delegates runtime to wiremock-rs.
 pub struct Stubr {
     http_server: MockServer,
+    /// PEM encoded CA which signed the generated certificate
+    #[cfg(feature = "tls")]
+    ca_cert: Option<String>,
+}
+
+impl Drop for Stubr {
+    /// Adds the hits of this server to the coverage report of the test run, when there is one
+    fn drop(&mut self) {
+        if let Some(run) = CoverageReport::current_run() {
+            if let Err(e) = CoverageReport::aggregate(run, self.coverage()) {
+                warn!("Failed writing stub coverage because {e}");
+            }
+        }
+    }
 }
 
 /// Fallible API
@@ -49,12 +85,37 @@ impl Stubr {
     where
         T: Into<AnyStubs>,
     {
+        Self::try_start_with_network(stubs, config, NetworkConfig::default()).await
+    }
+
+    /// Runs a mock server with some configuration, served over https or forwarding requests upstream.
+    /// The server is unbinded when the instance is dropped.
+    /// Use this in a test context.
+    /// * `stubs` - folder or file containing the stubs
+    /// * `config` - global server configuration
+    /// * `network` - https and upstream forwarding configuration
+    pub async fn try_start_with_network<T>(stubs: T, config: Config, network: NetworkConfig) -> StubrResult<Self>
+    where
+        T: Into<AnyStubs>,
+    {
+        #[cfg(feature = "tls")]
+        let tls = network
+            .tls
+            .as_ref()
+            .map(|tls| ServerTls::try_new(tls, network.client_auth.as_ref()))
+            .transpose()?;
+        #[cfg(feature = "tls")]
+        let acceptor = tls.as_ref().map(|tls| tls.acceptor.clone());
+        #[cfg(not(feature = "tls"))]
+        let acceptor = None;
         let server = if let Some(p) = config.port {
-            Self::try_start_on(p).await
+            Self::try_start_on(p, acceptor).await
         } else {
-            Self::try_start_on_random_port().await
+            Self::try_start_on_random_port(acceptor).await
         }?;
-        server.try_register_stubs(stubs.into(), config)?;
+        #[cfg(feature = "tls")]
+        let server = server.with_ca_cert(tls.and_then(|tls| tls.ca));
+        server.try_register_stubs(stubs.into(), config, network.record_on_miss)?;
         #[cfg(not(feature = "grpc"))]
         server.register_cloud_features().await;
         Ok(server)
@@ -83,6 +144,19 @@ impl Stubr {
         block_on(Self::try_start_with(stubs, config))
     }
 
+    /// Runs a mock server in a blocking way with some configuration, served over https or forwarding requests upstream.
+    /// The server is unbinded when the instance is dropped.
+    /// Use this in a test context.
+    /// * `stubs` - folder or file containing the stubs
+    /// * `config` - global server configuration
+    /// * `network` - https and upstream forwarding configuration
+    pub fn try_start_blocking_with_network<T>(stubs: T, config: Config, network: NetworkConfig) -> StubrResult<Self>
+    where
+        T: Into<AnyStubs>,
+    {
+        block_on(Self::try_start_with_network(stubs, config, network))
+    }
+
     /// Proxies requests and converts them into stubs
     #[cfg(feature = "record-standalone")]
     pub fn try_record() -> StubrResult<StubrRecord> {
@@ -145,54 +219,218 @@ impl Stubr {
         self.http_server.uri()
     }
 
+    /// PEM encoded CA which signed the certificate generated when serving over https with [crate::Tls::SelfSigned].
+    /// Have your http client trust it, it changes every time the server starts
+    #[cfg(feature = "tls")]
+    pub fn ca_cert(&self) -> Option<&str> {
+        self.ca_cert.as_deref()
+    }
+
     /// Get running server address and concatenate a path to it
     pub fn path(&self, path: &str) -> String {
         format!("{}{}", self.uri(), path)
     }
 
-    async fn try_start_on(port: u16) -> StubrResult<Self> {
+    /// Requests received by the server, oldest first.
+    /// Always empty unless the request journal is enabled with [Config::request_journal]
+    pub fn received_requests(&self) -> Vec<LoggedRequest> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        state.journal.requests().collect()
+    }
+
+    /// Mounts stub files created since startup or the last reload, replaces the modified ones
+    /// and drops the deleted ones. A stub file which cannot be loaded is reported and its
+    /// previous version, if any, keeps being served.
+    pub fn reload_stubs(&self) -> StubsReload {
+        let server_state = self.http_server.state();
+        let mut state = block_on(server_state.write());
+        let MockServerState { mock_set, admin, .. } = &mut *state;
+        admin.reload(mock_set)
+    }
+
+    /// Current state of a scenario, `None` when no stub belongs to it
+    pub fn scenario_state(&self, name: &str) -> Option<String> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        state.mock_set.scenarios.state(name).map(str::to_string)
+    }
+
+    /// Moves a scenario to the given state, as if a stub had just moved it there
+    pub fn try_set_scenario_state(&self, name: &str, scenario_state: &str) -> StubrResult<()> {
+        let server_state = self.http_server.state();
+        let mut state = block_on(server_state.write());
+        if state.mock_set.scenarios.set(name, scenario_state) {
+            Ok(())
+        } else {
+            Err(StubrError::UnknownScenario(name.to_string()))
+        }
+    }
+
+    /// Moves every scenario back to its initial 'Started' state
+    pub fn reset_scenarios(&self) {
+        let server_state = self.http_server.state();
+        block_on(server_state.write()).mock_set.scenarios.reset();
+    }
+
+    /// Stubs with `responses` serve their first response again to the next matching request
+    pub fn reset_sequences(&self) {
+        let server_state = self.http_server.state();
+        block_on(server_state.write()).mock_set.reset_sequences();
+    }
+
+    /// Value stored in the server's state, by a response template or [Stubr::set_state_value]
+    pub fn state_value(&self, context: &str, key: &str) -> Option<serde_json::Value> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        state.mock_set.state.get(context, key)
+    }
+
+    /// Seeds the server's state as if a response template had stored this value e.g. with `{{state-set 'user' '1' 'john'}}`
+    pub fn set_state_value(&self, context: &str, key: &str, value: impl Into<serde_json::Value>) {
+        let server_state = self.http_server.state();
+        block_on(server_state.read()).mock_set.state.set(context, key, value.into());
+    }
+
+    /// Drops every value stored in the server's state
+    pub fn clear_state(&self) {
+        let server_state = self.http_server.state();
+        block_on(server_state.read()).mock_set.state.clear();
+    }
+
+    /// Requests each stub file matched since startup, including the mounted ones no request matched
+    pub fn stub_hits(&self) -> BTreeMap<PathBuf, u64> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        state.admin.hits()
+    }
+
+    /// Like [Stubr::stub_hits] but also with the stub files next to the mounted ones, which are never matched
+    pub(crate) fn coverage(&self) -> BTreeMap<PathBuf, u64> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        state.admin.coverage()
+    }
+
+    /// Explains why the last requests did not match any stub, oldest first.
+    /// Print them when a test gets an unexpected 404
+    pub fn near_misses(&self) -> Vec<NearMissReport> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        state.admin.near_misses().cloned().collect()
+    }
+
+    /// Verifies how many of the requests received since startup or the last reset match a pattern,
+    /// either a stub's id or a [RequestStub](crate::RequestStub).
+    /// Fails with a report listing the matching and the closest requests when the count is not the expected one
+    pub fn verify(&self, count: RequestCount, pattern: impl Into<RequestPattern>) -> StubrResult<VerificationReport> {
+        let server_state = self.http_server.state();
+        let state = block_on(server_state.read());
+        let (description, stub, explain) = match pattern.into() {
+            RequestPattern::Stub(id) => {
+                let mapping = state.admin.find(&id).ok_or_else(|| StubrError::UnknownStub(id.clone()))?;
+                let fields = mapping.stub.get("request").cloned().unwrap_or_else(|| serde_json::json!({}));
+                (
+                    format!("stub '{id}'"),
+                    serde_json::from_value(fields)?,
+                    mapping.matchers.clone(),
+                )
+            },
+            RequestPattern::Request(stub) => {
+                let fields = serde_json::to_value(&stub)?;
+                let explain = fields.as_object().map(StubMatchers::new).unwrap_or_default();
+                (fields.to_string(), *stub, Arc::new(explain))
+            },
+        };
+        let _state = state.mock_set.state.enter();
+        let report = VerificationReport::try_new(count, description, &stub, &explain, state.journal.received())?;
+        if report.is_satisfied() {
+            Ok(report)
+        } else {
+            Err(StubrError::VerificationFailed(Box::new(report)))
+        }
+    }
+
+    async fn try_start_on(port: u16, tls: Option<TlsAcceptor>) -> StubrResult<Self> {
         if let Ok(listener) = TcpListener::bind(format!("{}:{}", Self::HOST, port)) {
-            let http_server = MockServer::builder()
-                .disable_request_recording()
-                .listener(listener)
-                .start()
-                .await?;
-            Ok(Self { http_server })
+            let http_server = Self::server_builder(tls).listener(listener).start().await?;
+            Ok(Self::new(http_server))
         } else {
-            Self::try_start_on_random_port().await
+            Self::try_start_on_random_port(tls).await
+        }
+    }
+
+    async fn try_start_on_random_port(tls: Option<TlsAcceptor>) -> StubrResult<Self> {
+        let http_server = Self::server_builder(tls).start().await?;
+        Ok(Self::new(http_server))
+    }
+
+    fn server_builder(tls: Option<TlsAcceptor>) -> MockServerBuilder {
+        let builder = MockServer::builder().disable_request_recording();
+        match tls {
+            #[cfg(feature = "tls")]
+            Some(tls) => builder.tls(tls),
+            None => builder,
+        }
+    }
+
+    fn new(http_server: MockServer) -> Self {
+        Self {
+            http_server,
+            #[cfg(feature = "tls")]
+            ca_cert: None,
         }
     }
 
-    async fn try_start_on_random_port() -> StubrResult<Self> {
-        let http_server = MockServer::builder().disable_request_recording().start().await?;
-        Ok(Self { http_server })
+    #[cfg(feature = "tls")]
+    fn with_ca_cert(mut self, ca_cert: Option<String>) -> Self {
+        self.ca_cert = ca_cert;
+        self
     }
 
-    fn try_register_stubs(&self, stub_folder: AnyStubs, config: Config) -> StubrResult<()> {
-        stub_folder
+    fn try_register_stubs(&self, stub_folder: AnyStubs, config: Config, record_on_miss: Option<RecordOnMiss>) -> StubrResult<()> {
+        let server_state = self.http_server.state();
+        let mut state = block_on(server_state.write());
+        let MockServerState {
+            mock_set, admin, journal, ..
+        } = &mut *state;
+        *journal = RequestJournal::new(config.request_journal, config.verified_requests);
+        *admin = StubrAdmin::new(config, record_on_miss, &stub_folder);
+        let (mappings, mut rejected): (Vec<_>, Vec<_>) = stub_folder
             .0
             .iter()
             .filter_map(|folder| self.try_find_all_mocks(folder).ok().map(|mocks| (folder, mocks)))
-            .flat_map(|(folder, mocks)| mocks.map(move |(s, p)| (s, p, folder)))
-            .sorted_by(|(a, ..), (b, ..)| a.priority.cmp(&b.priority))
-            .filter_map(|(stub, file, folder)| stub.try_creating_from(&config, &file).ok().map(|mock| (mock, file, folder)))
-            .for_each(|(mock, file, folder)| {
-                block_on(async move {
-                    self.http_server.register(mock).await;
-                });
-                if config.verbose {
+            .flat_map(|(folder, mocks)| mocks.map(move |(m, p)| (m, p, folder)))
+            .partition_map(|(mapping, file, folder)| match mapping {
+                Ok(mapping) => Either::Left((mapping, file, folder)),
+                Err(e) => Either::Right((file, e)),
+            });
+        mappings
+            .into_iter()
+            .sorted_by(|(a, ..), (b, ..)| a.priority().cmp(&b.priority()))
+            .for_each(|(mapping, file, folder)| match admin.try_mount(mock_set, mapping) {
+                Ok(_) if config.verbose => {
                     let maybe_file_name = file.strip_prefix(folder).ok().and_then(|file| file.to_str());
                     if let Some(file_name) = maybe_file_name {
                         info!("mounted stub '{}'", file_name);
                     }
-                };
+                },
+                Ok(_) => {},
+                Err(e) => rejected.push((file, e)),
             });
+        admin.track_sources();
+        if config.strict && !rejected.is_empty() {
+            return Err(StubrError::RejectedStubs(rejected));
+        }
+        for (file, e) in rejected {
+            warn!("could not load stub {:?} because {}", file, e);
+        }
         Ok(())
     }
 
     #[allow(clippy::needless_lifetimes)]
-    fn try_find_all_mocks<'a>(&self, from: &Path) -> StubrResult<impl Iterator<Item = (JsonStub, PathBuf)> + 'a> {
-        Ok(StubFinder::find_all_stubs(from).filter_map(move |path| JsonStub::try_from(&path).ok().map(|stub| (stub, path))))
+    fn try_find_all_mocks<'a>(&self, from: &Path) -> StubrResult<impl Iterator<Item = (StubrResult<Mapping>, PathBuf)> + 'a> {
+        Ok(StubFinder::find_all_stubs(from).map(move |path| (Mapping::try_from(&path), path)))
     }
 
     #[cfg(not(feature = "grpc"))]
@@ -241,6 +479,24 @@ impl Stubr {
         block_on(Self::start_with(stubs, config))
     }
 
+    /// see [Stubr::try_start_with_network]
+    pub async fn start_with_network<T>(stubs: T, config: Config, network: NetworkConfig) -> Self
+    where
+        T: Into<AnyStubs>,
+    {
+        Self::try_start_with_network(stubs, config, network)
+            .await
+            .expect("Could not start server")
+    }
+
+    /// see [Stubr::try_start_blocking_with_network]
+    pub fn start_blocking_with_network<T>(stubs: T, config: Config, network: NetworkConfig) -> Self
+    where
+        T: Into<AnyStubs>,
+    {
+        block_on(Self::start_with_network(stubs, config, network))
+    }
+
     /// see [Stubr::try_record]
     #[cfg(feature = "record-standalone")]
     pub fn record() -> StubrRecord {
@@ -253,6 +509,11 @@ impl Stubr {
         Self::try_record_with(config).expect("Failed recording")
     }
 
+    /// see [Stubr::try_set_scenario_state]
+    pub fn set_scenario_state(&self, name: &str, state: &str) {
+        self.try_set_scenario_state(name, state).expect("Could not set scenario state")
+    }
+
     /// see [Stubr::try_app]
     pub async fn app(name: &str) -> Self {
         Self::app_with(name, Config::default()).await
@@ -301,25 +562,25 @@ mod server_test {
     #[async_std::test]
     async fn should_find_all_mocks_from_dir() {
         let from = PathBuf::from("tests/stubs/server");
-        let stubr = Stubr::try_start_on_random_port().await.unwrap();
+        let stubr = Stubr::try_start_on_random_port(None).await.unwrap();
         assert!(stubr.try_find_all_mocks(&from).unwrap().count().gt(&2));
     }
 
     #[async_std::test]
     async fn should_find_all_mocks_from_single_file() {
         let from = PathBuf::from("tests/stubs/server/valid.json");
-        let stubr = Stubr::try_start_on_random_port().await.unwrap();
+        let stubr = Stubr::try_start_on_random_port(None).await.unwrap();
         assert_eq!(stubr.try_find_all_mocks(&from).unwrap().count(), 1);
     }
 
     #[async_std::test]
     async fn should_not_find_any_mock_when_path_does_not_exist() {
         let from = PathBuf::from("tests/stubs/server/unknown");
-        let stubr = Stubr::try_start_on_random_port().await.unwrap();
+        let stubr = Stubr::try_start_on_random_port(None).await.unwrap();
         assert_eq!(stubr.try_find_all_mocks(&from).unwrap().count(), 0);
 
         let from = PathBuf::from("tests/stubs/server/unknown.json");
-        let stubr = Stubr::try_start_on_random_port().await.unwrap();
+        let stubr = Stubr::try_start_on_random_port(None).await.unwrap();
         assert_eq!(stubr.try_find_all_mocks(&from).unwrap().count(), 0);
     }
 }
@@ -0,0 +1,67 @@
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    wiremock_rs::{
+        mock_server::{bare_server::MockServerState, hyper::DynError},
+        proxy::Proxy,
+        Request,
+    },
+    RecordOnMiss,
+};
+
+/// Marks the response of a request no stub matched so that it gets forwarded upstream then recorded
+#[derive(Debug, Clone)]
+pub(crate) struct MissRecorder {
+    upstream: String,
+    /// Where recorded stubs are written
+    output: Option<PathBuf>,
+}
+
+impl MissRecorder {
+    pub(crate) fn new(record_on_miss: &RecordOnMiss, root: Option<&PathBuf>) -> Self {
+        Self {
+            upstream: record_on_miss.upstream.clone(),
+            output: record_on_miss.output.clone().or_else(|| root.cloned()),
+        }
+    }
+
+    pub(crate) fn proxy(&self) -> Proxy {
+        Proxy {
+            base_url: self.upstream.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Writes the upstream response as a new stub then mounts it, so that it serves the next identical requests
+    #[cfg(feature = "record-standalone")]
+    pub(crate) async fn record(
+        &self, request: &Request, response: hyper::Response<hyper::Body>, server_state: Arc<tokio::sync::RwLock<MockServerState>>,
+    ) -> Result<hyper::Response<hyper::Body>, DynError> {
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+        let recorded = crate::record::standalone::miss::record_miss(
+            &self.upstream,
+            self.output.as_ref(),
+            request,
+            parts.status.as_u16(),
+            &parts.headers,
+            &body,
+        );
+        if let Some(file) = recorded {
+            let mut state = server_state.write().await;
+            let MockServerState { mock_set, admin, .. } = &mut *state;
+            if let Err(e) = admin.try_mount_file(mock_set, file.clone()) {
+                log::warn!("could not mount recorded stub {:?} because {}", file, e);
+            }
+        }
+        Ok(hyper::Response::from_parts(parts, hyper::Body::from(body)))
+    }
+
+    #[cfg(not(feature = "record-standalone"))]
+    pub(crate) async fn record(
+        &self, _request: &Request, response: hyper::Response<hyper::Body>, _server_state: Arc<tokio::sync::RwLock<MockServerState>>,
+    ) -> Result<hyper::Response<hyper::Body>, DynError> {
+        log::warn!("not recording unmatched request since 'record-standalone' feature is not turned on");
+        Ok(response)
+    }
+}
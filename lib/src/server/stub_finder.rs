@@ -56,11 +56,15 @@ impl StubFinder {
     }
 
     pub fn output_dir() -> PathBuf {
+        Self::try_output_dir().expect("Failed locating '/target' directory")
+    }
+
+    /// The '/target' directory, only found when running through cargo
+    pub fn try_output_dir() -> Option<PathBuf> {
         env::var(Self::LIB_PATH_ENV_VAR)
             .ok()
             .and_then(|v| v.split(':').map(PathBuf::from).find(|p| Self::is_target_debug(p)))
             .and_then(|p| p.parent().map(|it| it.to_path_buf()))
-            .expect("Failed locating '/target' directory")
     }
 
     fn is_target_debug(path: &Path) -> bool {
@@ -0,0 +1,178 @@
+use std::fs;
+
+use openssl::{
+    asn1::Asn1Time,
+    bn::{BigNum, MsbOption},
+    ec::{EcGroup, EcKey},
+    error::ErrorStack,
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{PKey, Private},
+    ssl::{select_next_proto, AlpnError, SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod, SslVerifyMode},
+    x509::{
+        extension::{AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName, SubjectKeyIdentifier},
+        X509Builder, X509Name, X509NameRef, X509,
+    },
+};
+
+use crate::{error::StubrResult, ClientAuth, Tls};
+
+/// What the server needs to serve stubs over https
+pub(crate) struct ServerTls {
+    pub(crate) acceptor: SslAcceptor,
+    /// PEM encoded CA which signed a generated certificate
+    pub(crate) ca: Option<String>,
+}
+
+impl ServerTls {
+    const CA_NAME: &'static str = "stubr CA";
+    const HOST: &'static str = "localhost";
+    const IPS: [&'static str; 2] = ["127.0.0.1", "::1"];
+    const VALIDITY_DAYS: u32 = 365;
+    /// Protocols offered over ALPN, by order of preference
+    const ALPN_PROTOCOLS: &'static [u8] = b"\x02h2\x08http/1.1";
+
+    pub(crate) fn try_new(tls: &Tls, client_auth: Option<&ClientAuth>) -> StubrResult<Self> {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())?;
+        // clients not offering ALPN get HTTP/1.1
+        acceptor.set_alpn_select_callback(|_, client| select_next_proto(Self::ALPN_PROTOCOLS, client).ok_or(AlpnError::NOACK));
+        if let Some(client_auth) = client_auth {
+            Self::authenticate_clients(&mut acceptor, client_auth)?;
+        }
+        let ca = match tls {
+            Tls::SelfSigned => {
+                let (ca, ca_key) = Self::generate_ca()?;
+                let (cert, key) = Self::generate_cert(&ca, &ca_key)?;
+                acceptor.set_certificate(&cert)?;
+                acceptor.set_private_key(&key)?;
+                let ca_pem = String::from_utf8_lossy(&ca.to_pem()?).to_string();
+                // clients trusting the CA need it in the chain to verify the certificate
+                acceptor.add_extra_chain_cert(ca)?;
+                Some(ca_pem)
+            },
+            Tls::Files { cert, key } => {
+                // openssl errors do not tell which file is missing
+                fs::metadata(cert)?;
+                fs::metadata(key)?;
+                acceptor.set_certificate_chain_file(cert)?;
+                acceptor.set_private_key_file(key, SslFiletype::PEM)?;
+                None
+            },
+        };
+        acceptor.check_private_key()?;
+        Ok(Self {
+            acceptor: acceptor.build(),
+            ca,
+        })
+    }
+
+    fn authenticate_clients(acceptor: &mut SslAcceptorBuilder, client_auth: &ClientAuth) -> StubrResult<()> {
+        let mut mode = SslVerifyMode::PEER;
+        if client_auth.required {
+            mode |= SslVerifyMode::FAIL_IF_NO_PEER_CERT;
+        }
+        if let Some(ca) = client_auth.ca.as_ref() {
+            fs::metadata(ca)?;
+            acceptor.set_ca_file(ca)?;
+            acceptor.set_verify(mode);
+        } else {
+            // any certificate is accepted, stubs decide
+            acceptor.set_verify_callback(mode, |_, _| true);
+        }
+        // otherwise resuming a TLS session fails once clients are verified
+        acceptor.set_session_id_context(Self::CA_NAME.as_bytes())?;
+        Ok(())
+    }
+
+    fn generate_ca() -> Result<(X509, PKey<Private>), ErrorStack> {
+        let key = Self::generate_key()?;
+        let name = Self::name(Self::CA_NAME)?;
+        let mut builder = Self::builder(&name, &name, &key)?;
+        builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+        builder.append_extension(KeyUsage::new().critical().key_cert_sign().crl_sign().build()?)?;
+        let subject_key_id = SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(subject_key_id)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+        Ok((builder.build(), key))
+    }
+
+    fn generate_cert(ca: &X509, ca_key: &PKey<Private>) -> Result<(X509, PKey<Private>), ErrorStack> {
+        let key = Self::generate_key()?;
+        let name = Self::name(Self::HOST)?;
+        let mut builder = Self::builder(&name, ca.subject_name(), &key)?;
+        builder.append_extension(BasicConstraints::new().build()?)?;
+        builder.append_extension(KeyUsage::new().critical().digital_signature().key_encipherment().build()?)?;
+        builder.append_extension(ExtendedKeyUsage::new().server_auth().build()?)?;
+        let mut alt_names = SubjectAlternativeName::new();
+        alt_names.dns(Self::HOST);
+        Self::IPS.iter().for_each(|ip| {
+            alt_names.ip(ip);
+        });
+        let alt_names = alt_names.build(&builder.x509v3_context(Some(ca), None))?;
+        builder.append_extension(alt_names)?;
+        let subject_key_id = SubjectKeyIdentifier::new().build(&builder.x509v3_context(Some(ca), None))?;
+        builder.append_extension(subject_key_id)?;
+        let authority_key_id = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&builder.x509v3_context(Some(ca), None))?;
+        builder.append_extension(authority_key_id)?;
+        builder.sign(ca_key, MessageDigest::sha256())?;
+        Ok((builder.build(), key))
+    }
+
+    fn generate_key() -> Result<PKey<Private>, ErrorStack> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        PKey::from_ec_key(EcKey::generate(&group)?)
+    }
+
+    fn name(common_name: &str) -> Result<X509Name, ErrorStack> {
+        let mut name = X509Name::builder()?;
+        name.append_entry_by_nid(Nid::COMMONNAME, common_name)?;
+        Ok(name.build())
+    }
+
+    fn builder(subject: &X509NameRef, issuer: &X509NameRef, key: &PKey<Private>) -> Result<X509Builder, ErrorStack> {
+        let mut serial = BigNum::new()?;
+        serial.rand(128, MsbOption::MAYBE_ZERO, false)?;
+        let serial = serial.to_asn1_integer()?;
+        let not_before = Asn1Time::days_from_now(0)?;
+        let not_after = Asn1Time::days_from_now(Self::VALIDITY_DAYS)?;
+        let mut builder = X509::builder()?;
+        builder.set_version(2)?;
+        builder.set_serial_number(&serial)?;
+        builder.set_subject_name(subject)?;
+        builder.set_issuer_name(issuer)?;
+        builder.set_pubkey(key)?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tls_tests {
+    use super::*;
+
+    #[test]
+    fn generated_cert_should_be_signed_by_generated_ca() {
+        let (ca, ca_key) = ServerTls::generate_ca().unwrap();
+        let (cert, _) = ServerTls::generate_cert(&ca, &ca_key).unwrap();
+        assert!(cert.verify(&ca_key).unwrap());
+        assert_eq!(cert.issuer_name().to_der().unwrap(), ca.subject_name().to_der().unwrap());
+    }
+
+    #[test]
+    fn generated_cert_should_be_valid_for_localhost() {
+        let (ca, ca_key) = ServerTls::generate_ca().unwrap();
+        let (cert, _) = ServerTls::generate_cert(&ca, &ca_key).unwrap();
+        let alt_names = cert.subject_alt_names().unwrap();
+        assert!(alt_names.iter().any(|n| n.dnsname() == Some("localhost")));
+        assert!(alt_names.iter().any(|n| n.ipaddress() == Some(&[127, 0, 0, 1])));
+    }
+
+    #[test]
+    fn self_signed_should_export_ca() {
+        let tls = ServerTls::try_new(&Tls::SelfSigned, None).unwrap();
+        assert!(tls.ca.unwrap().starts_with("-----BEGIN CERTIFICATE-----"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+use super::{admin::mapping::Mapping, any_stub::AnyStubs, stub_finder::StubFinder, Stubr};
+use crate::{model::JsonStub, Config, StubrError};
+
+/// Every problem found in a stub file
+#[derive(Debug)]
+pub struct StubValidation {
+    pub file: PathBuf,
+    pub errors: Vec<StubrError>,
+}
+
+impl StubValidation {
+    fn new(file: PathBuf) -> Self {
+        let errors = match Mapping::try_from(&file) {
+            Ok(mapping) => Self::validate(&mapping, &file),
+            Err(e) => vec![e],
+        };
+        Self { file, errors }
+    }
+
+    fn validate(mapping: &Mapping, file: &Path) -> Vec<StubrError> {
+        match JsonStub::try_from(mapping) {
+            Ok(stub) => {
+                let mut errors = stub.validate();
+                if let Err(e) = stub.try_creating_from(&Config::default(), file) {
+                    errors.insert(0, e);
+                }
+                errors
+            },
+            Err(e) => vec![e],
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Stubr {
+    /// Loads stubs the way a server would, without starting one, and reports every problem
+    /// found in each stub file: invalid json, regex or json path, missing body or protobuf
+    /// file, response templates which do not compile...
+    /// * `stubs` - folder or file containing the stubs
+    pub fn validate<T>(stubs: T) -> Vec<StubValidation>
+    where
+        T: Into<AnyStubs>,
+    {
+        stubs
+            .into()
+            .0
+            .iter()
+            .flat_map(|folder| StubFinder::find_all_stubs(folder))
+            .sorted()
+            .map(StubValidation::new)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::*;
+
+    #[test]
+    fn should_accept_valid_stub() {
+        let validations = Stubr::validate("tests/stubs/ping.json");
+        assert_eq!(validations.len(), 1);
+        assert!(validations[0].is_valid());
+    }
+
+    #[test]
+    fn should_report_missing_body_file() {
+        let validations = Stubr::validate("tests/stubs/validate/missing-body-file.json");
+        assert!(matches!(validations[0].errors.as_slice(), [StubrError::BodyFileNotFound(_)]));
+    }
+
+    #[test]
+    fn should_report_missing_dataset() {
+        let validations = Stubr::validate("tests/stubs/validate/missing-dataset.json");
+        assert!(matches!(validations[0].errors.as_slice(), [StubrError::DatasetNotFound(_)]));
+    }
+
+    #[test]
+    fn should_report_template_not_compiling() {
+        let validations = Stubr::validate("tests/stubs/validate/template.json");
+        assert!(matches!(validations[0].errors.as_slice(), [StubrError::TemplateError(_)]));
+    }
+
+    #[test]
+    fn should_report_invalid_regex() {
+        let validations = Stubr::validate("tests/stubs/strict/invalid/url-regex.json");
+        assert!(matches!(validations[0].errors.as_slice(), [StubrError::RegexError(_)]));
+    }
+}
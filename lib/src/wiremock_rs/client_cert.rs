@@ -0,0 +1,70 @@
+#[cfg(feature = "tls")]
+use itertools::Itertools;
+#[cfg(feature = "tls")]
+use openssl::{hash::MessageDigest, x509::X509Ref};
+
+/// Certificate a client presented over mutual TLS
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// Distinguished name e.g. 'CN=client, O=stubr'
+    pub subject: String,
+    /// DNS names, IP addresses, emails and URIs of the subject alternative names
+    pub sans: Vec<String>,
+    /// Lowercase hex encoded SHA-256 fingerprint
+    pub fingerprint: String,
+}
+
+impl ClientCertificate {
+    /// Lowercase and without the colons `openssl x509 -fingerprint` separates bytes with
+    pub(crate) fn normalize_fingerprint(fingerprint: &str) -> String {
+        fingerprint.replace(':', "").to_lowercase()
+    }
+
+    #[cfg(feature = "tls")]
+    fn ip(bytes: &[u8]) -> Option<String> {
+        match bytes.len() {
+            4 => Some(std::net::Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).to_string()),
+            16 => Some(std::net::Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<&X509Ref> for ClientCertificate {
+    fn from(cert: &X509Ref) -> Self {
+        let subject = cert
+            .subject_name()
+            .entries()
+            .filter_map(|e| {
+                let key = e.object().nid().short_name().ok()?;
+                let value = e.data().to_string().ok()?;
+                Some(format!("{key}={value}"))
+            })
+            .join(", ");
+        let sans = cert
+            .subject_alt_names()
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|n| {
+                        n.dnsname()
+                            .or_else(|| n.email())
+                            .or_else(|| n.uri())
+                            .map(str::to_string)
+                            .or_else(|| n.ipaddress().and_then(Self::ip))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let fingerprint = cert
+            .digest(MessageDigest::sha256())
+            .map(|d| d.iter().map(|b| format!("{b:02x}")).collect())
+            .unwrap_or_default();
+        Self {
+            subject,
+            sans,
+            fingerprint,
+        }
+    }
+}
@@ -1,20 +1,90 @@
-/// A distribution of a random delay
-#[derive(Debug, Clone)]
-pub struct LognormalDelay {
-    pub median: u64,
-    pub sigma: f64,
+use core::time::Duration;
+
+use rand::Rng as _;
+use rand_distr::Distribution as _;
+
+/// A distribution of a random delay, in milliseconds.
+/// See [https://wiremock.org/docs/simulating-faults/#per-stub-random-delays]
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DelayDistribution {
+    /// A pretty good approximation of long tailed latencies centered on the 50th percentile
+    Lognormal {
+        /// The 50th percentile of latencies
+        median: u64,
+        /// Standard deviation. The larger the value, the longer the tail
+        sigma: f64,
+    },
+    /// Delays evenly spread between `lower` and `upper`, both inclusive
+    Uniform { lower: u64, upper: u64 },
+    /// Delays centered on `mean`. The ones which would be negative are zero
+    Normal {
+        mean: u64,
+        /// Standard deviation, in milliseconds
+        sigma: f64,
+    },
+    /// Mostly short delays with a few long ones e.g. waiting in a queue
+    Exponential { mean: u64 },
+    /// Reproduces measured latency percentiles e.g. from production metrics.
+    /// Delays are interpolated between percentiles, spread from 0 below the lowest one and capped to the highest one
+    Percentiles {
+        p50: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        p75: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        p90: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        p95: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        p99: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        p999: Option<u64>,
+    },
 }
 
-impl LognormalDelay {
-    /// see [https://github.com/wiremock/wiremock/blob/60e9e858068548786af4a1a434b52fd1376c4d43/src/main/java/com/github/tomakehurst/wiremock/http/LogNormal.java#L52]
-    pub fn new_sample(&self) -> core::time::Duration {
-        let mean = self.median as f64;
-        // TODO: error handling
-        let normal = rand_distr::LogNormal::new(mean.ln(), self.sigma).unwrap();
+impl DelayDistribution {
+    pub fn new_sample(&self) -> Duration {
+        let mut rng = rand::thread_rng();
+        let milli = match *self {
+            // see [https://github.com/wiremock/wiremock/blob/60e9e858068548786af4a1a434b52fd1376c4d43/src/main/java/com/github/tomakehurst/wiremock/http/LogNormal.java#L52]
+            Self::Lognormal { median, sigma } => rand_distr::LogNormal::new((median as f64).ln(), sigma)
+                .map(|d| d.sample(&mut rng))
+                .unwrap_or(median as f64),
+            Self::Uniform { lower, upper } => rng.gen_range(lower.min(upper)..=upper.max(lower)) as f64,
+            Self::Normal { mean, sigma } => rand_distr::Normal::new(mean as f64, sigma)
+                .map(|d| d.sample(&mut rng))
+                .unwrap_or(mean as f64),
+            Self::Exponential { mean } => rand_distr::Exp::new(1.0 / mean as f64)
+                .map(|d| d.sample(&mut rng))
+                .unwrap_or(mean as f64),
+            Self::Percentiles { .. } => self.percentile(rng.gen_range(0.0..100.0)),
+        };
+        Duration::from_millis(milli.max(0.0) as u64)
+    }
 
-        use rand_distr::Distribution as _;
-        let milli = normal.sample(&mut rand::thread_rng()) as u64;
-        core::time::Duration::from_millis(milli)
+    /// Delay at the given percentile, interpolated between the known ones
+    fn percentile(&self, rank: f64) -> f64 {
+        let Self::Percentiles {
+            p50,
+            p75,
+            p90,
+            p95,
+            p99,
+            p999,
+        } = *self
+        else {
+            return 0.0;
+        };
+        let known = [(50.0, Some(p50)), (75.0, p75), (90.0, p90), (95.0, p95), (99.0, p99), (99.9, p999)];
+        let mut previous = (0.0, 0.0);
+        for (percentile, delay) in known.into_iter().filter_map(|(p, d)| Some((p, d? as f64))) {
+            if rank <= percentile {
+                let (lower_percentile, lower_delay) = previous;
+                return lower_delay + (delay - lower_delay) * (rank - lower_percentile) / (percentile - lower_percentile);
+            }
+            previous = (percentile, delay);
+        }
+        previous.1
     }
 }
 
@@ -22,21 +92,98 @@ impl LognormalDelay {
 pub mod tests {
     use super::*;
 
+    const ROUNDS: usize = 10_000;
+
+    fn mean(distribution: DelayDistribution) -> f64 {
+        let sum: f64 = (0..ROUNDS).map(|_| distribution.new_sample().as_millis() as f64).sum();
+        sum / (ROUNDS as f64)
+    }
+
+    fn samples(distribution: DelayDistribution) -> Vec<u64> {
+        let mut samples = (0..ROUNDS)
+            .map(|_| distribution.new_sample().as_millis() as u64)
+            .collect::<Vec<_>>();
+        samples.sort();
+        samples
+    }
+
     #[test]
     fn lognormal_should_return_expected_mean() {
-        const ROUNDS: usize = 10_000;
         const DELTA: f64 = 5.0;
         const EXPECTED: f64 = 97.1115;
         const LOWER: f64 = EXPECTED - DELTA;
         const UPPER: f64 = EXPECTED + DELTA;
 
-        let lognormal = LognormalDelay { median: 90, sigma: 0.39 };
-        let mut sum = 0.0;
-        for _ in 0..ROUNDS {
-            let sample = lognormal.new_sample().as_millis() as f64;
-            sum += sample;
-        }
-        let mean = sum / (ROUNDS as f64);
+        let mean = mean(DelayDistribution::Lognormal { median: 90, sigma: 0.39 });
         assert!((mean > LOWER) && (mean < UPPER));
     }
+
+    #[test]
+    fn uniform_should_stay_within_bounds() {
+        let samples = samples(DelayDistribution::Uniform { lower: 10, upper: 50 });
+        assert_eq!(samples.first(), Some(&10));
+        assert_eq!(samples.last(), Some(&50));
+    }
+
+    #[test]
+    fn normal_should_return_expected_mean_and_never_be_negative() {
+        let mean = mean(DelayDistribution::Normal { mean: 100, sigma: 20.0 });
+        assert!((mean > 97.0) && (mean < 103.0));
+        let samples = samples(DelayDistribution::Normal { mean: 10, sigma: 50.0 });
+        assert!(samples.contains(&0));
+    }
+
+    #[test]
+    fn exponential_should_return_expected_mean() {
+        let mean = mean(DelayDistribution::Exponential { mean: 100 });
+        assert!((mean > 95.0) && (mean < 105.0));
+    }
+
+    #[test]
+    fn percentiles_should_reproduce_known_percentiles() {
+        let distribution = DelayDistribution::Percentiles {
+            p50: 100,
+            p75: None,
+            p90: None,
+            p95: None,
+            p99: Some(1000),
+            p999: None,
+        };
+        let samples = samples(distribution);
+        let share_below = |delay: u64| samples.iter().filter(|s| **s <= delay).count() as f64 / ROUNDS as f64;
+        assert!((share_below(100) - 0.50).abs() < 0.03);
+        assert!((share_below(999) - 0.99).abs() < 0.01);
+        assert_eq!(samples.last(), Some(&1000));
+    }
+
+    #[test]
+    fn percentiles_should_interpolate_between_known_ones() {
+        let distribution = DelayDistribution::Percentiles {
+            p50: 100,
+            p75: None,
+            p90: Some(500),
+            p95: None,
+            p99: None,
+            p999: None,
+        };
+        assert_eq!(distribution.percentile(25.0), 50.0);
+        assert_eq!(distribution.percentile(70.0), 300.0);
+        assert_eq!(distribution.percentile(99.0), 500.0);
+    }
+
+    #[test]
+    fn should_deserialize_wiremock_distributions() {
+        let uniform = serde_json::from_str::<DelayDistribution>(r#"{"type": "uniform", "lower": 10, "upper": 50}"#).unwrap();
+        assert_eq!(uniform, DelayDistribution::Uniform { lower: 10, upper: 50 });
+        let percentiles = serde_json::from_str::<DelayDistribution>(r#"{"type": "percentiles", "p50": 10, "p99": 50}"#).unwrap();
+        assert!(matches!(
+            percentiles,
+            DelayDistribution::Percentiles {
+                p50: 10,
+                p99: Some(50),
+                p90: None,
+                ..
+            }
+        ));
+    }
 }
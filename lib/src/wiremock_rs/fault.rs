@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use crate::StubrError;
+
+/// A broken response, see [https://wiremock.org/docs/simulating-faults/#bad-responses]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Fault {
+    /// Closes the connection abruptly, the client gets a 'connection reset by peer' error
+    ConnectionResetByPeer,
+    /// Closes the connection without sending anything
+    EmptyResponse,
+    /// Sends a 200 status and headers then an invalid chunk before closing the connection
+    MalformedResponseChunk,
+    /// Sends garbage then closes the connection
+    RandomDataThenClose,
+}
+
+impl Fault {
+    /// Body sent before the connection is closed on a [Fault::MalformedResponseChunk]
+    pub(crate) const MALFORMED_CHUNK: &'static [u8] = b"lorem ipsum";
+}
+
+impl FromStr for Fault {
+    type Err = StubrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_uppercase())).map_err(|_| StubrError::UnknownFault(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod fault_tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_wiremock_names() {
+        assert_eq!(
+            Fault::from_str("CONNECTION_RESET_BY_PEER").unwrap(),
+            Fault::ConnectionResetByPeer
+        );
+        assert_eq!(Fault::from_str("empty_response").unwrap(), Fault::EmptyResponse);
+        assert_eq!(
+            Fault::from_str("MALFORMED_RESPONSE_CHUNK").unwrap(),
+            Fault::MalformedResponseChunk
+        );
+        assert_eq!(Fault::from_str("RANDOM_DATA_THEN_CLOSE").unwrap(), Fault::RandomDataThenClose);
+        assert!(Fault::from_str("UNKNOWN").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::wiremock_rs::mock_set::MountedMockState;
+use crate::wiremock_rs::mock_set::{MountedMockState, RequestMatch};
 use crate::wiremock_rs::{mock_server::bare_server::MockServerState, mock_set::MountedMockSet, ResponseTemplate};
 use futures_timer::Delay;
 use hyper::{Body, Request};
@@ -22,28 +22,38 @@ impl MockServerState {
     pub(crate) async fn handle_grpc_request(
         &mut self, request: crate::wiremock_rs::Request,
     ) -> (hyper::Response<hyper::Body>, Option<futures_timer::Delay>) {
-        self.mock_set.handle_grpc_request(request).await
+        // see MockServerState::handle_request
+        let _state = self.mock_set.state.enter();
+        let (response, delay, request_match) = self.mock_set.handle_grpc_request(&request);
+        self.journal.record(&request, request_match);
+        (response, delay)
     }
 }
 
 impl MountedMockSet {
-    pub(crate) async fn handle_grpc_request(
-        &mut self, request: crate::wiremock_rs::Request,
-    ) -> (hyper::Response<hyper::Body>, Option<Delay>) {
+    /// Not async, like [MountedMockSet::handle_request]
+    pub(crate) fn handle_grpc_request(
+        &mut self, request: &crate::wiremock_rs::Request,
+    ) -> (hyper::Response<hyper::Body>, Option<Delay>, RequestMatch) {
         let mut response_template: Option<ResponseTemplate> = None;
+        let mut request_match = RequestMatch::Unmatched;
         self.mocks.sort_by_key(|(m, _)| m.specification.priority);
         for (mock, mock_state) in &mut self.mocks {
-            if *mock_state == MountedMockState::OutOfScope {
+            if *mock_state == MountedMockState::OutOfScope || !self.scenarios.allows_mock(&mock.specification) {
                 continue;
             }
-            if mock.matches(&request) {
-                response_template = mock.response_template(&request).ok();
+            if mock.matches(request) {
+                response_template = mock.response_template(request).ok();
+                request_match = RequestMatch::Matched(mock.specification.id.clone());
+                if let Some(scenario) = mock.specification.scenario.as_ref() {
+                    self.scenarios.transition(scenario);
+                }
                 break;
             }
         }
         if let Some(response_template) = response_template {
-            let delay = response_template.delay().map(|d| Delay::new(d.into_owned()));
-            (response_template.generate_grpc_response(), delay)
+            let delay = response_template.delay().map(Delay::new);
+            (response_template.generate_grpc_response(), delay, request_match)
         } else {
             let default_resp = tonic::codegen::http::Response::builder()
                 .status(200)
@@ -51,7 +61,7 @@ impl MountedMockSet {
                 .header("content-type", "application/grpc")
                 .body(hyper::Body::from(vec![0u8; 5]))
                 .unwrap();
-            (default_resp, None)
+            (default_resp, None, request_match)
         }
     }
 }
@@ -66,7 +76,13 @@ impl ResponseTemplate {
             mime: None,
             body: None,
             delay: None,
-            lognormal_delay: None,
+            random_delays: vec![],
+            fault: None,
+            throttle: None,
+            stream: None,
+            proxy: None,
+            webhooks: vec![],
+            web_socket: None,
         }
     }
 
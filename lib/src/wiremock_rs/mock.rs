@@ -1,3 +1,4 @@
+use crate::server::admin::scenario::Scenario;
 use crate::wiremock_rs::respond::Respond;
 use crate::wiremock_rs::{MockGuard, MockServer, Request, ResponseTemplate};
 use crate::StubrResult;
@@ -270,6 +271,11 @@ pub struct Mock {
     pub(crate) name: Option<String>,
     /// The expectation is satisfied if the number of incoming requests falls within `expectation_range`.
     pub(crate) expectation_range: Times,
+    /// Identifier of the stub this mock was created from.
+    /// Used by the admin api to find a mounted mock back.
+    pub(crate) id: Option<String>,
+    /// Scenario gating this mock, if it belongs to one.
+    pub(crate) scenario: Option<Scenario>,
 }
 
 /// A fluent builder to construct a [`Mock`] instance given matchers and a [`ResponseTemplate`].
@@ -279,6 +285,9 @@ pub struct MockBuilder {
 }
 
 impl Mock {
+    /// Lowest priority, reserved to the mocks stubr registers itself so that stubs always take precedence
+    pub(crate) const INTERNAL_PRIORITY: u8 = u8::MAX;
+
     /// Start building a [`Mock`] specifying the first matcher.
     ///
     /// It returns an instance of [`MockBuilder`].
@@ -387,7 +396,6 @@ impl Mock {
     /// ```
     ///
     /// [`matchers`]: crate::matchers
-    #[allow(dead_code)]
     pub fn with_priority(mut self, p: u8) -> Mock {
         assert!(p > 0, "priority must be strictly greater than 0!");
         self.priority = p;
@@ -515,6 +523,20 @@ impl Mock {
         self
     }
 
+    /// Identifies this [`Mock`] with the id of the stub it originates from so that it can later
+    /// be replaced or removed at runtime.
+    pub(crate) fn identified<T: Into<String>>(mut self, id: T) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Makes this [`Mock`] part of a scenario: it only matches requests when the scenario is in the
+    /// required state, then moves the scenario to a new state.
+    pub(crate) fn in_scenario(mut self, scenario: Scenario) -> Self {
+        self.scenario = Some(scenario);
+        self
+    }
+
     /// Mount a [`Mock`] on an instance of [`MockServer`].
     /// The [`Mock`] will remain active until [`MockServer`] is shut down. If you want to control or limit how
     /// long your [`Mock`] stays active, check out [`Mock::mount_as_scoped`].
@@ -631,9 +653,10 @@ impl Mock {
     }
 
     /// Given a [`Request`](crate::Request) build an instance a [`ResponseTemplate`] using
-    /// the responder associated with the `Mock`.
-    pub(crate) fn response_template(&self, request: &Request) -> StubrResult<ResponseTemplate> {
-        self.response.respond(request)
+    /// the responder associated with the `Mock`, `nth` being the (zero indexed) position of
+    /// the request among the ones this `Mock` matched.
+    pub(crate) fn response_template(&self, request: &Request, nth: u64) -> StubrResult<ResponseTemplate> {
+        self.response.respond_nth(request, nth)
     }
 }
 
@@ -664,6 +687,8 @@ impl MockBuilder {
             priority: 5,
             name: None,
             expectation_range: Times(TimesEnum::Unbounded(RangeFull)),
+            id: None,
+            scenario: None,
         }
     }
 }
@@ -1,9 +1,12 @@
 use std::net::{SocketAddr, TcpListener, TcpStream};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use crate::cloud::metrics::Metrics;
+use crate::server::admin::{journal::RequestJournal, StubrAdmin};
 use crate::wiremock_rs::mock_server::hyper::try_run_server;
+use crate::wiremock_rs::mock_server::TlsAcceptor;
 use crate::wiremock_rs::mock_set::MockId;
-use crate::wiremock_rs::mock_set::MountedMockSet;
+use crate::wiremock_rs::mock_set::{MountedMockSet, RequestMatch};
 use crate::wiremock_rs::{mock::Mock, verification::VerificationOutcome, Request};
 use crate::StubrResult;
 
@@ -16,6 +19,8 @@ use crate::StubrResult;
 pub(crate) struct BareMockServer {
     pub(crate) state: Arc<tokio::sync::RwLock<MockServerState>>,
     pub(crate) server_address: SocketAddr,
+    /// Whether connections are over TLS
+    pub(crate) tls: bool,
     // When `_shutdown_trigger` gets dropped the listening server terminates gracefully.
     pub(crate) _shutdown_trigger: tokio::sync::oneshot::Sender<()>,
 }
@@ -26,23 +31,42 @@ pub(crate) struct BareMockServer {
 pub(crate) struct MockServerState {
     pub(crate) mock_set: MountedMockSet,
     pub(crate) received_requests: Option<Vec<Request>>,
+    pub(crate) admin: StubrAdmin,
+    pub(crate) journal: RequestJournal,
+    /// Apart from the rest of the state, so that recording a response served does not wait for other requests
+    pub(crate) metrics: Arc<Mutex<Metrics>>,
 }
 
 impl MockServerState {
-    pub(crate) async fn handle_request(&mut self, request: Request) -> (http_types::Response, Option<futures_timer::Delay>) {
+    /// Also returns the name of the stub which matched the request, if any
+    pub(crate) async fn handle_request(
+        &mut self, request: &Request,
+    ) -> (http_types::Response, Option<futures_timer::Delay>, Option<String>) {
         // If request recording is enabled, record the incoming request
         // by adding it to the `received_requests` stack
         if let Some(received_requests) = &mut self.received_requests {
             received_requests.push(request.clone());
         }
-        self.mock_set.handle_request(request).await
+        // Templates and matchers reach the server's state through a thread local, set until the end of this function.
+        // Nothing is awaited meanwhile: another request handled on this thread would otherwise see this server's state.
+        // Besides, the write lock on the server state already serializes requests, all handled on a single-threaded LocalSet
+        let _state = self.mock_set.state.enter();
+        let (mut response, delay, request_match) = self.mock_set.handle_request(request);
+        if request_match == RequestMatch::Unmatched {
+            self.admin.report_near_misses(request, &mut response);
+            self.admin.record_on_miss(&mut response);
+        }
+        self.admin.record_hit(&request_match);
+        let stub_name = self.admin.stub_name(&request_match);
+        self.journal.record(request, request_match);
+        (response, delay, stub_name)
     }
 }
 
 impl BareMockServer {
     /// Start a new instance of a `BareMockServer` listening on the specified
     /// [`TcpListener`](TcpListener).
-    pub(super) async fn start(listener: TcpListener, request_recording: RequestRecording) -> StubrResult<Self> {
+    pub(super) async fn start(listener: TcpListener, request_recording: RequestRecording, tls: Option<TlsAcceptor>) -> StubrResult<Self> {
         let (shutdown_trigger, shutdown_receiver) = tokio::sync::oneshot::channel();
         let received_requests = match request_recording {
             RequestRecording::Enabled => Some(Vec::new()),
@@ -51,12 +75,16 @@ impl BareMockServer {
         let state = Arc::new(tokio::sync::RwLock::new(MockServerState {
             mock_set: MountedMockSet::new(),
             received_requests,
+            admin: StubrAdmin::default(),
+            journal: RequestJournal::default(),
+            metrics: Arc::default(),
         }));
         let server_address = listener.local_addr().expect("Failed to get server address.");
 
         let server_state = state.clone();
+        let is_tls = tls.is_some();
         std::thread::spawn(move || {
-            let server_future = try_run_server(listener, server_state, shutdown_receiver);
+            let server_future = try_run_server(listener, tls, server_state, shutdown_receiver);
 
             let runtime = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
@@ -77,6 +105,7 @@ impl BareMockServer {
         Ok(Self {
             state,
             server_address,
+            tls: is_tls,
             _shutdown_trigger: shutdown_trigger,
         })
     }
@@ -125,12 +154,13 @@ impl BareMockServer {
         mock_set.verify_all()
     }
 
-    /// Return the base uri of this running instance of `BareMockServer`, e.g. `http://127.0.0.1:4372`.
+    /// Return the base uri of this running instance of `BareMockServer`, e.g. `http://127.0.0.1:4372`, `https` when serving over TLS.
     ///
     /// Use this method to compose uris when interacting with this instance of `BareMockServer` via
     /// an HTTP client.
     pub(crate) fn uri(&self) -> String {
-        format!("http://{}", self.server_address)
+        let scheme = if self.tls { "https" } else { "http" };
+        format!("{scheme}://{}", self.server_address)
     }
 
     /// Return the socket address of this running instance of `BareMockServer`, e.g. `127.0.0.1:4372`.
@@ -0,0 +1,54 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use hyper::{body::HttpBody as _, http};
+
+/// Runs a hook once hyper is done with a response body i.e. once it has been written or the connection closed.
+/// Hyper writes a body it knows in full along with the headers and never polls the body of a response to a 'HEAD'
+/// request: dropping the body is the only step every response goes through
+pub(crate) struct BodyHook {
+    body: hyper::Body,
+    hook: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl BodyHook {
+    /// * `method` - method of the request answered, which may forbid the response from having a length
+    pub(crate) fn attach(
+        response: hyper::Response<hyper::Body>, method: http_types::Method, hook: impl FnOnce() + Send + 'static,
+    ) -> hyper::Response<hyper::Body> {
+        let (mut parts, body) = response.into_parts();
+        // hyper can no longer size the body once wrapped and would send it with chunked transfer encoding
+        let sized = parts.headers.contains_key(http::header::CONTENT_LENGTH) || parts.headers.contains_key(http::header::TRANSFER_ENCODING);
+        // like hyper, e.g. the response upgrading a WebSocket connection or establishing a tunnel
+        let bodyless = parts.status.is_informational()
+            || parts.status == http::StatusCode::NO_CONTENT
+            || parts.status == http::StatusCode::NOT_MODIFIED
+            || (method == http_types::Method::Connect && parts.status.is_success());
+        if let Some(length) = body.size_hint().exact().filter(|_| !sized && !bodyless) {
+            parts.headers.insert(http::header::CONTENT_LENGTH, length.into());
+        }
+        let body = Self {
+            body,
+            hook: Some(Box::new(hook)),
+        };
+        hyper::Response::from_parts(parts, hyper::Body::wrap_stream(body))
+    }
+}
+
+impl futures::Stream for BodyHook {
+    type Item = Result<hyper::body::Bytes, hyper::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.body).poll_data(cx)
+    }
+}
+
+impl Drop for BodyHook {
+    fn drop(&mut self) {
+        if let Some(hook) = self.hook.take() {
+            hook();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::wiremock_rs::mock_server::bare_server::{BareMockServer, RequestRecording};
 use crate::wiremock_rs::mock_server::exposed_server::InnerServer;
+use crate::wiremock_rs::mock_server::TlsAcceptor;
 use crate::wiremock_rs::MockServer;
 use crate::StubrResult;
 use std::net::TcpListener;
@@ -9,6 +10,7 @@ use std::net::TcpListener;
 pub struct MockServerBuilder {
     listener: Option<TcpListener>,
     record_incoming_requests: bool,
+    tls: Option<TlsAcceptor>,
 }
 
 impl MockServerBuilder {
@@ -16,6 +18,7 @@ impl MockServerBuilder {
         Self {
             listener: None,
             record_incoming_requests: true,
+            tls: None,
         }
     }
 
@@ -77,6 +80,15 @@ impl MockServerBuilder {
         self
     }
 
+    /// By default the [`MockServer`] serves plain http.
+    /// With `MockServerBuilder::tls` it serves https instead, presenting the certificate of the given acceptor.
+    /// Clients negotiating HTTP/2 with ALPN are served over it, as configured on the acceptor.
+    #[cfg(feature = "tls")]
+    pub(crate) fn tls(mut self, acceptor: TlsAcceptor) -> Self {
+        self.tls = Some(acceptor);
+        self
+    }
+
     /// Finalise the builder to get an instance of a [`BareMockServer`].
     pub(super) async fn build_bare(self) -> StubrResult<BareMockServer> {
         let listener = if let Some(listener) = self.listener {
@@ -89,7 +101,7 @@ impl MockServerBuilder {
         } else {
             RequestRecording::Disabled
         };
-        BareMockServer::start(listener, recording).await
+        BareMockServer::start(listener, recording, self.tls).await
     }
 
     /// Finalise the builder and launch the [`MockServer`] instance!
@@ -1,11 +1,15 @@
 use std::net::SocketAddr;
 use std::ops::Deref;
+use std::sync::Arc;
 
 use log::debug;
 
 use crate::wiremock_rs::{
     mock::Mock,
-    mock_server::{bare_server::BareMockServer, MockServerBuilder},
+    mock_server::{
+        bare_server::{BareMockServer, MockServerState},
+        MockServerBuilder,
+    },
     verification::VerificationOutcome,
     MockGuard, Request,
 };
@@ -430,6 +434,12 @@ impl MockServer {
     pub async fn received_requests(&self) -> Option<Vec<Request>> {
         self.0.received_requests().await
     }
+
+    /// Shared state of the running server, for the parts of stubr which have to alter it at runtime
+    /// e.g. the admin api.
+    pub(crate) fn state(&self) -> Arc<tokio::sync::RwLock<MockServerState>> {
+        self.0.state.clone()
+    }
 }
 
 impl Drop for MockServer {
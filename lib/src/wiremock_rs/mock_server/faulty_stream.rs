@@ -0,0 +1,152 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+#[cfg(feature = "tls")]
+use tokio_openssl::SslStream;
+
+use crate::wiremock_rs::{client_cert::ClientCertificate, fault::Fault};
+
+/// Fault a handler injects in the connection the request came from
+pub(crate) type ConnectionFault = Arc<Mutex<Option<Fault>>>;
+
+/// A connection accepted by the mock server, over https when it has a certificate
+pub(crate) enum Connection {
+    Plain(TcpStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<SslStream<TcpStream>>),
+}
+
+impl Connection {
+    /// Certificate the client presented over mutual TLS
+    fn client_certificate(&self) -> Option<ClientCertificate> {
+        match self {
+            Self::Plain(_) => None,
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.ssl().peer_certificate().as_deref().map(ClientCertificate::from),
+        }
+    }
+
+    fn socket(&self) -> &TcpStream {
+        match self {
+            Self::Plain(stream) => stream,
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.get_ref(),
+        }
+    }
+}
+
+impl AsyncRead for Connection {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Connection {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+/// A connection accepted by the mock server which a response [Fault] can sabotage.
+/// Faults which cannot be expressed with a hyper response are applied here, on the raw socket.
+pub(crate) struct FaultyStream {
+    inner: Connection,
+    fault: ConnectionFault,
+}
+
+impl FaultyStream {
+    pub(crate) fn new(inner: Connection) -> Self {
+        Self {
+            inner,
+            fault: Arc::default(),
+        }
+    }
+
+    pub(crate) fn fault(&self) -> ConnectionFault {
+        self.fault.clone()
+    }
+
+    pub(crate) fn client_certificate(&self) -> Option<ClientCertificate> {
+        self.inner.client_certificate()
+    }
+
+    fn current_fault(&self) -> Option<Fault> {
+        self.fault.lock().ok().and_then(|f| *f)
+    }
+
+    fn random_data(len: usize) -> Vec<u8> {
+        (0..len).map(|_| rand::random::<u8>()).collect()
+    }
+}
+
+impl AsyncRead for FaultyStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for FaultyStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if self.current_fault() == Some(Fault::RandomDataThenClose) {
+            let garbage = Self::random_data(buf.len());
+            return match Pin::new(&mut self.inner).poll_write(cx, &garbage) {
+                // hyper then drops, hence closes, the connection
+                Poll::Ready(Ok(_)) => Poll::Ready(Err(io::Error::new(io::ErrorKind::ConnectionAborted, "random data fault"))),
+                other => other,
+            };
+        }
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl Drop for FaultyStream {
+    fn drop(&mut self) {
+        if self.current_fault() == Some(Fault::ConnectionResetByPeer) {
+            // closing a socket with a zero linger sends a RST instead of a FIN.
+            // Only a non-zero linger blocks on drop
+            #[allow(deprecated)]
+            let _ = self.inner.socket().set_linger(Some(Duration::ZERO));
+        }
+    }
+}
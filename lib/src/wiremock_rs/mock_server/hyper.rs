@@ -1,23 +1,72 @@
-use std::{net::TcpListener, sync::Arc};
+use std::{
+    net::TcpListener,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
 
+use futures::StreamExt as _;
 use hyper::{
     http,
     service::{make_service_fn, service_fn},
 };
+#[cfg(feature = "tls")]
+use openssl::ssl::{Ssl, SslAcceptor};
+use tokio::net::TcpStream;
+#[cfg(feature = "tls")]
+use tokio_openssl::SslStream;
+
+use super::{
+    body_hook::BodyHook,
+    faulty_stream::{Connection, ConnectionFault, FaultyStream},
+    TlsAcceptor,
+};
+#[cfg(feature = "websocket")]
+use crate::wiremock_rs::websocket::WebSocketScript;
+use crate::{
+    cloud::metrics::Metrics,
+    server::{admin::StubrAdmin, record_on_miss::MissRecorder},
+    wiremock_rs::{
+        client_cert::ClientCertificate,
+        fault::Fault,
+        mock_server::bare_server::MockServerState,
+        proxy::{Proxy, ProxyClient},
+        stream::StreamedBody,
+        throttle::Throttle,
+        webhook::Webhooks,
+    },
+    StubrResult,
+};
 
-use crate::{wiremock_rs::mock_server::bare_server::MockServerState, StubrResult};
+pub(crate) type DynError = Box<dyn std::error::Error + Send + Sync>;
 
-type DynError = Box<dyn std::error::Error + Send + Sync>;
+/// TLS handshakes of incoming connections happening at the same time
+const MAX_PENDING_HANDSHAKES: usize = 64;
+/// A client not completing its TLS handshake by then is disconnected, so that it does not hold a handshake slot forever
+#[cfg(feature = "tls")]
+const TLS_HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Pause before accepting connections again after an error such as too many open files
+const ACCEPT_ERROR_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
 
 /// The actual HTTP server responding to incoming requests according to the specified mocks.
 pub(super) async fn try_run_server(
-    listener: TcpListener, server_state: Arc<tokio::sync::RwLock<MockServerState>>, shutdown_signal: tokio::sync::oneshot::Receiver<()>,
+    listener: TcpListener, tls: Option<TlsAcceptor>, server_state: Arc<tokio::sync::RwLock<MockServerState>>,
+    shutdown_signal: tokio::sync::oneshot::Receiver<()>,
 ) -> StubrResult<()> {
-    let request_handler = make_service_fn(move |_| {
+    let proxy_client = Proxy::client();
+    let metrics = server_state.read().await.metrics.clone();
+    let request_handler = make_service_fn(move |connection: &FaultyStream| {
         let server_state = server_state.clone();
+        let proxy_client = proxy_client.clone();
+        let metrics = metrics.clone();
+        let connection_fault = connection.fault();
+        let client_certificate = connection.client_certificate();
         async move {
             Ok::<_, DynError>(service_fn(move |request: hyper::Request<hyper::Body>| {
                 let server_state = server_state.clone();
+                let proxy_client = proxy_client.clone();
+                let metrics = metrics.clone();
+                let connection_fault = connection_fault.clone();
+                let client_certificate = client_certificate.clone();
                 async move {
                     let content_type = request.headers().get("content-type").map(|v| v.as_bytes());
                     match content_type {
@@ -31,15 +80,36 @@ pub(super) async fn try_run_server(
                                 panic!("Received a gRPC request but 'grpc' feature is not turned on")
                             }
                         },
-                        _ => handle_http(request, server_state).await,
+                        _ if StubrAdmin::is_admin(request.uri().path()) => StubrAdmin::handle(request, server_state).await,
+                        _ => {
+                            handle_http(
+                                request,
+                                server_state,
+                                &proxy_client,
+                                metrics,
+                                connection_fault,
+                                client_certificate,
+                            )
+                            .await
+                        },
                     }
                 }
             }))
         }
     });
 
-    let server = hyper::Server::from_tcp(listener)
-        .unwrap()
+    // accepting connections ourselves so that faults can be injected on them
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    let connections = futures::stream::unfold(listener, |listener| async move {
+        let stream = accept(&listener).await;
+        Some((stream, listener))
+    })
+    .map(move |stream| handshake(stream, tls.clone()))
+    // a slow handshake does not hold back the other connections
+    .buffer_unordered(MAX_PENDING_HANDSHAKES)
+    .filter_map(futures::future::ready);
+    let server = hyper::Server::builder(hyper::server::accept::from_stream(connections))
         .executor(LocalExec)
         .serve(request_handler)
         .with_graceful_shutdown(async {
@@ -52,11 +122,70 @@ pub(super) async fn try_run_server(
     Ok(server.await?)
 }
 
+/// Next incoming connection. Like hyper's `AddrIncoming`, accept errors are logged and skipped instead of
+/// stopping the server: a connection aborted before being accepted or running out of file descriptors is transient
+async fn accept(listener: &tokio::net::TcpListener) -> TcpStream {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let _ = stream.set_nodelay(true);
+                return stream;
+            },
+            Err(e) if is_connection_error(&e) => log::debug!("skipping connection which failed before being accepted: {e}"),
+            Err(e) => {
+                log::error!("failed accepting a connection, retrying in {ACCEPT_ERROR_BACKOFF:?}: {e}");
+                tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+            },
+        }
+    }
+}
+
+/// Errors only concerning the connection being accepted, retrying right away is fine
+fn is_connection_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::ConnectionRefused | std::io::ErrorKind::ConnectionAborted | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+/// A connection whose TLS handshake fails or times out is dropped instead of failing the whole server
+async fn handshake(stream: TcpStream, tls: Option<TlsAcceptor>) -> Option<std::io::Result<FaultyStream>> {
+    let connection = match tls {
+        #[cfg(feature = "tls")]
+        Some(tls) => match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, accept_tls(stream, &tls)).await {
+            Ok(Ok(stream)) => Connection::Tls(Box::new(stream)),
+            Ok(Err(e)) => {
+                log::debug!("dropping connection because its TLS handshake failed: {e}");
+                return None;
+            },
+            Err(_) => {
+                log::debug!("dropping connection because its TLS handshake took more than {TLS_HANDSHAKE_TIMEOUT:?}");
+                return None;
+            },
+        },
+        None => Connection::Plain(stream),
+    };
+    Some(Ok(FaultyStream::new(connection)))
+}
+
+#[cfg(feature = "tls")]
+async fn accept_tls(stream: TcpStream, tls: &SslAcceptor) -> Result<SslStream<TcpStream>, DynError> {
+    let mut stream = SslStream::new(Ssl::new(tls.context())?, stream)?;
+    std::pin::Pin::new(&mut stream).accept().await?;
+    Ok(stream)
+}
+
 async fn handle_http(
-    request: hyper::Request<hyper::Body>, server_state: Arc<tokio::sync::RwLock<MockServerState>>,
+    #[cfg_attr(not(feature = "websocket"), allow(unused_mut))] mut request: hyper::Request<hyper::Body>,
+    server_state: Arc<tokio::sync::RwLock<MockServerState>>, proxy_client: &ProxyClient, metrics: Arc<Mutex<Metrics>>,
+    connection_fault: ConnectionFault, client_certificate: Option<ClientCertificate>,
 ) -> Result<hyper::Response<hyper::Body>, Box<dyn std::error::Error + Send + Sync>> {
-    let wiremock_request = crate::wiremock_rs::Request::from_hyper(request).await;
-    let (response, delay) = server_state.write().await.handle_request(wiremock_request).await;
+    let started = Instant::now();
+    #[cfg(feature = "websocket")]
+    let on_upgrade = hyper::upgrade::on(&mut request);
+    let mut wiremock_request = crate::wiremock_rs::Request::from_hyper(request).await;
+    wiremock_request.client_certificate = client_certificate;
+    let (response, delay, stub_name) = server_state.write().await.handle_request(&wiremock_request).await;
 
     // We do not wait for the delay within the handler otherwise we would be
     // holding on to the write-side of the `tokio::sync::RwLock` on `mock_set`.
@@ -69,8 +198,116 @@ async fn handle_http(
     if let Some(delay) = delay {
         delay.await;
     }
+    let method = wiremock_request.method;
+    let observe = move |status: u16| {
+        let mut metrics = metrics.lock().unwrap_or_else(PoisonError::into_inner);
+        metrics.observe(stub_name.as_deref(), status, started.elapsed());
+    };
 
-    Ok::<_, DynError>(http_types_response_to_hyper_response(response).await)
+    let webhooks = response.ext().get::<Webhooks>().cloned();
+    #[cfg(feature = "websocket")]
+    if let Some(script) = response.ext().get::<WebSocketScript>().cloned() {
+        let response = script.upgrade(wiremock_request, on_upgrade);
+        return Ok(served(response, method, observe, webhooks));
+    }
+    if let Some(fault) = response.ext().get::<Fault>().copied() {
+        // the response never makes it to the client, neither do its webhooks
+        let status = response.status().into();
+        return match inject_fault(fault, response, connection_fault).await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                Ok(BodyHook::attach(response, method, move || observe(status)))
+            },
+            Err(e) => {
+                // no response at all, counted with the status of the stub
+                observe(status);
+                Err(e)
+            },
+        };
+    }
+    let response = respond(response, &wiremock_request, server_state, proxy_client).await?;
+    Ok(served(response, method, observe, webhooks))
+}
+
+/// Once the client got the response: its delivery is timed and webhooks are sent then, as Wiremock does
+fn served(
+    response: hyper::Response<hyper::Body>, method: http_types::Method, observe: impl FnOnce(u16) + Send + 'static,
+    webhooks: Option<Webhooks>,
+) -> hyper::Response<hyper::Body> {
+    let status = response.status().as_u16();
+    BodyHook::attach(response, method, move || {
+        observe(status);
+        if let Some(webhooks) = webhooks {
+            webhooks.dispatch();
+        }
+    })
+}
+
+/// Turns the response of the matching stub into the one sent to the client, possibly streamed or fetched upstream
+async fn respond(
+    response: http_types::Response, wiremock_request: &crate::wiremock_rs::Request,
+    server_state: Arc<tokio::sync::RwLock<MockServerState>>, proxy_client: &ProxyClient,
+) -> Result<hyper::Response<hyper::Body>, DynError> {
+    let throttle = response.ext().get::<Throttle>().copied();
+    let stream = response.ext().get::<StreamedBody>().cloned();
+    let recorder = response.ext().get::<MissRecorder>().cloned();
+    let response = match response.ext().get::<Proxy>().cloned() {
+        Some(proxy) => match (proxy.forward(proxy_client, wiremock_request).await, recorder) {
+            (Ok(response), Some(recorder)) => recorder.record(wiremock_request, response, server_state).await?,
+            (Ok(response), None) => response,
+            (Err(e), _) => bad_gateway(&proxy, e),
+        },
+        None => http_types_response_to_hyper_response(response).await,
+    };
+    if let Some(stream) = stream {
+        let (mut parts, _) = response.into_parts();
+        // without a length hyper sends the body with chunked transfer encoding
+        parts.headers.remove(http::header::CONTENT_LENGTH);
+        return Ok(hyper::Response::from_parts(parts, stream.body()));
+    }
+    if let Some(throttle) = throttle {
+        let (mut parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+        // a streamed body would otherwise be sent with chunked transfer encoding
+        parts.headers.remove(http::header::TRANSFER_ENCODING);
+        parts.headers.insert(http::header::CONTENT_LENGTH, body.len().into());
+        return Ok(hyper::Response::from_parts(parts, throttle.body(body)));
+    }
+    Ok(response)
+}
+
+fn bad_gateway(proxy: &Proxy, error: DynError) -> hyper::Response<hyper::Body> {
+    let message = format!("Failed proxying request to '{}' because {error}", proxy.base_url);
+    hyper::Response::builder()
+        .status(http::StatusCode::BAD_GATEWAY)
+        .body(hyper::Body::from(message))
+        .unwrap_or_default()
+}
+
+/// Failing the handler makes hyper close the connection without responding
+async fn inject_fault(
+    fault: Fault, response: http_types::Response, connection_fault: ConnectionFault,
+) -> Result<hyper::Response<hyper::Body>, DynError> {
+    if let Ok(mut connection_fault) = connection_fault.lock() {
+        *connection_fault = Some(fault);
+    }
+    match fault {
+        Fault::ConnectionResetByPeer | Fault::EmptyResponse => Err(format!("{fault:?} fault").into()),
+        Fault::MalformedResponseChunk => {
+            // hyper sends nothing when the body fails before it had a chance to flush the first chunk
+            let chunk = futures::stream::once(async { Ok::<_, DynError>(Fault::MALFORMED_CHUNK) });
+            let failure = futures::stream::once(async {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                Err::<&'static [u8], DynError>("malformed chunk fault".into())
+            });
+            let mut response = http_types_response_to_hyper_response(response).await;
+            response.headers_mut().remove(http::header::CONTENT_LENGTH);
+            *response.status_mut() = http::StatusCode::OK;
+            *response.body_mut() = hyper::Body::wrap_stream(chunk.chain(failure));
+            Ok(response)
+        },
+        Fault::RandomDataThenClose => Ok(http_types_response_to_hyper_response(response).await),
+    }
 }
 
 // An executor that can spawn !Send futures.
@@ -11,11 +11,21 @@
 //! we provision a dedicated one if they specify their own `TcpListener` with `start_on`.
 //! Check the `pool` submodule for more details on our pooling strategy.
 pub(crate) mod bare_server;
+mod body_hook;
 mod builder;
 mod exposed_server;
+mod faulty_stream;
 pub(crate) mod hyper;
 // mod pool;
 
 pub use bare_server::MockGuard;
 pub use builder::MockServerBuilder;
 pub use exposed_server::MockServer;
+
+/// Certificate and settings presented to clients connecting over https
+#[cfg(feature = "tls")]
+pub(crate) type TlsAcceptor = openssl::ssl::SslAcceptor;
+/// Without the 'tls' feature the server only speaks plain http, there is no way to build one
+#[cfg(not(feature = "tls"))]
+#[derive(Clone)]
+pub(crate) enum TlsAcceptor {}
@@ -1,3 +1,4 @@
+use crate::server::admin::{scenario::Scenarios, state::StateStore};
 use crate::wiremock_rs::{
     mounted_mock::MountedMock,
     verification::{VerificationOutcome, VerificationReport},
@@ -22,6 +23,10 @@ pub(crate) struct MountedMockSet {
     /// We need `generation` to know if a [`MockId`] points to an [`MountedMock`] that has been
     /// removed via [`MountedMockSet::reset`].
     generation: u16,
+    /// State of the scenarios mocks belong to
+    pub(crate) scenarios: Scenarios,
+    /// Values response templates store and matchers check
+    pub(crate) state: StateStore,
 }
 
 /// A `MockId` is an opaque index that uniquely identifies an [`MountedMock`] inside an [`MountedMockSet`].
@@ -36,38 +41,61 @@ pub(crate) struct MockId {
     generation: u16,
 }
 
+/// Tells whether a request was served by one of the mocks of a [`MountedMockSet`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RequestMatch {
+    /// Holds the id of the stub the matching mock was created from, if any
+    Matched(Option<String>),
+    Unmatched,
+}
+
 impl MountedMockSet {
     /// Create a new instance of MockSet.
     pub(crate) fn new() -> MountedMockSet {
         MountedMockSet {
             mocks: vec![],
             generation: 0,
+            scenarios: Scenarios::default(),
+            state: StateStore::default(),
         }
     }
 
-    pub(crate) async fn handle_request(&mut self, request: Request) -> (Response, Option<Delay>) {
+    /// Not async: it runs within the [crate::server::admin::state::StateScope] of the server, which must not be
+    /// held across an `.await`
+    pub(crate) fn handle_request(&mut self, request: &Request) -> (Response, Option<Delay>, RequestMatch) {
         debug!("Handling request.");
         let mut response_template: Option<ResponseTemplate> = None;
+        let mut request_match = RequestMatch::Unmatched;
         self.mocks.sort_by_key(|(m, _)| m.specification.priority);
         for (mock, mock_state) in &mut self.mocks {
-            if *mock_state == MountedMockState::OutOfScope {
+            if *mock_state == MountedMockState::OutOfScope || !self.scenarios.allows_mock(&mock.specification) {
                 continue;
             }
-            if mock.matches(&request) {
-                response_template = mock.response_template(&request).ok();
+            if mock.matches(request) {
+                response_template = mock
+                    .response_template(request)
+                    .map_err(|e| log::warn!("failed responding to {} '{}' because {e}", request.method, request.url.path()))
+                    .ok();
+                request_match = RequestMatch::Matched(mock.specification.id.clone());
+                if let Some(scenario) = mock.specification.scenario.as_ref() {
+                    self.scenarios.transition(scenario);
+                }
                 break;
             }
         }
         if let Some(response_template) = response_template {
-            let delay = response_template.delay().map(|d| Delay::new(d.into_owned()));
-            (response_template.generate_response(), delay)
+            let delay = response_template.delay().map(Delay::new);
+            (response_template.generate_response(), delay, request_match)
         } else {
             debug!("Got unexpected request:\n{}", request);
-            (Response::new(StatusCode::NotFound), None)
+            (Response::new(StatusCode::NotFound), None, request_match)
         }
     }
 
     pub(crate) fn register(&mut self, mock: Mock) -> MockId {
+        if let Some(scenario) = mock.scenario.as_ref() {
+            self.scenarios.register(scenario);
+        }
         let n_registered_mocks = self.mocks.len();
         let active_mock = MountedMock::new(mock, n_registered_mocks);
         self.mocks.push((active_mock, MountedMockState::InScope));
@@ -81,6 +109,7 @@ impl MountedMockSet {
     #[allow(dead_code)]
     pub(crate) fn reset(&mut self) {
         self.mocks = vec![];
+        self.scenarios = Scenarios::default();
         self.generation += 1;
     }
 
@@ -92,6 +121,21 @@ impl MountedMockSet {
         *mock = MountedMockState::OutOfScope;
     }
 
+    /// Drops every mock created from the stub with the given id, so that replacing stubs at runtime
+    /// does not grow the set endlessly.
+    ///
+    /// Returns `true` when at least one mock was dropped.
+    pub(crate) fn remove_by_id(&mut self, id: &str) -> bool {
+        let before = self.mocks.len();
+        self.mocks.retain(|(mock, _)| mock.specification.id.as_deref() != Some(id));
+        self.mocks.len() != before
+    }
+
+    /// Every mock serving a sequence of responses starts over from the first one
+    pub(crate) fn reset_sequences(&mut self) {
+        self.mocks.iter_mut().for_each(|(mock, _)| mock.reset_sequence());
+    }
+
     /// Verify that expectations have been met for **all** [`MountedMock`]s in the set.
     pub(crate) fn verify_all(&self) -> VerificationOutcome {
         let failed_verifications: Vec<VerificationReport> = self
@@ -207,4 +251,19 @@ mod tests {
         let second_mock = &set[second_mock_id];
         assert_eq!(second_mock.1, MountedMockState::InScope);
     }
+
+    #[test]
+    fn removing_by_id_only_drops_identified_mocks() {
+        let mut set = MountedMockSet::new();
+        let first_mock = Mock::given(path("/")).respond_with(ResponseTemplate::new(200)).identified("a");
+        let second_mock = Mock::given(path("/hello")).respond_with(ResponseTemplate::new(500));
+        set.register(first_mock);
+        set.register(second_mock);
+
+        assert!(set.remove_by_id("a"));
+        assert!(!set.remove_by_id("a"));
+
+        assert_eq!(set.mocks.len(), 1);
+        assert_eq!(set.mocks[0].0.specification.id, None);
+    }
 }
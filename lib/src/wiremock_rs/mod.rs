@@ -1,19 +1,27 @@
 //! Forking the excellent [wiremock](https://crates.io/crates/wiremock) since this crates will
 //! diverge a lot from it: gRPC support, focus more on standalone mode and using it in Docker
 //! meaning no panic allowed
-mod delay;
+pub mod client_cert;
+pub mod delay;
+pub mod fault;
 #[cfg(feature = "grpc")]
 pub mod grpc;
 pub mod http;
 pub mod matchers;
 mod mock;
-mod mock_server;
-mod mock_set;
+pub(crate) mod mock_server;
+pub(crate) mod mock_set;
 mod mounted_mock;
+pub mod proxy;
 mod request;
 mod respond;
 mod response_template;
+pub mod stream;
+pub mod throttle;
 mod verification;
+pub mod webhook;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 pub use mock::{Match, Mock, MockBuilder, Times};
 pub use mock_server::{MockGuard, MockServer, MockServerBuilder};
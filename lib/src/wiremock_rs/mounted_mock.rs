@@ -6,6 +6,9 @@ use crate::StubrResult;
 pub(crate) struct MountedMock {
     pub(crate) specification: Mock,
     n_matched_requests: u64,
+    /// Matched requests responders answering differently to successive requests start counting from.
+    /// Moved forward instead of resetting `n_matched_requests` which expectations are verified against
+    sequence_start: u64,
     /// The position occupied by this mock within the parent [`MountedMockSet`](crate::mock_set::MountedMockSet)
     /// collection of `MountedMock`s.
     ///
@@ -22,6 +25,7 @@ impl MountedMock {
         Self {
             specification,
             n_matched_requests: 0,
+            sequence_start: 0,
             position_in_set,
             matched_requests: Vec::new(),
         }
@@ -61,8 +65,15 @@ impl MountedMock {
         }
     }
 
+    /// To call right after [`MountedMock::matches`] matched the request
     pub(crate) fn response_template(&self, request: &Request) -> StubrResult<ResponseTemplate> {
-        self.specification.response_template(request)
+        let nth = self.n_matched_requests.saturating_sub(self.sequence_start + 1);
+        self.specification.response_template(request, nth)
+    }
+
+    /// The next matched request gets the first response of a sequence again
+    pub(crate) fn reset_sequence(&mut self) {
+        self.sequence_start = self.n_matched_requests;
     }
 
     #[allow(dead_code)]
@@ -0,0 +1,151 @@
+use hyper::{client::HttpConnector, http, Body, Client};
+
+use crate::wiremock_rs::{mock_server::hyper::DynError, Request};
+
+/// Reaches https upstreams too with the 'tls' feature, only plain http ones otherwise
+#[cfg(feature = "tls")]
+pub(crate) type Connector = hyper_tls::HttpsConnector<HttpConnector>;
+#[cfg(not(feature = "tls"))]
+pub(crate) type Connector = HttpConnector;
+
+/// Shared by the requests of a server so that upstream connections are pooled
+pub(crate) type ProxyClient = Client<Connector>;
+
+/// Forwards a request to an upstream server and responds with its response.
+/// See [https://wiremock.org/docs/proxying/]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Proxy {
+    /// Upstream base url e.g. 'http://localhost:8080'
+    pub base_url: String,
+    /// Headers added to the forwarded request, replacing the ones it might already have
+    pub additional_headers: Vec<(String, String)>,
+    /// Removed from the request path before forwarding it
+    pub url_prefix_to_remove: Option<String>,
+}
+
+impl Proxy {
+    /// Headers which only make sense for a single connection, hence never forwarded
+    const HOP_BY_HOP_HEADERS: [&'static str; 8] = [
+        "connection",
+        "keep-alive",
+        "proxy-authenticate",
+        "proxy-authorization",
+        "te",
+        "trailer",
+        "transfer-encoding",
+        "upgrade",
+    ];
+
+    /// Upstream url of the request
+    pub(crate) fn url(&self, request: &Request) -> String {
+        let path_and_query = request.path_and_query();
+        let path_and_query = self
+            .url_prefix_to_remove
+            .as_deref()
+            .and_then(|prefix| Self::strip_prefix(&path_and_query, prefix))
+            .unwrap_or(&path_and_query);
+        let separator = if path_and_query.starts_with('/') { "" } else { "/" };
+        format!("{}{separator}{path_and_query}", self.base_url.trim_end_matches('/'))
+    }
+
+    /// Only a whole path segment is removed: '/api' is removed from '/api/users' but not from '/apis'
+    fn strip_prefix<'a>(path_and_query: &'a str, prefix: &str) -> Option<&'a str> {
+        let rest = path_and_query.strip_prefix(prefix)?;
+        let at_boundary = prefix.ends_with('/') || rest.is_empty() || rest.starts_with(['/', '?']);
+        at_boundary.then_some(rest)
+    }
+
+    pub(crate) async fn forward(&self, client: &ProxyClient, request: &Request) -> Result<hyper::Response<Body>, DynError> {
+        let mut builder = http::Request::builder().method(request.method.as_ref()).uri(self.url(request));
+        let headers = builder.headers_mut().ok_or("invalid proxied request")?;
+        for (name, values) in &request.headers {
+            let name = name.as_str().to_lowercase();
+            // the upstream host is inferred from its url
+            if name == "host" || Self::HOP_BY_HOP_HEADERS.contains(&name.as_str()) {
+                continue;
+            }
+            let name = http::header::HeaderName::from_bytes(name.as_bytes())?;
+            for value in values {
+                headers.append(&name, http::header::HeaderValue::from_str(value.as_str())?);
+            }
+        }
+        for (name, value) in &self.additional_headers {
+            headers.insert(
+                http::header::HeaderName::from_bytes(name.as_bytes())?,
+                http::header::HeaderValue::from_str(value)?,
+            );
+        }
+        let request = builder.body(Body::from(request.body.clone()))?;
+        let mut response = client.request(request).await?;
+        let headers = response.headers_mut();
+        Self::HOP_BY_HOP_HEADERS.iter().for_each(|h| {
+            headers.remove(*h);
+        });
+        Ok(response)
+    }
+
+    pub(crate) fn client() -> ProxyClient {
+        Client::builder().build(Connector::new())
+    }
+}
+
+#[cfg(test)]
+mod proxy_tests {
+    use std::collections::HashMap;
+
+    use http_types::{Method, Url};
+
+    use super::*;
+
+    fn upstream(base_url: &str) -> Proxy {
+        Proxy {
+            base_url: base_url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn request(path: &str) -> Request {
+        Request {
+            url: Url::parse(&format!("http://localhost:1234{path}")).unwrap(),
+            method: Method::Get,
+            headers: HashMap::new(),
+            body: vec![],
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    #[test]
+    fn should_append_path_and_query_to_base_url() {
+        let proxy = upstream("http://upstream:8080");
+        assert_eq!(proxy.url(&request("/a/b?c=d")), "http://upstream:8080/a/b?c=d");
+        let proxy = upstream("http://upstream:8080/api/");
+        assert_eq!(proxy.url(&request("/a")), "http://upstream:8080/api/a");
+    }
+
+    #[test]
+    fn should_remove_url_prefix() {
+        let proxy = Proxy {
+            url_prefix_to_remove: Some("/other".to_string()),
+            ..upstream("http://upstream:8080")
+        };
+        assert_eq!(proxy.url(&request("/other/a?b=c")), "http://upstream:8080/a?b=c");
+        assert_eq!(proxy.url(&request("/a")), "http://upstream:8080/a");
+        assert_eq!(proxy.url(&request("/other")), "http://upstream:8080/");
+        assert_eq!(proxy.url(&request("/other?b=c")), "http://upstream:8080/?b=c");
+    }
+
+    #[test]
+    fn should_only_remove_url_prefix_ending_a_path_segment() {
+        let proxy = Proxy {
+            url_prefix_to_remove: Some("/api".to_string()),
+            ..upstream("http://upstream:8080")
+        };
+        assert_eq!(proxy.url(&request("/apis/a")), "http://upstream:8080/apis/a");
+        let proxy = Proxy {
+            url_prefix_to_remove: Some("/api/".to_string()),
+            ..upstream("http://upstream:8080")
+        };
+        assert_eq!(proxy.url(&request("/api/a")), "http://upstream:8080/a");
+    }
+}
@@ -4,7 +4,9 @@ use std::{collections::HashMap, fmt};
 use futures::AsyncReadExt;
 use http_types::convert::DeserializeOwned;
 use http_types::headers::{HeaderName, HeaderValue, HeaderValues};
-use http_types::{Method, Url};
+use http_types::{Method, Url, Version};
+
+use super::client_cert::ClientCertificate;
 
 /// An incoming request to an instance of [`MockServer`].
 ///
@@ -30,6 +32,10 @@ pub struct Request {
     pub method: Method,
     pub headers: HashMap<HeaderName, HeaderValues>,
     pub body: Vec<u8>,
+    /// HTTP/1.1 or HTTP/2 mostly, as negotiated with the client
+    pub version: Version,
+    /// Certificate the client presented, when served over mutual TLS
+    pub client_certificate: Option<ClientCertificate>,
 }
 
 impl fmt::Display for Request {
@@ -45,6 +51,14 @@ impl fmt::Display for Request {
 }
 
 impl Request {
+    /// Url path followed by the query, if any
+    pub(crate) fn path_and_query(&self) -> String {
+        match self.url.query() {
+            Some(query) => format!("{}?{}", self.url.path(), query),
+            None => self.url.path().to_string(),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn body_json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
         serde_json::from_slice(&self.body)
@@ -54,6 +68,7 @@ impl Request {
     pub async fn from(mut request: http_types::Request) -> Request {
         let method = request.method();
         let url = request.url().to_owned();
+        let version = request.version().unwrap_or(Version::Http1_1);
 
         let mut headers = HashMap::new();
         for (header_name, header_values) in &request {
@@ -73,12 +88,15 @@ impl Request {
             method,
             headers,
             body,
+            version,
+            client_certificate: None,
         }
     }
 
     pub(crate) async fn from_hyper(request: hyper::Request<hyper::Body>) -> Request {
         let (parts, body) = request.into_parts();
         let method = parts.method.into();
+        let version = parts.version.into();
         let url = match parts.uri.authority() {
             Some(_) => parts.uri.to_string(),
             None => format!("http://localhost{}", parts.uri),
@@ -109,6 +127,8 @@ impl Request {
             method,
             headers,
             body,
+            version,
+            client_certificate: None,
         }
     }
 }
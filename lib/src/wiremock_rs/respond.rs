@@ -130,6 +130,14 @@ pub trait Respond: Send + Sync {
     /// [`MockServer`]: crate::MockServer
     /// [`ResponseTemplate`]: crate::ResponseTemplate
     fn respond(&self, request: &Request) -> StubrResult<ResponseTemplate>;
+
+    /// Same as [`Respond::respond`] for the `nth` (zero indexed) request the [`Mock`] matched.
+    /// Only responders answering differently to successive requests have to care about it.
+    ///
+    /// [`Mock`]: crate::Mock
+    fn respond_nth(&self, request: &Request, _nth: u64) -> StubrResult<ResponseTemplate> {
+        self.respond(request)
+    }
 }
 
 /// A `ResponseTemplate` is the simplest `Respond` implementation: it returns a clone of itself
@@ -1,4 +1,13 @@
-use crate::wiremock_rs::delay::LognormalDelay;
+#[cfg(feature = "websocket")]
+use crate::wiremock_rs::websocket::WebSocketScript;
+use crate::wiremock_rs::{
+    delay::DelayDistribution,
+    fault::Fault,
+    proxy::Proxy,
+    stream::StreamedBody,
+    throttle::Throttle,
+    webhook::{Webhook, Webhooks},
+};
 use http_types::headers::{HeaderName, HeaderValue};
 use http_types::{Response, StatusCode};
 use serde::Serialize;
@@ -20,7 +29,20 @@ pub struct ResponseTemplate {
     pub(crate) headers: HashMap<HeaderName, Vec<HeaderValue>>,
     pub(crate) body: Option<Vec<u8>>,
     pub(crate) delay: Option<Duration>,
-    pub(crate) lognormal_delay: Option<LognormalDelay>,
+    /// Sampled for every response then added to the fixed delay
+    pub(crate) random_delays: Vec<DelayDistribution>,
+    /// Fault along with the probability, from 0 to 1, of injecting it
+    pub(crate) fault: Option<(Fault, f64)>,
+    pub(crate) throttle: Option<Throttle>,
+    /// Body sent piece by piece, superseding the plain body
+    pub(crate) stream: Option<StreamedBody>,
+    /// Responds with the response of an upstream server instead
+    pub(crate) proxy: Option<Proxy>,
+    /// Sent once the response is served
+    pub(crate) webhooks: Vec<Webhook>,
+    /// Upgrades the connection then holds this conversation instead of responding
+    #[cfg(feature = "websocket")]
+    pub(crate) web_socket: Option<WebSocketScript>,
 }
 
 // `wiremock` is a crate meant for testing - failures are most likely not handled/temporary mistakes.
@@ -261,9 +283,46 @@ impl ResponseTemplate {
         self
     }
 
-    /// see [https://wiremock.org/docs/simulating-faults/#lognormal-delay]
-    pub fn set_lognormal_delay(mut self, median: u64, sigma: f64) -> Self {
-        self.lognormal_delay = Some(LognormalDelay { median, sigma });
+    /// see [https://wiremock.org/docs/simulating-faults/#per-stub-random-delays]
+    pub fn add_random_delay(mut self, distribution: DelayDistribution) -> Self {
+        self.random_delays.push(distribution);
+        self
+    }
+
+    /// Breaks the response, at the connection level, with the given probability between 0 and 1
+    pub fn set_fault(mut self, fault: Fault, probability: f64) -> Self {
+        self.fault = Some((fault, probability));
+        self
+    }
+
+    /// Streams the response body slowly, in chunks
+    pub fn set_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Streams the response body piece by piece, each one after its own pause
+    pub fn set_stream(mut self, stream: StreamedBody) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Forwards the request to an upstream server and responds with its response
+    pub fn set_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends a request once the response is served
+    pub fn add_webhook(mut self, webhook: Webhook) -> Self {
+        self.webhooks.push(webhook);
+        self
+    }
+
+    /// Upgrades the connection to a WebSocket then holds the scripted conversation
+    #[cfg(feature = "websocket")]
+    pub fn set_web_socket(mut self, script: WebSocketScript) -> Self {
+        self.web_socket = Some(script);
         self
     }
 
@@ -283,6 +342,30 @@ impl ResponseTemplate {
             if let Some(mime) = &self.mime {
                 response.set_content_type(mime.to_owned());
             }
+            // The server breaks the response when it finds a fault in its extensions
+            if let Some((fault, probability)) = self.fault {
+                if rand::random::<f64>() < probability {
+                    response.ext_mut().insert(fault);
+                }
+            }
+            // Likewise, the server streams the body slowly when it finds a throttle
+            if let Some(throttle) = self.throttle {
+                response.ext_mut().insert(throttle);
+            }
+            if let Some(stream) = self.stream.as_ref() {
+                response.set_body(stream.concat());
+                response.ext_mut().insert(stream.clone());
+            }
+            if let Some(proxy) = self.proxy.as_ref() {
+                response.ext_mut().insert(proxy.clone());
+            }
+            if !self.webhooks.is_empty() {
+                response.ext_mut().insert(Webhooks(self.webhooks.clone()));
+            }
+            #[cfg(feature = "websocket")]
+            if let Some(script) = self.web_socket.as_ref() {
+                response.ext_mut().insert(script.clone());
+            }
             response
         } else {
             panic!("Implementation error")
@@ -290,13 +373,11 @@ impl ResponseTemplate {
     }
 
     /// Retrieve the response delay.
-    pub(crate) fn delay(&self) -> Option<std::borrow::Cow<Duration>> {
-        if let Some(delay) = self.delay.as_ref() {
-            Some(std::borrow::Cow::Borrowed(delay))
-        } else {
-            self.lognormal_delay
-                .as_ref()
-                .map(|lognormal_delay| std::borrow::Cow::Owned(lognormal_delay.new_sample()))
-        }
+    pub(crate) fn delay(&self) -> Option<Duration> {
+        self.random_delays
+            .iter()
+            .map(DelayDistribution::new_sample)
+            .chain(self.delay)
+            .reduce(|a, b| a + b)
     }
 }
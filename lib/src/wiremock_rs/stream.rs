@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use futures::StreamExt as _;
+use hyper::body::Bytes;
+
+/// A response body sent piece by piece with chunked transfer encoding, each piece after its own pause.
+/// Used for server-sent events as well as raw chunks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamedBody(pub Vec<(Bytes, Duration)>);
+
+impl StreamedBody {
+    pub fn push(&mut self, chunk: impl Into<Bytes>, pause: Duration) {
+        self.0.push((chunk.into(), pause));
+    }
+
+    /// Whole body, as received by a client which does not care about the pauses
+    pub(crate) fn concat(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|(chunk, _)| chunk.iter().copied()).collect()
+    }
+
+    pub(crate) fn body(self) -> hyper::Body {
+        let chunks = futures::stream::iter(self.0).then(|(chunk, pause)| async move {
+            tokio::time::sleep(pause).await;
+            Ok::<_, std::convert::Infallible>(chunk)
+        });
+        hyper::Body::wrap_stream(chunks)
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use super::*;
+
+    #[test]
+    fn should_concat_chunks() {
+        let mut stream = StreamedBody::default();
+        stream.push("ab", Duration::ZERO);
+        stream.push("cd", Duration::from_millis(10));
+        assert_eq!(stream.concat(), b"abcd".to_vec());
+        assert!(StreamedBody::default().concat().is_empty());
+    }
+}
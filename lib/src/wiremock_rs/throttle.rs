@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use futures::StreamExt as _;
+use hyper::body::Bytes;
+
+/// Slows down the transfer of a response body, which is then streamed in chunks
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Throttle {
+    /// see [https://wiremock.org/docs/simulating-faults/#chunked-dribble-delay]
+    Dribble { chunks: u32, total: Duration },
+    /// Caps the transfer rate, in bytes per second
+    Bandwidth(u64),
+}
+
+impl Throttle {
+    /// How many times per second a bandwidth throttled body is written
+    const BANDWIDTH_WRITES_PER_SECOND: u64 = 10;
+
+    /// Splits the body in chunks, each one sent after a pause
+    pub(crate) fn chunks(&self, body: Bytes) -> Vec<(Bytes, Duration)> {
+        if body.is_empty() {
+            return vec![];
+        }
+        match *self {
+            Self::Dribble { chunks, total } => Self::dribble(body, chunks, total),
+            Self::Bandwidth(bytes_per_second) => {
+                let bytes_per_second = bytes_per_second.max(1);
+                let chunk_size = (bytes_per_second / Self::BANDWIDTH_WRITES_PER_SECOND).max(1) as usize;
+                let pause = Duration::from_secs(1).mul_f64(chunk_size as f64 / bytes_per_second as f64);
+                (0..body.len())
+                    .step_by(chunk_size)
+                    .map(|start| (body.slice(start..(start + chunk_size).min(body.len())), pause))
+                    .collect()
+            },
+        }
+    }
+
+    /// Exactly `chunks` chunks (unless the body is shorter), the first ones taking a byte more when the body
+    /// does not split evenly, so that the pauses add up to `total`
+    fn dribble(body: Bytes, chunks: u32, total: Duration) -> Vec<(Bytes, Duration)> {
+        let chunks = chunks.clamp(1, body.len() as u32);
+        let pause = total / chunks;
+        let (size, remainder) = (body.len() / chunks as usize, body.len() % chunks as usize);
+        (0..chunks as usize)
+            .scan(0, |start, i| {
+                let end = *start + size + usize::from(i < remainder);
+                let chunk = body.slice(*start..end);
+                *start = end;
+                Some((chunk, pause))
+            })
+            .collect()
+    }
+
+    pub(crate) fn body(&self, body: Bytes) -> hyper::Body {
+        let chunks = futures::stream::iter(self.chunks(body)).then(|(chunk, pause)| async move {
+            tokio::time::sleep(pause).await;
+            Ok::<_, std::convert::Infallible>(chunk)
+        });
+        hyper::Body::wrap_stream(chunks)
+    }
+}
+
+#[cfg(test)]
+mod throttle_tests {
+    use super::*;
+
+    fn sizes(throttle: Throttle, body: &'static [u8]) -> Vec<usize> {
+        throttle.chunks(Bytes::from_static(body)).iter().map(|(c, _)| c.len()).collect()
+    }
+
+    #[test]
+    fn dribble_should_split_body_in_chunks() {
+        let throttle = Throttle::Dribble {
+            chunks: 3,
+            total: Duration::from_millis(300),
+        };
+        assert_eq!(sizes(throttle, b"abcdefgh"), vec![3, 3, 2]);
+        let pauses = throttle.chunks(Bytes::from_static(b"abcdefgh"));
+        assert!(pauses.iter().all(|(_, p)| *p == Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn dribble_should_spread_remaining_bytes_over_the_requested_chunks() {
+        let throttle = Throttle::Dribble {
+            chunks: 10,
+            total: Duration::from_millis(1000),
+        };
+        let chunks = throttle.chunks(Bytes::from_static(b"abcdefghijk"));
+        assert_eq!(
+            chunks.iter().map(|(c, _)| c.len()).collect::<Vec<_>>(),
+            vec![2, 1, 1, 1, 1, 1, 1, 1, 1, 1]
+        );
+        assert_eq!(chunks.iter().map(|(_, p)| *p).sum::<Duration>(), Duration::from_millis(1000));
+        assert_eq!(chunks.iter().flat_map(|(c, _)| c.to_vec()).collect::<Vec<_>>(), b"abcdefghijk");
+    }
+
+    #[test]
+    fn dribble_should_not_have_more_chunks_than_bytes() {
+        let throttle = Throttle::Dribble {
+            chunks: 10,
+            total: Duration::from_millis(300),
+        };
+        assert_eq!(sizes(throttle, b"ab"), vec![1, 1]);
+        assert!(sizes(throttle, b"").is_empty());
+    }
+
+    #[test]
+    fn bandwidth_should_write_ten_times_per_second() {
+        let chunks = Throttle::Bandwidth(40).chunks(Bytes::from_static(b"abcdefghij"));
+        assert_eq!(chunks.iter().map(|(c, _)| c.len()).collect::<Vec<_>>(), vec![4, 4, 2]);
+        assert!(chunks.iter().all(|(_, p)| *p == Duration::from_millis(100)));
+    }
+}
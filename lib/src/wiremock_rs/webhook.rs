@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use hyper::{http, Body, Client};
+
+use crate::wiremock_rs::{mock_server::hyper::DynError, proxy::Connector};
+
+/// An outbound request the server sends once it has responded, to test callback flows.
+/// See [https://wiremock.org/docs/webhooks-and-callbacks/]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Webhook {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Waited for before sending the request
+    pub delay: Option<Duration>,
+}
+
+/// Webhooks of a response, which the server finds in its extensions
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Webhooks(pub(crate) Vec<Webhook>);
+
+impl Webhooks {
+    /// Sends every webhook in the background, on the server's runtime
+    pub(crate) fn dispatch(self) {
+        for webhook in self.0 {
+            tokio::task::spawn_local(async move {
+                if let Some(delay) = webhook.delay {
+                    tokio::time::sleep(delay).await;
+                }
+                match webhook.send().await {
+                    Ok(status) if status.is_success() => log::debug!("webhook {} '{}' responded {status}", webhook.method, webhook.url),
+                    Ok(status) => log::warn!("webhook {} '{}' responded {status}", webhook.method, webhook.url),
+                    Err(e) => log::warn!("webhook {} '{}' failed because {e}", webhook.method, webhook.url),
+                }
+            });
+        }
+    }
+}
+
+impl Webhook {
+    pub(crate) async fn send(&self) -> Result<http::StatusCode, DynError> {
+        let mut builder = http::Request::builder()
+            .method(http::Method::from_bytes(self.method.as_bytes())?)
+            .uri(&self.url);
+        let headers = builder.headers_mut().ok_or("invalid webhook request")?;
+        for (name, value) in &self.headers {
+            headers.append(
+                http::header::HeaderName::from_bytes(name.as_bytes())?,
+                http::header::HeaderValue::from_str(value)?,
+            );
+        }
+        let body = self.body.clone().map(Body::from).unwrap_or_default();
+        let client = Client::builder().build::<_, Body>(Connector::new());
+        let response = client.request(builder.body(body)?).await?;
+        Ok(response.status())
+    }
+}
@@ -0,0 +1,204 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use futures::{SinkExt as _, StreamExt as _};
+use http_types::headers::HeaderName;
+use hyper::{http, upgrade::OnUpgrade};
+use tokio_tungstenite::{
+    tungstenite::{
+        handshake::derive_accept_key,
+        protocol::{frame::coding::CloseCode, CloseFrame, Role},
+        Message,
+    },
+    WebSocketStream,
+};
+
+use super::{mock::Matcher, Match, MockBuilder, Request};
+
+/// A frame the server sends during a WebSocket conversation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketFrame {
+    Text(String),
+    Binary(Vec<u8>),
+    /// Closes the conversation
+    Close {
+        code: u16,
+        reason: String,
+    },
+}
+
+/// A frame sent after a pause
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketStep {
+    pub frame: WebSocketFrame,
+    pub delay: Duration,
+}
+
+/// Frames sent in reply to an incoming message, when all the matchers match it
+pub struct WebSocketReply {
+    matchers: Vec<Matcher>,
+    steps: Vec<WebSocketStep>,
+}
+
+impl WebSocketReply {
+    /// An incoming message is matched as the body of the upgrade request
+    pub fn new(matchers: MockBuilder, steps: Vec<WebSocketStep>) -> Self {
+        Self {
+            matchers: matchers.matchers,
+            steps,
+        }
+    }
+
+    fn matches(&self, message: &Request) -> bool {
+        self.matchers.iter().all(|m| m.matches(message))
+    }
+}
+
+/// Conversation the server holds once it has upgraded a connection, which it finds in the response extensions
+#[derive(Clone, Default)]
+pub struct WebSocketScript {
+    on_connect: Arc<Vec<WebSocketStep>>,
+    on_message: Arc<Vec<WebSocketReply>>,
+}
+
+impl std::fmt::Debug for WebSocketScript {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketScript")
+            .field("on_connect", &self.on_connect)
+            .field("on_message", &self.on_message.len())
+            .finish()
+    }
+}
+
+impl WebSocketScript {
+    pub fn new(on_connect: Vec<WebSocketStep>, on_message: Vec<WebSocketReply>) -> Self {
+        Self {
+            on_connect: Arc::new(on_connect),
+            on_message: Arc::new(on_message),
+        }
+    }
+
+    /// Completes the handshake, then holds the conversation in the background once hyper has upgraded the connection
+    pub(crate) fn upgrade(self, request: Request, on_upgrade: OnUpgrade) -> hyper::Response<hyper::Body> {
+        let header = |name: &str| {
+            let name = HeaderName::from_str(name).ok()?;
+            request.headers.get(&name).map(|v| v.as_str().to_string())
+        };
+        let is_upgrade = header("upgrade")
+            .map(|u| u.eq_ignore_ascii_case("websocket"))
+            .unwrap_or_default();
+        let Some(key) = header("sec-websocket-key").filter(|_| is_upgrade) else {
+            return hyper::Response::builder()
+                .status(http::StatusCode::UPGRADE_REQUIRED)
+                .header(http::header::UPGRADE, "websocket")
+                .body(hyper::Body::from("Expected a WebSocket upgrade request"))
+                .unwrap_or_default();
+        };
+        tokio::task::spawn_local(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    let socket = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+                    self.converse(socket, request).await
+                },
+                Err(e) => log::warn!("failed upgrading connection to a WebSocket because {e}"),
+            }
+        });
+        hyper::Response::builder()
+            .status(http::StatusCode::SWITCHING_PROTOCOLS)
+            .header(http::header::UPGRADE, "websocket")
+            .header(http::header::CONNECTION, "upgrade")
+            .header(http::header::SEC_WEBSOCKET_ACCEPT, derive_accept_key(key.as_bytes()))
+            .body(hyper::Body::empty())
+            .unwrap_or_default()
+    }
+
+    async fn converse<S>(&self, mut socket: WebSocketStream<S>, request: Request)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        if !Self::play(&mut socket, &self.on_connect).await {
+            return;
+        }
+        while let Some(Ok(message)) = socket.next().await {
+            let body = match message {
+                Message::Text(text) => text.into_bytes(),
+                Message::Binary(bytes) => bytes,
+                Message::Close(_) => break,
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+            };
+            let message = Request { body, ..request.clone() };
+            match self.on_message.iter().find(|reply| reply.matches(&message)) {
+                Some(reply) if !Self::play(&mut socket, &reply.steps).await => return,
+                Some(_) => {},
+                None => log::debug!(
+                    "no reply matches WebSocket message '{}'",
+                    String::from_utf8_lossy(&message.body)
+                ),
+            }
+        }
+    }
+
+    /// Whether the conversation goes on
+    async fn play<S>(socket: &mut WebSocketStream<S>, steps: &[WebSocketStep]) -> bool
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        for step in steps {
+            tokio::time::sleep(step.delay).await;
+            let message = match &step.frame {
+                WebSocketFrame::Text(text) => Message::Text(text.to_string()),
+                WebSocketFrame::Binary(bytes) => Message::Binary(bytes.clone()),
+                WebSocketFrame::Close { code, reason } => {
+                    let frame = CloseFrame {
+                        code: CloseCode::from(*code),
+                        reason: reason.to_string().into(),
+                    };
+                    let _ = socket.close(Some(frame)).await;
+                    return false;
+                },
+            };
+            if let Err(e) = socket.send(message).await {
+                log::debug!("WebSocket conversation ended because {e}");
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod websocket_tests {
+    use super::*;
+
+    struct BodyMatcher(&'static str);
+
+    impl Match for BodyMatcher {
+        fn matches(&self, req: &Request) -> bool {
+            req.body == self.0.as_bytes()
+        }
+    }
+
+    fn message(body: &str) -> Request {
+        Request {
+            url: "http://localhost/".parse().unwrap(),
+            method: http_types::Method::Get,
+            headers: Default::default(),
+            body: body.as_bytes().to_vec(),
+            version: http_types::Version::Http1_1,
+            client_certificate: None,
+        }
+    }
+
+    #[test]
+    fn reply_should_match_when_all_matchers_do() {
+        let reply = WebSocketReply::new(super::super::Mock::given(BodyMatcher("ping")), vec![]);
+        assert!(reply.matches(&message("ping")));
+        assert!(!reply.matches(&message("pong")));
+    }
+
+    #[test]
+    fn should_require_upgrade_headers() {
+        let on_upgrade = hyper::upgrade::on(hyper::Request::new(hyper::Body::empty()));
+        let resp = WebSocketScript::default().upgrade(message(""), on_upgrade);
+        assert_eq!(resp.status(), http::StatusCode::UPGRADE_REQUIRED);
+    }
+}
@@ -0,0 +1,177 @@
+use asserhttp::*;
+use serde_json::{json, Value};
+use surf::{delete, get, post, put};
+
+use crate::utils::*;
+
+const PING_ID: &str = "4b8b9b9e-0d46-4a2a-9b1e-0f0bd1a6c1a1";
+
+fn pong() -> Value {
+    json!({"request": {"method": "GET", "urlPath": "/pong"}, "response": {"status": 201}})
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_list_mappings_loaded_from_files() {
+    get(stubr.path("/__admin/mappings"))
+        .await
+        .expect_status_ok()
+        .expect_content_type_json()
+        .expect_body_json(|b: Value| {
+            assert_eq!(b["meta"]["total"], json!(1));
+            assert_eq!(b["mappings"][0]["id"], json!(PING_ID));
+            assert_eq!(b["mappings"][0]["request"]["urlPath"], json!("/ping"));
+        });
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_get_a_single_mapping() {
+    get(stubr.path(&format!("/__admin/mappings/{PING_ID}")))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| assert_eq!(b["uuid"], json!(PING_ID)));
+    get(stubr.path("/__admin/mappings/unknown")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_create_a_mapping() {
+    get(stubr.path("/pong")).await.expect_status_not_found();
+    let mut created = post(stubr.path("/__admin/mappings")).body(pong()).await.unwrap();
+    created.expect_status_created();
+    let created: Value = created.body_json().await.unwrap();
+    let id = created["id"].as_str().unwrap();
+    get(stubr.path("/pong")).await.expect_status(201);
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path(&format!("/__admin/mappings/{id}"))).await.expect_status_ok();
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_create_a_mapping_with_its_own_id() {
+    let stub = json!({"id": "abcd", "request": {"method": "GET", "urlPath": "/pong"}, "response": {"status": 201}});
+    post(stubr.path("/__admin/mappings"))
+        .body(stub)
+        .await
+        .expect_status_created()
+        .expect_body_json(|b: Value| assert_eq!(b["id"], json!("abcd")));
+    get(stubr.path("/__admin/mappings/abcd")).await.expect_status_ok();
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_fail_creating_a_mapping_with_an_existing_id() {
+    let stub = json!({"id": PING_ID, "request": {"method": "GET", "urlPath": "/pong"}, "response": {"status": 201}});
+    post(stubr.path("/__admin/mappings")).body(stub).await.expect_status(422);
+    get(stubr.path("/pong")).await.expect_status_not_found();
+    delete(stubr.path(&format!("/__admin/mappings/{PING_ID}")))
+        .await
+        .expect_status_ok();
+    get(stubr.path("/ping")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_fail_creating_an_invalid_mapping() {
+    post(stubr.path("/__admin/mappings"))
+        .body(json!({"request": {"method": 42}}))
+        .await
+        .expect_status(422)
+        .expect_body_json(|b: Value| assert!(b["errors"][0]["title"].is_string()));
+    post(stubr.path("/__admin/mappings")).body("not json").await.expect_status(422);
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_update_a_mapping() {
+    let stub = json!({"request": {"method": "GET", "urlPath": "/ping"}, "response": {"status": 202}});
+    put(stubr.path(&format!("/__admin/mappings/{PING_ID}")))
+        .body(stub)
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| assert_eq!(b["id"], json!(PING_ID)));
+    get(stubr.path("/ping")).await.expect_status(202);
+    put(stubr.path("/__admin/mappings/unknown"))
+        .body(pong())
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_delete_a_mapping() {
+    delete(stubr.path(&format!("/__admin/mappings/{PING_ID}")))
+        .await
+        .expect_status_ok();
+    get(stubr.path("/ping")).await.expect_status_not_found();
+    delete(stubr.path(&format!("/__admin/mappings/{PING_ID}")))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_delete_all_mappings() {
+    post(stubr.path("/__admin/mappings")).body(pong()).await.expect_status_created();
+    delete(stubr.path("/__admin/mappings")).await.expect_status_ok();
+    get(stubr.path("/ping")).await.expect_status_not_found();
+    get(stubr.path("/pong")).await.expect_status_not_found();
+    get(stubr.path("/__admin/mappings"))
+        .await
+        .expect_body_json(|b: Value| assert_eq!(b["meta"]["total"], json!(0)));
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn reset_should_restore_mappings_from_files() {
+    delete(stubr.path(&format!("/__admin/mappings/{PING_ID}")))
+        .await
+        .expect_status_ok();
+    post(stubr.path("/__admin/mappings")).body(pong()).await.expect_status_created();
+    post(stubr.path("/__admin/reset")).await.expect_status_ok();
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path("/pong")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_save_mappings_created_at_runtime() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::copy("tests/stubs/admin/ping.json", dir.path().join("ping.json")).unwrap();
+    let stubr = Stubr::start(dir.path().to_path_buf()).await;
+    let stub = json!({"id": "abcd", "request": {"method": "GET", "urlPath": "/pong"}, "response": {"status": 201}});
+    post(stubr.path("/__admin/mappings")).body(stub).await.expect_status_created();
+    post(stubr.path("/__admin/mappings/save")).await.expect_status_ok();
+    let saved = std::fs::read_to_string(dir.path().join("abcd.json")).unwrap();
+    let saved: Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(saved["request"]["urlPath"], json!("/pong"));
+
+    // saved mappings survive a reset
+    post(stubr.path("/__admin/reset")).await.expect_status_ok();
+    get(stubr.path("/pong")).await.expect_status(201);
+}
+
+#[async_std::test]
+async fn should_reject_mapping_ids_escaping_the_stub_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let stubs = dir.path().join("stubs");
+    std::fs::create_dir(&stubs).unwrap();
+    std::fs::copy("tests/stubs/admin/ping.json", stubs.join("ping.json")).unwrap();
+    let stubr = Stubr::start(stubs.clone()).await;
+    let outside = dir.path().join("escape");
+    for id in ["../escape".to_string(), outside.display().to_string()] {
+        let stub = json!({"id": id, "request": {"method": "GET", "urlPath": "/pong"}, "response": {"status": 201}});
+        post(stubr.path("/__admin/mappings")).body(stub).await.expect_status(422);
+    }
+    post(stubr.path("/__admin/mappings/save")).await.expect_status_ok();
+    assert!(!dir.path().join("escape.json").exists());
+    let files = std::fs::read_dir(dir.path()).unwrap().count();
+    assert_eq!(files, 1);
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_not_shadow_user_stubs_outside_admin_path() {
+    get(stubr.path("/__administrator")).await.expect_status_not_found();
+    get(stubr.path("/__admin/unknown")).await.expect_status_not_found();
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use asserhttp::*;
+use surf::get;
+
+use stubr::Config;
+
+use crate::utils::*;
+
+#[async_std::test]
+async fn should_throttle_body_to_bandwidth() {
+    let cfg = Config {
+        bandwidth: Some(24),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with("tests/stubs/resp/delay/hello-world.json", cfg).await;
+    let begin = std::time::Instant::now();
+    get(srv.uri()).await.expect_status_ok().expect_body_text_eq("Hello world!");
+    // 12 bytes at 24 bytes per second
+    assert!(begin.elapsed() >= Duration::from_millis(450));
+}
+
+#[async_std::test]
+async fn should_not_throttle_without_bandwidth() {
+    let srv = Stubr::start("tests/stubs/resp/delay/hello-world.json").await;
+    let begin = std::time::Instant::now();
+    get(srv.uri()).await.expect_status_ok().expect_body_text_eq("Hello world!");
+    assert!(begin.elapsed() < Duration::from_millis(200));
+}
+
+#[async_std::test]
+async fn stub_dribble_delay_should_have_precedence_over_bandwidth() {
+    let cfg = Config {
+        bandwidth: Some(1),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with("tests/stubs/resp/delay/dribble-4-chunks-400ms.json", cfg).await;
+    let begin = std::time::Instant::now();
+    get(srv.uri()).await.expect_status_ok().expect_body_text_eq("Hello world!");
+    assert!(begin.elapsed() < Duration::from_secs(2));
+}
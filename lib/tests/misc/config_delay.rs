@@ -4,7 +4,7 @@ use asserhttp::*;
 use async_std::{io, task};
 use surf::get;
 
-use stubr::Config;
+use stubr::{Config, DelayDistribution};
 
 use crate::utils::*;
 
@@ -112,3 +112,28 @@ async fn should_add_latency_to_locally_defined_delay_and_not_timeout() {
     }));
     assert!(timeout.is_ok())
 }
+
+#[async_std::test]
+async fn should_add_latency_distribution_to_locally_defined_delay() {
+    let cfg = Config {
+        latency_distribution: Some(DelayDistribution::Uniform { lower: 100, upper: 200 }),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with("tests/stubs/resp/delay/uniform-100-200.json", cfg).await;
+    let begin = std::time::Instant::now();
+    get(srv.uri()).await.expect_status_ok();
+    assert!(begin.elapsed() >= Duration::from_millis(200));
+}
+
+#[async_std::test]
+async fn should_add_latency_to_latency_distribution() {
+    let cfg = Config {
+        latency: Some(300),
+        latency_distribution: Some(DelayDistribution::Exponential { mean: 10 }),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with("tests/stubs/ping.json", cfg).await;
+    let begin = std::time::Instant::now();
+    get(srv.uri()).await.expect_status_ok();
+    assert!(begin.elapsed() >= Duration::from_millis(300));
+}
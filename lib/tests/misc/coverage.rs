@@ -0,0 +1,18 @@
+use std::path::Path;
+
+use asserhttp::*;
+use surf::get;
+
+const HIT: &str = "tests/stubs/coverage/hit.json";
+const DEAD: &str = "tests/stubs/coverage/dead.json";
+
+#[async_std::test]
+#[stubr::mock("coverage")]
+async fn should_count_hits_per_stub_file() {
+    get(stubr.path("/coverage/hit")).await.expect_status_ok();
+    get(stubr.path("/coverage/hit")).await.expect_status_ok();
+    get(stubr.path("/coverage/none")).await.expect_status_not_found();
+    let hits = stubr.stub_hits();
+    assert_eq!(hits.get(Path::new(HIT)), Some(&2));
+    assert_eq!(hits.get(Path::new(DEAD)), Some(&0));
+}
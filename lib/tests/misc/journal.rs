@@ -0,0 +1,177 @@
+use asserhttp::*;
+use serde_json::{json, Value};
+use surf::{delete, get, post};
+
+use stubr::Config;
+
+use crate::utils::*;
+
+const PING_ID: &str = "4b8b9b9e-0d46-4a2a-9b1e-0f0bd1a6c1a1";
+const PING: &str = "tests/stubs/admin/ping.json";
+
+fn journal(capacity: usize) -> Config {
+    Config {
+        request_journal: Some(capacity),
+        ..Default::default()
+    }
+}
+
+#[async_std::test]
+async fn should_not_record_requests_by_default() {
+    let stubr = Stubr::start(PING).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    assert!(stubr.received_requests().is_empty());
+    get(stubr.path("/__admin/requests"))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| {
+            assert_eq!(b["requestJournalDisabled"], json!(true));
+            assert_eq!(b["requests"], json!([]));
+        });
+}
+
+#[async_std::test]
+async fn should_record_matched_and_unmatched_requests() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path("/unknown?a=b")).await.expect_status_not_found();
+    let requests = stubr.received_requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].url, "/ping");
+    assert!(requests[0].was_matched);
+    assert_eq!(requests[0].stub_id.as_deref(), Some(PING_ID));
+    assert_eq!(requests[1].url, "/unknown?a=b");
+    assert!(!requests[1].was_matched);
+    assert!(requests[1].stub_id.is_none());
+}
+
+#[async_std::test]
+async fn should_not_record_admin_requests() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/__admin/mappings")).await.expect_status_ok();
+    assert!(stubr.received_requests().is_empty());
+}
+
+#[async_std::test]
+async fn should_evict_oldest_requests() {
+    let stubr = Stubr::start_with(PING, journal(2)).await;
+    for path in ["/a", "/b", "/c"] {
+        get(stubr.path(path)).await.expect_status_not_found();
+    }
+    let urls = stubr.received_requests().into_iter().map(|r| r.url).collect::<Vec<_>>();
+    assert_eq!(urls, vec!["/b", "/c"]);
+}
+
+#[async_std::test]
+async fn should_list_requests_most_recent_first() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    post(stubr.path("/unknown")).body("hello").await.expect_status_not_found();
+    get(stubr.path("/__admin/requests"))
+        .await
+        .expect_status_ok()
+        .expect_content_type_json()
+        .expect_body_json(|b: Value| {
+            assert_eq!(b["requestJournalDisabled"], json!(false));
+            assert_eq!(b["meta"]["total"], json!(2));
+            assert_eq!(b["requests"][0]["request"]["url"], json!("/unknown"));
+            assert_eq!(b["requests"][0]["request"]["method"], json!("POST"));
+            assert_eq!(b["requests"][0]["request"]["body"], json!("hello"));
+            assert_eq!(b["requests"][0]["wasMatched"], json!(false));
+            assert_eq!(b["requests"][1]["request"]["url"], json!("/ping"));
+            assert_eq!(b["requests"][1]["wasMatched"], json!(true));
+            assert_eq!(b["requests"][1]["stubMapping"]["id"], json!(PING_ID));
+        });
+}
+
+#[async_std::test]
+async fn should_get_and_delete_a_single_request() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    let id = stubr.received_requests()[0].id.clone();
+    get(stubr.path(&format!("/__admin/requests/{id}")))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| assert_eq!(b["request"]["url"], json!("/ping")));
+    delete(stubr.path(&format!("/__admin/requests/{id}"))).await.expect_status_ok();
+    get(stubr.path(&format!("/__admin/requests/{id}")))
+        .await
+        .expect_status_not_found();
+    delete(stubr.path(&format!("/__admin/requests/{id}")))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_list_unmatched_requests() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path("/unknown")).await.expect_status_not_found();
+    get(stubr.path("/__admin/requests/unmatched"))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| {
+            assert_eq!(b["requests"].as_array().unwrap().len(), 1);
+            assert_eq!(b["requests"][0]["request"]["url"], json!("/unknown"));
+        });
+}
+
+#[async_std::test]
+async fn should_count_and_find_requests_matching_a_pattern() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path("/ping")).await.expect_status_ok();
+    post(stubr.path("/ping")).await.expect_status_not_found();
+    post(stubr.path("/__admin/requests/count"))
+        .body(json!({"method": "GET", "url": "/ping"}))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| assert_eq!(b, json!({"count": 2})));
+    post(stubr.path("/__admin/requests/count"))
+        .body(json!({"urlPath": "/ping"}))
+        .await
+        .expect_body_json(|b: Value| assert_eq!(b, json!({"count": 3})));
+    post(stubr.path("/__admin/requests/find"))
+        .body(json!({"method": "POST"}))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| {
+            assert_eq!(b["requests"].as_array().unwrap().len(), 1);
+            assert_eq!(b["requests"][0]["request"]["method"], json!("POST"));
+        });
+}
+
+#[async_std::test]
+async fn should_fail_counting_with_invalid_pattern() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    post(stubr.path("/__admin/requests/count"))
+        .body(json!({"method": 42}))
+        .await
+        .expect_status(422);
+}
+
+#[async_std::test]
+async fn should_remove_requests_matching_a_pattern() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path("/unknown")).await.expect_status_not_found();
+    post(stubr.path("/__admin/requests/remove"))
+        .body(json!({"url": "/ping"}))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| assert_eq!(b["requests"][0]["request"]["url"], json!("/ping")));
+    let urls = stubr.received_requests().into_iter().map(|r| r.url).collect::<Vec<_>>();
+    assert_eq!(urls, vec!["/unknown"]);
+}
+
+#[async_std::test]
+async fn should_clear_journal() {
+    let stubr = Stubr::start_with(PING, journal(10)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+    delete(stubr.path("/__admin/requests")).await.expect_status_ok();
+    assert!(stubr.received_requests().is_empty());
+    get(stubr.path("/ping")).await.expect_status_ok();
+    post(stubr.path("/__admin/reset")).await.expect_status_ok();
+    assert!(stubr.received_requests().is_empty());
+}
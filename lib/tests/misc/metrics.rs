@@ -0,0 +1,77 @@
+use asserhttp::*;
+use surf::get;
+
+async fn metrics(stubr: &stubr::Stubr) -> String {
+    let mut resp = get(stubr.path("/__admin/metrics")).await.unwrap();
+    resp.expect_status_ok()
+        .expect_header("content-type", "text/plain; version=0.0.4");
+    resp.body_string().await.unwrap()
+}
+
+#[async_std::test]
+#[stubr::mock("metrics")]
+async fn should_count_requests_by_stub_and_status() {
+    get(stubr.path("/slow")).await.expect_status_ok();
+    get(stubr.path("/broken")).await.expect_status(503);
+    get(stubr.path("/broken")).await.expect_status(503);
+    let metrics = metrics(&stubr).await;
+    assert!(metrics.contains("stubr_requests_total{stub=\"slow ping\",status=\"200\"} 1\n"));
+    assert!(metrics.contains("stubr_requests_total{stub=\"broken\",status=\"503\"} 2\n"));
+}
+
+#[async_std::test]
+#[stubr::mock("metrics")]
+async fn should_count_unmatched_requests() {
+    get(stubr.path("/none")).await.expect_status_not_found();
+    let metrics = metrics(&stubr).await;
+    assert!(metrics.contains("stubr_unmatched_requests_total 1\n"));
+    assert!(!metrics.contains("status=\"404\""));
+}
+
+#[async_std::test]
+#[stubr::mock("metrics")]
+async fn should_include_injected_delays_in_latencies() {
+    get(stubr.path("/slow")).await.expect_status_ok();
+    let metrics = metrics(&stubr).await;
+    assert!(metrics.contains("stubr_request_duration_seconds_bucket{stub=\"slow ping\",le=\"0.025\"} 0\n"));
+    assert!(metrics.contains("stubr_request_duration_seconds_bucket{stub=\"slow ping\",le=\"+Inf\"} 1\n"));
+    assert!(metrics.contains("stubr_request_duration_seconds_count{stub=\"slow ping\"} 1\n"));
+}
+
+#[async_std::test]
+#[stubr::mock("metrics")]
+async fn should_expose_mounted_stubs() {
+    assert!(metrics(&stubr).await.contains("stubr_mounted_stubs 2\n"));
+}
+
+#[async_std::test]
+#[stubr::mock("metrics")]
+async fn should_not_count_admin_requests() {
+    metrics(&stubr).await;
+    let metrics = metrics(&stubr).await;
+    assert!(metrics.contains("stubr_unmatched_requests_total 0\n"));
+    assert!(!metrics.contains("stubr_requests_total{"));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/proxy/unreachable.json")]
+async fn should_count_the_status_sent_rather_than_the_stub_one() {
+    get(stubr.path("/any")).await.expect_status(502);
+    let metrics = metrics(&stubr).await;
+    assert!(metrics.contains("stubr_requests_total{stub=\"unreachable\",status=\"502\"} 1\n"));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/delay/dribble-4-chunks-400ms.json")]
+async fn should_include_body_delivery_in_latencies() {
+    get(stubr.path("/any"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("Hello world!");
+    let metrics = metrics(&stubr).await;
+    let stub = "dribble-4-chunks-400ms";
+    assert!(metrics.contains(&format!(
+        "stubr_request_duration_seconds_bucket{{stub=\"{stub}\",le=\"0.25\"}} 0\n"
+    )));
+    assert!(metrics.contains(&format!("stubr_request_duration_seconds_count{{stub=\"{stub}\"}} 1\n")));
+}
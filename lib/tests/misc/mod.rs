@@ -1,10 +1,25 @@
+pub mod admin;
 pub mod auth;
 pub mod config;
+pub mod config_bandwidth;
 pub mod config_delay;
 pub mod config_verify;
+pub mod coverage;
+pub mod journal;
 pub mod jwt;
+pub mod metrics;
+pub mod near_miss;
 pub mod opentracing;
 pub mod probes;
 pub mod pub_api;
+pub mod record_on_miss;
+pub mod reload;
+pub mod resource;
+pub mod scenario;
+pub mod strict;
+pub mod tls;
 pub mod tokio;
+pub mod verification;
+pub mod webhook;
+pub mod websocket;
 pub mod wiremock_iso;
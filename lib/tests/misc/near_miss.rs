@@ -0,0 +1,64 @@
+use asserhttp::*;
+use serde_json::{json, Value};
+use surf::{delete, get, post};
+
+use stubr::RequestPart;
+
+const PING_ID: &str = "4b8b9b9e-0d46-4a2a-9b1e-0f0bd1a6c1a1";
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_explain_unmatched_request_in_response_body() {
+    let mut resp = post(stubr.path("/ping")).await.unwrap();
+    resp.expect_status_not_found();
+    let body = resp.body_string().await.unwrap();
+    assert!(body.starts_with("No stub matched request 'POST /ping'. Closest stubs:"));
+    assert!(body.contains(&format!("stub '{PING_ID}' at distance 0.50")));
+    assert!(body.contains("!= method 'method': expected \"GET\" got 'POST'"));
+    assert!(body.contains("== url 'urlPath': expected \"/ping\" got '/ping'"));
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_not_alter_matched_responses() {
+    get(stubr.path("/ping")).await.expect_status_ok().expect_body_absent();
+    assert!(stubr.near_misses().is_empty());
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_keep_near_misses() {
+    post(stubr.path("/ping")).await.expect_status_not_found();
+    get(stubr.path("/pong?a=b")).await.expect_status_not_found();
+    let near_misses = stubr.near_misses();
+    assert_eq!(near_misses.len(), 2);
+    assert_eq!(near_misses[0].method, "POST");
+    assert_eq!(near_misses[1].url, "/pong?a=b");
+    let closest = &near_misses[1].near_misses[0];
+    assert_eq!(closest.stub_id, PING_ID);
+    let url = closest.diffs.iter().find(|d| d.part == RequestPart::Url).unwrap();
+    assert!(!url.matched);
+    assert_eq!(url.expected, json!("/ping"));
+    assert_eq!(url.actual.as_deref(), Some("/pong"));
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_expose_near_misses_in_admin_api() {
+    post(stubr.path("/ping")).await.expect_status_not_found();
+    get(stubr.path("/__admin/requests/unmatched/near-misses"))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| {
+            let report = &b["nearMisses"][0];
+            assert_eq!(report["request"], json!({"method": "POST", "url": "/ping"}));
+            assert_eq!(report["nearMisses"][0]["stubId"], json!(PING_ID));
+            assert_eq!(report["nearMisses"][0]["distance"], json!(0.5));
+            assert_eq!(
+                report["nearMisses"][0]["diffs"][0],
+                json!({"part": "method", "name": "method", "expected": "GET", "actual": "POST", "matched": false})
+            );
+        });
+    delete(stubr.path("/__admin/requests")).await.expect_status_ok();
+    assert!(stubr.near_misses().is_empty());
+}
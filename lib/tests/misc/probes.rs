@@ -1,8 +1,17 @@
 use asserhttp::*;
-use surf::get;
+use serde_json::json;
+use surf::{get, post};
 
 #[async_std::test]
 #[stubr::mock]
 async fn should_publish_probes_when_started() {
     get(stubr.path("/healtz")).await.expect_status_ok();
 }
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn stubs_with_lowest_priority_should_take_precedence_over_probes() {
+    let stub = json!({"priority": 255, "request": {"method": "GET", "urlPath": "/healtz"}, "response": {"status": 503}});
+    post(stubr.path("/__admin/mappings")).body(stub).await.expect_status_created();
+    get(stubr.path("/healtz")).await.expect_status(503);
+}
@@ -47,3 +47,9 @@ async fn should_start_even_without_stubs() {
     let srv = Stubr::start("").await;
     get(&srv.path("/healtz")).await.expect_status_ok();
 }
+
+#[test]
+fn shipped_stub_schema_should_be_the_maintained_one() {
+    let maintained = std::fs::read_to_string("../schemas/stubr.schema.json").unwrap();
+    assert_eq!(stubr::STUB_SCHEMA, maintained);
+}
@@ -0,0 +1,87 @@
+use std::{fs::copy, path::Path};
+
+use asserhttp::*;
+use serde_json::json;
+use surf::get;
+use tempfile::tempdir;
+
+use stubr::{Config, NetworkConfig, RecordOnMiss};
+
+use crate::utils::*;
+
+fn recorded_stubs(dir: &Path) -> Vec<String> {
+    let mut stubs = std::fs::read_dir(dir.join("localhost"))
+        .map(|files| files.flatten().map(|f| f.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_else(|_| vec![]);
+    stubs.sort();
+    stubs
+}
+
+#[async_std::test]
+async fn should_forward_and_record_requests_no_stub_matches() {
+    let upstream = Stubr::start("tests/stubs/resp/proxy/upstream").await;
+    let dir = tempdir().unwrap();
+    copy("tests/stubs/resp/proxy/mocked.json", dir.path().join("mocked.json")).unwrap();
+    let network = NetworkConfig {
+        record_on_miss: Some(RecordOnMiss {
+            upstream: upstream.uri(),
+            output: None,
+        }),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with_network(dir.path().to_path_buf(), Config::default(), network).await;
+
+    get(srv.path("/users/2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 2, "mocked": true}));
+    assert!(recorded_stubs(dir.path()).is_empty());
+
+    get(srv.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_header("x-upstream", "true")
+        .expect_body_json_eq(json!({"id": 1}));
+    let recorded = recorded_stubs(dir.path());
+    assert_eq!(recorded.len(), 1);
+    assert!(recorded[0].starts_with("users-1-"));
+
+    // now served by the recorded stub
+    drop(upstream);
+    get(srv.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_header("x-upstream", "true")
+        .expect_body_json_eq(json!({"id": 1}));
+    assert_eq!(recorded_stubs(dir.path()).len(), 1);
+
+    // and by the next run
+    drop(srv);
+    let next = Stubr::start(dir.path().to_path_buf()).await;
+    get(next.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 1}));
+}
+
+#[async_std::test]
+async fn should_record_in_output_directory() {
+    let upstream = Stubr::start("tests/stubs/resp/proxy/upstream").await;
+    let output = tempdir().unwrap();
+    let network = NetworkConfig {
+        record_on_miss: Some(RecordOnMiss {
+            upstream: upstream.uri(),
+            output: Some(output.path().to_path_buf()),
+        }),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with_network("tests/stubs/resp/proxy/mocked.json", Config::default(), network).await;
+    get(srv.path("/users/1")).await.expect_status_ok();
+    assert_eq!(recorded_stubs(output.path()).len(), 1);
+}
+
+#[async_std::test]
+async fn should_not_forward_without_record_on_miss() {
+    let srv = Stubr::start("tests/stubs/resp/proxy/mocked.json").await;
+    get(srv.path("/users/1")).await.expect_status_not_found();
+}
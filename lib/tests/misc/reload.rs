@@ -0,0 +1,89 @@
+use std::{
+    fs::{remove_file, write, File},
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use asserhttp::*;
+use serde_json::json;
+use surf::get;
+
+use crate::utils::*;
+
+fn write_stub(file: &Path, path: &str, status: u16) {
+    let stub = json!({"request": {"method": "GET", "urlPath": path}, "response": {"status": status}});
+    write(file, stub.to_string()).unwrap();
+    touch(file);
+}
+
+/// Moves the modification time of a file forward so that it is seen as modified whatever the
+/// file system timestamp precision
+fn touch(file: &Path) {
+    static OFFSET: AtomicU64 = AtomicU64::new(1);
+    let modified = SystemTime::now() + Duration::from_secs(OFFSET.fetch_add(1, Ordering::SeqCst));
+    File::options().write(true).open(file).unwrap().set_modified(modified).unwrap();
+}
+
+#[async_std::test]
+async fn should_not_reload_anything_when_nothing_changed() {
+    let dir = tempfile::tempdir().unwrap();
+    write_stub(&dir.path().join("ping.json"), "/ping", 200);
+    let stubr = Stubr::start(dir.path().to_path_buf()).await;
+    assert!(stubr.reload_stubs().is_empty());
+}
+
+#[async_std::test]
+async fn should_mount_added_stubs() {
+    let dir = tempfile::tempdir().unwrap();
+    let stubr = Stubr::start(dir.path().to_path_buf()).await;
+    get(stubr.path("/ping")).await.expect_status_not_found();
+    let file = dir.path().join("ping.json");
+    write_stub(&file, "/ping", 200);
+    let reload = stubr.reload_stubs();
+    assert_eq!(reload.added, vec![file]);
+    get(stubr.path("/ping")).await.expect_status_ok();
+    assert!(stubr.reload_stubs().is_empty());
+}
+
+#[async_std::test]
+async fn should_replace_changed_stubs() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("ping.json");
+    write_stub(&file, "/ping", 200);
+    let stubr = Stubr::start(dir.path().to_path_buf()).await;
+    write_stub(&file, "/ping", 201);
+    let reload = stubr.reload_stubs();
+    assert_eq!(reload.changed, vec![file]);
+    assert!(reload.added.is_empty());
+    get(stubr.path("/ping")).await.expect_status(201);
+}
+
+#[async_std::test]
+async fn should_drop_removed_stubs() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("ping.json");
+    write_stub(&file, "/ping", 200);
+    let stubr = Stubr::start(dir.path().to_path_buf()).await;
+    remove_file(&file).unwrap();
+    let reload = stubr.reload_stubs();
+    assert_eq!(reload.removed, vec![file]);
+    get(stubr.path("/ping")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_keep_serving_previous_stub_when_changed_one_is_invalid() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("ping.json");
+    write_stub(&file, "/ping", 200);
+    let stubr = Stubr::start(dir.path().to_path_buf()).await;
+    write(&file, r#"{"request": {"method": 42}}"#).unwrap();
+    touch(&file);
+    let reload = stubr.reload_stubs();
+    assert_eq!(reload.errors.len(), 1);
+    assert_eq!(reload.errors[0].0, file);
+    assert!(reload.changed.is_empty());
+    get(stubr.path("/ping")).await.expect_status_ok();
+    // the error is reported once until the file changes again
+    assert!(stubr.reload_stubs().is_empty());
+}
@@ -0,0 +1,177 @@
+use asserhttp::*;
+use serde_json::json;
+use surf::{delete, get, patch, post, put};
+
+use stubr::{Config, StubrError};
+
+use crate::utils::*;
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_list_seeded_items() {
+    get(stubr.path("/users"))
+        .await
+        .expect_status_ok()
+        .expect_header("x-total-count", "3")
+        .expect_body_json_eq(json!([{"id": 1, "name": "alice"}, {"id": 2, "name": "bob"}, {"id": 3, "name": "carol"}]));
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_paginate() {
+    get(stubr.path("/users?page=2&size=2"))
+        .await
+        .expect_status_ok()
+        .expect_header("x-total-count", "3")
+        .expect_body_json_eq(json!([{"id": 3, "name": "carol"}]));
+    get(stubr.path("/users?size=2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!([{"id": 1, "name": "alice"}, {"id": 2, "name": "bob"}]));
+    get(stubr.path("/users?page=3&size=2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!([]));
+    get(stubr.path("/users?page=0")).await.expect_status_bad_request();
+    get(stubr.path("/users?size=abc")).await.expect_status_bad_request();
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_get_item_by_id() {
+    get(stubr.path("/users/2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 2, "name": "bob"}));
+    get(stubr.path("/users/42")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_create_item_with_next_id() {
+    post(stubr.path("/users"))
+        .body(json!({"name": "dave"}))
+        .await
+        .expect_status_created()
+        .expect_header("location", "/users/4")
+        .expect_body_json_eq(json!({"id": 4, "name": "dave"}));
+    get(stubr.path("/users/4"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 4, "name": "dave"}));
+    get(stubr.path("/users")).await.expect_header("x-total-count", "4");
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_not_create_item_twice_or_from_invalid_body() {
+    post(stubr.path("/users"))
+        .body(json!({"id": 1, "name": "alice"}))
+        .await
+        .expect_status_conflict();
+    post(stubr.path("/users")).body(json!([1, 2])).await.expect_status_bad_request();
+    post(stubr.path("/users")).body("not json").await.expect_status_bad_request();
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_replace_item() {
+    put(stubr.path("/users/1"))
+        .body(json!({"name": "alicia", "age": 30}))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 1, "name": "alicia", "age": 30}));
+    put(stubr.path("/users/1"))
+        .body(json!({"name": "alice"}))
+        .await
+        .expect_status_ok();
+    get(stubr.path("/users/1"))
+        .await
+        .expect_body_json_eq(json!({"id": 1, "name": "alice"}));
+    put(stubr.path("/users/42"))
+        .body(json!({"name": "nobody"}))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_patch_item() {
+    patch(stubr.path("/users/2"))
+        .body(json!({"age": 40}))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 2, "name": "bob", "age": 40}));
+    patch(stubr.path("/users/2"))
+        .body(json!({"age": null}))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 2, "name": "bob"}));
+    patch(stubr.path("/users/42"))
+        .body(json!({"age": 40}))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_delete_item() {
+    delete(stubr.path("/users/3")).await.expect_status(204);
+    get(stubr.path("/users/3")).await.expect_status_not_found();
+    delete(stubr.path("/users/3")).await.expect_status_not_found();
+    get(stubr.path("/users")).await.expect_header("x-total-count", "2");
+}
+
+#[async_std::test]
+#[stubr::mock("resource/users.json")]
+async fn should_not_serve_other_paths_or_methods() {
+    get(stubr.path("/users/1/orders")).await.expect_status_not_found();
+    get(stubr.path("/accounts")).await.expect_status_not_found();
+    delete(stubr.path("/users")).await.expect_status(405);
+    post(stubr.path("/users/1")).body(json!({})).await.expect_status(405);
+}
+
+#[async_std::test]
+#[stubr::mock("resource/orders.json")]
+async fn should_use_id_field_and_request_matchers() {
+    post(stubr.path("/orders"))
+        .header("x-tenant", "acme")
+        .body(json!({"reference": "ab-12", "total": 10}))
+        .await
+        .expect_status_created()
+        .expect_header("location", "/orders/ab-12");
+    get(stubr.path("/orders/ab-12"))
+        .header("x-tenant", "acme")
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"reference": "ab-12", "total": 10}));
+    get(stubr.path("/orders/ab-12")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn items_should_not_be_shared_between_servers() {
+    let (first, second) = (
+        Stubr::start("tests/stubs/resource/users.json").await,
+        Stubr::start("tests/stubs/resource/users.json").await,
+    );
+    delete(first.path("/users/1")).await.expect_status(204);
+    get(second.path("/users/1")).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_reject_invalid_resources() {
+    for stub in ["invalid-seed.json", "invalid-with-response.json"] {
+        let cfg = Config {
+            strict: true,
+            ..Default::default()
+        };
+        let result = Stubr::try_start_with(format!("tests/stubs/resource/{stub}"), cfg).await;
+        match result {
+            Err(StubrError::RejectedStubs(rejected)) => assert!(matches!(
+                rejected[0].1,
+                StubrError::ResourceSeedNotFound(_) | StubrError::InvalidResource(_)
+            )),
+            _ => panic!("expected stub '{stub}' to be rejected"),
+        }
+    }
+}
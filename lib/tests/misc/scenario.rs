@@ -0,0 +1,77 @@
+use asserhttp::*;
+use serde_json::{json, Value};
+use surf::{delete, get, post, put};
+
+#[async_std::test]
+#[stubr::mock("scenario")]
+async fn should_move_to_new_state_once_matched() {
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("Started"));
+    get(stubr.path("/order"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"status": "pending"}));
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("done"));
+    get(stubr.path("/order"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"status": "done"}));
+    get(stubr.path("/order"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"status": "done"}));
+}
+
+#[async_std::test]
+#[stubr::mock("scenario")]
+async fn should_match_in_any_state_without_required_state() {
+    delete(stubr.path("/order")).await.expect_status(204);
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("cancelled"));
+    get(stubr.path("/order")).await.expect_status_not_found();
+    delete(stubr.path("/order")).await.expect_status(204);
+}
+
+#[async_std::test]
+#[stubr::mock("scenario")]
+async fn should_set_and_reset_state_from_tests() {
+    stubr.set_scenario_state("order", "done");
+    get(stubr.path("/order")).await.expect_body_json_eq(json!({"status": "done"}));
+    stubr.reset_scenarios();
+    get(stubr.path("/order"))
+        .await
+        .expect_body_json_eq(json!({"status": "pending"}));
+    assert!(stubr.try_set_scenario_state("unknown", "done").is_err());
+    assert!(stubr.scenario_state("unknown").is_none());
+}
+
+#[async_std::test]
+#[stubr::mock("scenario")]
+async fn should_manage_scenarios_from_admin_api() {
+    get(stubr.path("/order")).await.expect_status_ok();
+    get(stubr.path("/__admin/scenarios"))
+        .await
+        .expect_status_ok()
+        .expect_body_json(|b: Value| {
+            assert_eq!(b["scenarios"], json!([{"id": "order", "name": "order", "state": "done"}]));
+        });
+    post(stubr.path("/__admin/scenarios/reset")).await.expect_status_ok();
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("Started"));
+    put(stubr.path("/__admin/scenarios/order/state"))
+        .body(json!({"state": "cancelled"}))
+        .await
+        .expect_status_ok();
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("cancelled"));
+    put(stubr.path("/__admin/scenarios/order/state")).await.expect_status_ok();
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("Started"));
+    put(stubr.path("/__admin/scenarios/unknown/state"))
+        .body(json!({"state": "done"}))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("scenario")]
+async fn admin_reset_should_reset_scenarios() {
+    get(stubr.path("/order")).await.expect_status_ok();
+    post(stubr.path("/__admin/reset")).await.expect_status_ok();
+    assert_eq!(stubr.scenario_state("order").as_deref(), Some("Started"));
+}
@@ -0,0 +1,70 @@
+use asserhttp::*;
+use surf::get;
+
+use stubr::{Config, StubrError};
+
+use crate::utils::*;
+
+const STUBS: &str = "tests/stubs/strict";
+
+fn strict() -> Config {
+    Config {
+        strict: true,
+        ..Default::default()
+    }
+}
+
+#[async_std::test]
+async fn should_skip_unreadable_stubs_by_default() {
+    let stubr = Stubr::try_start(STUBS).await.unwrap();
+    get(stubr.path("/valid")).await.expect_status_ok();
+    let mounted = stubr
+        .stub_hits()
+        .into_keys()
+        .filter_map(|f| f.file_name().map(|n| n.to_os_string()))
+        .collect::<Vec<_>>();
+    assert!(!mounted.contains(&"status.json".into()));
+}
+
+#[async_std::test]
+async fn should_ignore_invalid_matchers_by_default() {
+    let stubr = Stubr::try_start("tests/stubs/strict/invalid/url-regex.json").await.unwrap();
+    get(stubr.path("/anything")).await.expect_status_ok();
+    let stubr = Stubr::try_start("tests/stubs/strict/invalid/header-regex.json").await.unwrap();
+    get(stubr.path("/anything")).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn strict_should_start_when_every_stub_is_valid() {
+    let stubr = Stubr::try_start_with("tests/stubs/strict/valid.json", strict()).await.unwrap();
+    get(stubr.path("/valid")).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn strict_should_report_every_invalid_stub() {
+    match Stubr::try_start_with(STUBS, strict()).await {
+        Err(StubrError::RejectedStubs(rejected)) => {
+            let mut files = rejected
+                .iter()
+                .filter_map(|(file, _)| file.file_name().and_then(|f| f.to_str()))
+                .collect::<Vec<_>>();
+            files.sort_unstable();
+            assert_eq!(
+                files,
+                vec!["header-regex.json", "json-path.json", "status.json", "url-regex.json"]
+            );
+        },
+        Err(e) => panic!("unexpected error {e}"),
+        Ok(_) => panic!("should not start"),
+    }
+}
+
+#[async_std::test]
+async fn strict_should_explain_why_stubs_are_rejected() {
+    let error = Stubr::try_start_with(STUBS, strict()).await.err().unwrap().to_string();
+    assert!(error.starts_with("4 stub(s) could not be loaded:"));
+    assert!(error.contains("url-regex.json\": regex parse error"));
+    assert!(error.contains("header-regex.json\": regex parse error"));
+    assert!(error.contains("json-path.json\": json path error"));
+    assert!(error.contains("status.json\": invalid type: string \"ok\""));
+}
@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use asserhttp::*;
+use isahc::{
+    config::{CaCertificate, ClientCertificate, Configurable, PrivateKey, SslOption, VersionNegotiation},
+    AsyncReadResponseExt, HttpClient,
+};
+use tempfile::NamedTempFile;
+
+use stubr::{ClientAuth, Config, NetworkConfig, Tls};
+
+use crate::utils::*;
+
+fn tls(tls: Tls) -> NetworkConfig {
+    NetworkConfig {
+        tls: Some(tls),
+        ..Default::default()
+    }
+}
+
+fn client_trusting(ca: impl Into<PathBuf>) -> HttpClient {
+    HttpClient::builder()
+        .ssl_ca_certificate(CaCertificate::file(ca.into()))
+        .build()
+        .unwrap()
+}
+
+fn trust_generated_ca(srv: &Stubr) -> (HttpClient, NamedTempFile) {
+    let ca = NamedTempFile::new().unwrap();
+    std::fs::write(ca.path(), srv.ca_cert().unwrap()).unwrap();
+    (client_trusting(ca.path()), ca)
+}
+
+#[async_std::test]
+async fn should_serve_over_https_with_generated_certificate() {
+    let srv = Stubr::start_with_network("tests/stubs/ping.json", Config::default(), tls(Tls::SelfSigned)).await;
+    assert!(srv.uri().starts_with("https://127.0.0.1:"));
+    let (client, _ca) = trust_generated_ca(&srv);
+    client.get_async(srv.uri()).await.expect_status_ok();
+    let localhost = srv.uri().replace("127.0.0.1", "localhost");
+    client.get_async(localhost).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_serve_over_https_with_provided_certificate() {
+    let network = tls(Tls::Files {
+        cert: "tests/stubs/tls/cert.pem".into(),
+        key: "tests/stubs/tls/key.pem".into(),
+    });
+    let srv = Stubr::start_with_network("tests/stubs/hello.json", Config::default(), network).await;
+    assert!(srv.ca_cert().is_none());
+    let mut resp = client_trusting("tests/stubs/tls/ca.pem")
+        .get_async(srv.path("/hello"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().await.unwrap(), "Hello stubr");
+}
+
+#[async_std::test]
+async fn should_reject_clients_not_trusting_ca() {
+    let srv = Stubr::start_with_network("tests/stubs/ping.json", Config::default(), tls(Tls::SelfSigned)).await;
+    assert!(isahc::get_async(srv.uri()).await.is_err());
+    // the server keeps serving the other clients
+    let (client, _ca) = trust_generated_ca(&srv);
+    client.get_async(srv.uri()).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_survive_plain_http_requests() {
+    let srv = Stubr::start_with_network("tests/stubs/ping.json", Config::default(), tls(Tls::SelfSigned)).await;
+    assert!(isahc::get_async(srv.uri().replace("https", "http")).await.is_err());
+    let insecure = HttpClient::builder()
+        .ssl_options(SslOption::DANGER_ACCEPT_INVALID_CERTS)
+        .build()
+        .unwrap();
+    insecure.get_async(srv.uri()).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_negotiate_http2_over_alpn() {
+    let srv = Stubr::start_with_network(
+        "tests/stubs/resp/template/url/protocol.json",
+        Config::default(),
+        tls(Tls::SelfSigned),
+    )
+    .await;
+    let (client, _ca) = trust_generated_ca(&srv);
+    let mut resp = client.get_async(srv.path("/api/protocol")).await.unwrap();
+    assert_eq!(resp.version(), isahc::http::Version::HTTP_2);
+    assert_eq!(resp.text().await.unwrap(), "HTTP/2");
+}
+
+#[async_std::test]
+async fn should_fall_back_to_http11_over_alpn() {
+    let srv = Stubr::start_with_network(
+        "tests/stubs/resp/template/url/protocol.json",
+        Config::default(),
+        tls(Tls::SelfSigned),
+    )
+    .await;
+    let ca = NamedTempFile::new().unwrap();
+    std::fs::write(ca.path(), srv.ca_cert().unwrap()).unwrap();
+    let client = HttpClient::builder()
+        .ssl_ca_certificate(CaCertificate::file(ca.path()))
+        .version_negotiation(VersionNegotiation::http11())
+        .build()
+        .unwrap();
+    let mut resp = client.get_async(srv.path("/api/protocol")).await.unwrap();
+    assert_eq!(resp.version(), isahc::http::Version::HTTP_11);
+    assert_eq!(resp.text().await.unwrap(), "HTTP/1.1");
+}
+
+#[async_std::test]
+async fn should_fail_starting_with_missing_certificate() {
+    let network = tls(Tls::Files {
+        cert: "tests/stubs/tls/missing.pem".into(),
+        key: "tests/stubs/tls/key.pem".into(),
+    });
+    assert!(
+        Stubr::try_start_with_network("tests/stubs/ping.json", Config::default(), network)
+            .await
+            .is_err()
+    );
+}
+
+fn mtls(client_auth: ClientAuth) -> NetworkConfig {
+    NetworkConfig {
+        client_auth: Some(client_auth),
+        ..tls(Tls::SelfSigned)
+    }
+}
+
+fn client_with_cert(srv: &Stubr, name: &str) -> (HttpClient, NamedTempFile) {
+    let ca = NamedTempFile::new().unwrap();
+    std::fs::write(ca.path(), srv.ca_cert().unwrap()).unwrap();
+    let cert = ClientCertificate::pem_file(
+        format!("tests/stubs/tls/{name}.pem"),
+        PrivateKey::pem_file(format!("tests/stubs/tls/{name}-key.pem"), None),
+    );
+    let client = HttpClient::builder()
+        .ssl_ca_certificate(CaCertificate::file(ca.path()))
+        .ssl_client_certificate(cert)
+        .build()
+        .unwrap();
+    (client, ca)
+}
+
+#[async_std::test]
+async fn should_fail_handshake_without_required_client_certificate() {
+    let client_auth = ClientAuth {
+        required: true,
+        ..Default::default()
+    };
+    let srv = Stubr::start_with_network("tests/stubs/ping.json", Config::default(), mtls(client_auth)).await;
+    let (client, _ca) = trust_generated_ca(&srv);
+    assert!(client.get_async(srv.uri()).await.is_err());
+    let (client, _ca) = client_with_cert(&srv, "client");
+    client.get_async(srv.uri()).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_serve_clients_without_optional_client_certificate() {
+    let srv = Stubr::start_with_network("tests/stubs/ping.json", Config::default(), mtls(ClientAuth::default())).await;
+    let (client, _ca) = trust_generated_ca(&srv);
+    client.get_async(srv.uri()).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_fail_handshake_with_client_certificate_from_untrusted_ca() {
+    let client_auth = ClientAuth {
+        ca: Some("tests/stubs/tls/client-ca.pem".into()),
+        required: true,
+    };
+    let srv = Stubr::start_with_network("tests/stubs/ping.json", Config::default(), mtls(client_auth)).await;
+    let (client, _ca) = client_with_cert(&srv, "untrusted");
+    assert!(client.get_async(srv.uri()).await.is_err());
+    let (client, _ca) = client_with_cert(&srv, "client");
+    client.get_async(srv.uri()).await.expect_status_ok();
+}
@@ -0,0 +1,83 @@
+use asserhttp::*;
+use serde_json::json;
+use surf::{delete, get, post};
+
+use stubr::{RequestCount, RequestStub, StubrError};
+
+const PING_ID: &str = "4b8b9b9e-0d46-4a2a-9b1e-0f0bd1a6c1a1";
+
+fn request(json: serde_json::Value) -> RequestStub {
+    serde_json::from_value(json).unwrap()
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_verify_requests_matching_a_stub() {
+    get(stubr.path("/ping")).await.expect_status_ok();
+    get(stubr.path("/ping")).await.expect_status_ok();
+    let report = stubr.verify(RequestCount::Exactly(2), PING_ID).unwrap();
+    assert_eq!(report.matching, vec!["GET /ping", "GET /ping"]);
+    assert!(stubr.verify(RequestCount::AtLeast(1), PING_ID).is_ok());
+    assert!(stubr.verify(RequestCount::AtMost(2), PING_ID).is_ok());
+    assert!(stubr.verify(RequestCount::AtMost(1), PING_ID).is_err());
+    assert!(stubr.verify(RequestCount::Never, PING_ID).is_err());
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_verify_requests_matching_a_request_pattern() {
+    get(stubr.path("/ping?a=b")).await.expect_status_ok();
+    post(stubr.path("/pong")).await.expect_status_not_found();
+    let with_query = request(json!({"method": "GET", "queryParameters": {"a": {"equalTo": "b"}}}));
+    assert!(stubr.verify(RequestCount::Exactly(1), with_query).is_ok());
+    let pong = request(json!({"urlPath": "/pong"}));
+    assert!(stubr.verify(RequestCount::Exactly(1), pong).is_ok());
+    let delete = request(json!({"method": "DELETE"}));
+    assert!(stubr.verify(RequestCount::Never, delete).is_ok());
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_report_near_matching_requests_when_failing() {
+    get(stubr.path("/ping")).await.expect_status_ok();
+    post(stubr.path("/ping")).await.expect_status_not_found();
+    match stubr.verify(RequestCount::Exactly(2), PING_ID) {
+        Err(StubrError::VerificationFailed(report)) => {
+            assert_eq!(report.matching.len(), 1);
+            assert_eq!(report.near_matching[0].request, "POST /ping");
+            let message = report.to_string();
+            assert!(message.starts_with(&format!(
+                "Expected exactly 2 request(s) matching stub '{PING_ID}' but received 1"
+            )));
+            assert!(message.contains("!= method 'method': expected \"GET\" got 'POST'"));
+        },
+        other => panic!("Unexpected verification outcome {other:?}"),
+    }
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_fail_verifying_unknown_stub() {
+    assert!(matches!(
+        stubr.verify(RequestCount::Never, "unknown"),
+        Err(StubrError::UnknownStub(id)) if id == "unknown"
+    ));
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_not_verify_admin_requests() {
+    get(stubr.path("/__admin/mappings")).await.expect_status_ok();
+    assert!(stubr.verify(RequestCount::Never, request(json!({}))).is_ok());
+}
+
+#[async_std::test]
+#[stubr::mock("admin/ping.json")]
+async fn should_forget_received_requests_once_reset() {
+    get(stubr.path("/ping")).await.expect_status_ok();
+    delete(stubr.path("/__admin/requests")).await.expect_status_ok();
+    assert!(stubr.verify(RequestCount::Never, PING_ID).is_ok());
+    get(stubr.path("/ping")).await.expect_status_ok();
+    post(stubr.path("/__admin/reset")).await.expect_status_ok();
+    assert!(stubr.verify(RequestCount::Never, PING_ID).is_ok());
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use asserhttp::*;
+use serde_json::json;
+
+use stubr::{Config, LoggedRequest};
+
+use crate::utils::*;
+
+async fn receiver() -> Stubr {
+    let cfg = Config {
+        request_journal: Some(10),
+        ..Default::default()
+    };
+    Stubr::start_with("tests/stubs/webhook/receiver.json", cfg).await
+}
+
+/// Webhooks are sent in the background
+async fn callbacks(receiver: &Stubr, timeout: Duration) -> Vec<LoggedRequest> {
+    let step = Duration::from_millis(10);
+    let mut waited = Duration::ZERO;
+    while waited < timeout {
+        let requests = receiver.received_requests();
+        if !requests.is_empty() {
+            return requests;
+        }
+        async_std::task::sleep(step).await;
+        waited += step;
+    }
+    vec![]
+}
+
+#[async_std::test]
+async fn should_send_templated_webhook() {
+    let receiver = receiver().await;
+    let srv = Stubr::start("tests/stubs/webhook/payment.json").await;
+    surf::post(srv.path("/payments/42"))
+        .header("x-callback", receiver.uri())
+        .await
+        .expect_status_accepted();
+    let callbacks = callbacks(&receiver, Duration::from_secs(2)).await;
+    assert_eq!(callbacks.len(), 1);
+    let callback = &callbacks[0];
+    assert_eq!(callback.method, "POST");
+    assert_eq!(callback.url, "/callbacks/42");
+    assert!(callback.was_matched);
+    assert_eq!(
+        callback.headers.get("content-type").map(String::as_str),
+        Some("application/json")
+    );
+    let body = serde_json::from_slice::<serde_json::Value>(&callback.body).unwrap();
+    assert_eq!(body, json!({"payment": 42, "status": "SUCCESS"}));
+}
+
+#[async_std::test]
+async fn should_delay_webhook() {
+    let receiver = receiver().await;
+    let srv = Stubr::start("tests/stubs/webhook/delayed.json").await;
+    surf::post(srv.path("/delayed"))
+        .header("x-callback", receiver.uri())
+        .await
+        .expect_status_accepted();
+    assert!(callbacks(&receiver, Duration::from_millis(150)).await.is_empty());
+    assert_eq!(callbacks(&receiver, Duration::from_secs(2)).await.len(), 1);
+}
+
+#[async_std::test]
+async fn failing_webhook_should_not_fail_response() {
+    let srv = Stubr::start("tests/stubs/webhook/unreachable.json").await;
+    surf::post(srv.path("/unreachable")).await.expect_status_accepted();
+    surf::post(srv.path("/unreachable")).await.expect_status_accepted();
+}
+
+#[async_std::test]
+async fn should_not_send_webhook_of_a_response_broken_by_a_fault() {
+    let receiver = receiver().await;
+    let srv = Stubr::start("tests/stubs/webhook/faulty.json").await;
+    assert!(surf::post(srv.path("/faulty"))
+        .header("x-callback", receiver.uri())
+        .await
+        .is_err());
+    assert!(callbacks(&receiver, Duration::from_millis(300)).await.is_empty());
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use asserhttp::*;
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{protocol::frame::coding::CloseCode, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+use stubr::{Config, StubrError};
+
+use crate::utils::*;
+
+type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn connect(stubr: &Stubr, path: &str) -> Socket {
+    let url = stubr.path(path).replacen("http", "ws", 1);
+    let (socket, resp) = connect_async(url).await.unwrap();
+    assert_eq!(resp.status(), 101);
+    socket
+}
+
+async fn next(socket: &mut Socket) -> Message {
+    tokio::time::timeout(Duration::from_secs(2), socket.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+#[stubr::mock("websocket/feed.json")]
+async fn should_send_messages_on_connect_with_delay() {
+    let mut socket = connect(&stubr, "/feed").await;
+    let begin = std::time::Instant::now();
+    assert_eq!(next(&mut socket).await, Message::Text("welcome".to_string()));
+    assert_eq!(next(&mut socket).await, Message::Text(r#"{"price":42}"#.to_string()));
+    assert!(begin.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+#[stubr::mock("websocket/feed.json")]
+async fn should_reply_to_matching_messages() {
+    let mut socket = connect(&stubr, "/feed").await;
+    next(&mut socket).await;
+    next(&mut socket).await;
+    socket.send(Message::Text("ping".to_string())).await.unwrap();
+    assert_eq!(next(&mut socket).await, Message::Text("pong".to_string()));
+    socket
+        .send(Message::Text(r#"{"subscribe":"prices"}"#.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(next(&mut socket).await, Message::Text(r#"{"subscribed":true}"#.to_string()));
+    assert_eq!(next(&mut socket).await, Message::Binary(vec![1, 2, 3]));
+}
+
+#[tokio::test]
+#[stubr::mock("websocket/feed.json")]
+async fn should_ignore_unmatched_messages() {
+    let mut socket = connect(&stubr, "/feed").await;
+    next(&mut socket).await;
+    next(&mut socket).await;
+    socket.send(Message::Text("unknown".to_string())).await.unwrap();
+    socket.send(Message::Text("ping".to_string())).await.unwrap();
+    assert_eq!(next(&mut socket).await, Message::Text("pong".to_string()));
+}
+
+#[tokio::test]
+#[stubr::mock("websocket/feed.json")]
+async fn should_close_with_scripted_frame() {
+    let mut socket = connect(&stubr, "/feed").await;
+    next(&mut socket).await;
+    next(&mut socket).await;
+    socket.send(Message::Text("bye".to_string())).await.unwrap();
+    match next(&mut socket).await {
+        Message::Close(Some(frame)) => {
+            assert_eq!(frame.code, CloseCode::from(4000));
+            assert_eq!(frame.reason, "see you");
+        },
+        other => panic!("expected a close frame, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+#[stubr::mock("websocket/feed.json")]
+async fn should_match_upgrade_request_with_request_matchers() {
+    let url = stubr.path("/other").replacen("http", "ws", 1);
+    assert!(connect_async(url).await.is_err());
+}
+
+#[tokio::test]
+#[stubr::mock("websocket/feed.json")]
+async fn should_require_upgrade_request() {
+    surf::get(stubr.path("/feed")).await.expect_status(426);
+}
+
+#[async_std::test]
+async fn should_fail_when_both_web_socket_and_response() {
+    let cfg = Config {
+        strict: true,
+        ..Default::default()
+    };
+    let result = Stubr::try_start_with("tests/stubs/websocket/invalid-with-response.json", cfg).await;
+    match result {
+        Err(StubrError::RejectedStubs(rejected)) => {
+            assert!(matches!(rejected[0].1, StubrError::InvalidWebSocket(_)))
+        },
+        _ => panic!("expected stub to be rejected"),
+    }
+}
@@ -0,0 +1,76 @@
+use asserhttp::*;
+use isahc::{
+    config::{CaCertificate, ClientCertificate, Configurable, PrivateKey},
+    HttpClient,
+};
+use tempfile::NamedTempFile;
+
+use stubr::{ClientAuth, Config, NetworkConfig, Tls};
+
+use crate::utils::*;
+
+const CLIENT: (&str, &str) = ("tests/stubs/tls/client.pem", "tests/stubs/tls/client-key.pem");
+const UNTRUSTED: (&str, &str) = ("tests/stubs/tls/untrusted.pem", "tests/stubs/tls/untrusted-key.pem");
+
+async fn mtls_server(stub: &str) -> (Stubr, NamedTempFile) {
+    let network = NetworkConfig {
+        tls: Some(Tls::SelfSigned),
+        client_auth: Some(ClientAuth::default()),
+        ..Default::default()
+    };
+    let srv = Stubr::start_with_network(format!("tests/stubs/req/client-cert/{stub}.json"), Config::default(), network).await;
+    let ca = NamedTempFile::new().unwrap();
+    std::fs::write(ca.path(), srv.ca_cert().unwrap()).unwrap();
+    (srv, ca)
+}
+
+fn client(ca: &NamedTempFile, cert: Option<(&str, &str)>) -> HttpClient {
+    let builder = HttpClient::builder().ssl_ca_certificate(CaCertificate::file(ca.path()));
+    match cert {
+        Some((cert, key)) => builder.ssl_client_certificate(ClientCertificate::pem_file(cert, PrivateKey::pem_file(key, None))),
+        None => builder,
+    }
+    .build()
+    .unwrap()
+}
+
+#[async_std::test]
+async fn should_match_subject() {
+    let (srv, ca) = mtls_server("subject").await;
+    let uri = srv.path("/subject");
+    client(&ca, Some(CLIENT)).get_async(&uri).await.expect_status_ok();
+    client(&ca, Some(UNTRUSTED)).get_async(&uri).await.expect_status_not_found();
+    client(&ca, None).get_async(&uri).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_match_any_san() {
+    let (srv, ca) = mtls_server("san").await;
+    let uri = srv.path("/san");
+    client(&ca, Some(CLIENT)).get_async(&uri).await.expect_status_ok();
+    client(&ca, Some(UNTRUSTED)).get_async(&uri).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_match_fingerprint() {
+    let (srv, ca) = mtls_server("fingerprint").await;
+    let uri = srv.path("/fingerprint");
+    client(&ca, Some(CLIENT)).get_async(&uri).await.expect_status_ok();
+    client(&ca, Some(UNTRUSTED)).get_async(&uri).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_match_absent_certificate() {
+    let (srv, ca) = mtls_server("absent").await;
+    let uri = srv.path("/absent");
+    client(&ca, None).get_async(&uri).await.expect_status(401);
+    client(&ca, Some(CLIENT)).get_async(&uri).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_not_match_without_tls() {
+    let srv = Stubr::start("tests/stubs/req/client-cert/subject.json").await;
+    surf::get(srv.path("/subject")).await.expect_status_not_found();
+    let srv = Stubr::start("tests/stubs/req/client-cert/absent.json").await;
+    surf::get(srv.path("/absent")).await.expect_status(401);
+}
@@ -1,7 +1,9 @@
 pub mod body;
+pub mod client_cert;
 pub mod headers;
 pub mod matcher_precedence;
 pub mod method;
 pub mod priority;
+pub mod protocol;
 pub mod query;
 pub mod url;
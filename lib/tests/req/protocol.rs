@@ -0,0 +1,37 @@
+use asserhttp::*;
+use isahc::{
+    config::{Configurable, VersionNegotiation},
+    HttpClient,
+};
+
+fn client(version: VersionNegotiation) -> HttpClient {
+    HttpClient::builder().version_negotiation(version).build().unwrap()
+}
+
+#[async_std::test]
+#[stubr::mock("req/protocol/http2.json")]
+async fn should_match_http2_with_prior_knowledge() {
+    client(VersionNegotiation::http2())
+        .get_async(stubr.path("/protocol"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("HTTP/2 only");
+    client(VersionNegotiation::http11())
+        .get_async(stubr.path("/protocol"))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+#[stubr::mock("req/protocol/http11.json")]
+async fn should_match_http11() {
+    client(VersionNegotiation::http11())
+        .get_async(stubr.path("/protocol"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("HTTP/1.1 only");
+    client(VersionNegotiation::http2())
+        .get_async(stubr.path("/protocol"))
+        .await
+        .expect_status_not_found();
+}
@@ -63,3 +63,56 @@ mod lognormal {
         assert!((mean > LOWER) && (mean < UPPER));
     }
 }
+
+mod uniform {
+    use super::*;
+
+    #[async_std::test]
+    #[stubr::mock("resp/delay/uniform-100-200.json")]
+    async fn should_delay_between_lower_and_upper() {
+        for _ in 0..10 {
+            let begin = std::time::Instant::now();
+            get(stubr.uri()).await.expect_status_ok();
+            let delta = begin.elapsed();
+            assert!(delta >= Duration::from_millis(100) && delta < Duration::from_millis(400));
+        }
+    }
+}
+
+mod percentiles {
+    use super::*;
+
+    #[async_std::test]
+    #[stubr::mock("resp/delay/percentiles-p50-100-p99-200.json")]
+    async fn should_not_exceed_highest_percentile() {
+        for _ in 0..10 {
+            let begin = std::time::Instant::now();
+            get(stubr.uri()).await.expect_status_ok();
+            assert!(begin.elapsed() < Duration::from_millis(400));
+        }
+    }
+}
+
+mod dribble {
+    use super::*;
+
+    #[async_std::test]
+    #[stubr::mock("resp/delay/dribble-4-chunks-400ms.json")]
+    async fn should_send_headers_immediately_then_dribble_body() {
+        let begin = std::time::Instant::now();
+        let mut resp = get(stubr.uri()).await.unwrap();
+        resp.expect_status_ok().expect_header("content-length", "12");
+        assert!(begin.elapsed() < Duration::from_millis(300));
+        assert_eq!(resp.body_string().await.unwrap(), "Hello world!");
+        assert!(begin.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[async_std::test]
+    #[stubr::mock("resp/delay/dribble-4-chunks-400ms.json")]
+    async fn should_timeout_reading_dribbled_body() {
+        let mut resp = get(stubr.uri()).await.unwrap();
+        resp.expect_status_ok();
+        let timeout = io::timeout(Duration::from_millis(200), async { Ok(resp.body_string().await.unwrap()) }).await;
+        assert!(timeout.is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::TcpStream,
+};
+
+use asserhttp::*;
+use surf::get;
+
+use stubr::{Config, Fault, GlobalFault};
+
+use crate::utils::*;
+
+/// Sends a raw GET request then reads whatever the server sends back until it closes the connection
+fn raw_get(uri: &str) -> std::io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(uri.trim_start_matches("http://"))?;
+    stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+fn global(fault: Fault, probability: f64) -> Config {
+    Config {
+        fault: Some(GlobalFault { fault, probability }),
+        ..Default::default()
+    }
+}
+
+#[async_std::test]
+#[stubr::mock("resp/fault/connection-reset-by-peer.json")]
+async fn should_reset_connection() {
+    let error = raw_get(&stubr.uri()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::ConnectionReset);
+    assert!(get(stubr.uri()).await.is_err());
+}
+
+#[async_std::test]
+#[stubr::mock("resp/fault/empty-response.json")]
+async fn should_close_connection_without_responding() {
+    assert!(raw_get(&stubr.uri()).unwrap().is_empty());
+    assert!(get(stubr.uri()).await.is_err());
+}
+
+#[async_std::test]
+#[stubr::mock("resp/fault/malformed-response-chunk.json")]
+async fn should_send_malformed_chunk() {
+    let response = String::from_utf8(raw_get(&stubr.uri()).unwrap()).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("transfer-encoding: chunked"));
+    assert!(response.contains("lorem ipsum"));
+    // no terminating chunk
+    assert!(!response.ends_with("0\r\n\r\n"));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/fault/random-data-then-close.json")]
+async fn should_send_random_data() {
+    let response = raw_get(&stubr.uri()).unwrap();
+    assert!(!response.is_empty());
+    assert!(!response.starts_with(b"HTTP/1.1"));
+}
+
+#[async_std::test]
+async fn should_apply_global_fault_to_every_stub() {
+    let stubr = Stubr::start_with("tests/stubs/ping.json", global(Fault::EmptyResponse, 1.0)).await;
+    assert!(raw_get(&stubr.uri()).unwrap().is_empty());
+}
+
+#[async_std::test]
+async fn should_not_apply_global_fault_with_zero_probability() {
+    let stubr = Stubr::start_with("tests/stubs/ping.json", global(Fault::EmptyResponse, 0.0)).await;
+    get(stubr.path("/ping")).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn stub_fault_should_supersede_global_one() {
+    let stubr = Stubr::start_with(
+        "tests/stubs/resp/fault/empty-response.json",
+        global(Fault::RandomDataThenClose, 1.0),
+    )
+    .await;
+    assert!(raw_get(&stubr.uri()).unwrap().is_empty());
+}
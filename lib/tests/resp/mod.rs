@@ -1,5 +1,9 @@
 pub mod body;
 pub mod delay;
+pub mod fault;
 pub mod headers;
+pub mod proxy;
+pub mod sequence;
 pub mod status;
+pub mod stream;
 pub mod template;
@@ -0,0 +1,81 @@
+use std::fs::{read_to_string, write};
+
+use asserhttp::*;
+use serde_json::json;
+use surf::{get, post};
+use tempfile::{tempdir, TempDir};
+
+use crate::utils::*;
+
+/// Starts an upstream server then a server with the given proxy stubs forwarding to it
+async fn proxy(stubs: &[&str]) -> (Stubr, Stubr, TempDir) {
+    let upstream = Stubr::start("tests/stubs/resp/proxy/upstream").await;
+    let dir = tempdir().unwrap();
+    for stub in stubs {
+        let content = read_to_string(format!("tests/stubs/resp/proxy/{stub}.json"))
+            .unwrap()
+            .replace("http://upstream", &upstream.uri());
+        write(dir.path().join(format!("{stub}.json")), content).unwrap();
+    }
+    let proxy = Stubr::start(dir.path().to_path_buf()).await;
+    (proxy, upstream, dir)
+}
+
+#[async_std::test]
+async fn should_forward_request_and_respond_with_upstream_response() {
+    let (srv, _upstream, _dir) = proxy(&["catch-all"]).await;
+    get(srv.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_header("x-upstream", "true")
+        .expect_body_json_eq(json!({"id": 1}));
+}
+
+#[async_std::test]
+async fn should_forward_method_query_and_body() {
+    let (srv, _upstream, _dir) = proxy(&["catch-all"]).await;
+    post(srv.path_query("/users", "notify", "true"))
+        .body(json!({"name": "alice"}))
+        .await
+        .expect_status_created();
+    post(srv.path_query("/users", "notify", "false"))
+        .body(json!({"name": "alice"}))
+        .await
+        .expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_respond_with_mocked_stub_before_low_priority_proxy() {
+    let (srv, _upstream, _dir) = proxy(&["catch-all", "mocked"]).await;
+    get(srv.path("/users/2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 2, "mocked": true}));
+    get(srv.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_header("x-upstream", "true");
+    get(srv.path("/users/3")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_add_additional_proxy_request_headers() {
+    let (srv, upstream, _dir) = proxy(&["additional-headers"]).await;
+    get(upstream.path("/extra")).await.expect_status_not_found();
+    get(srv.path("/extra")).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_remove_url_prefix() {
+    let (srv, _upstream, _dir) = proxy(&["prefix"]).await;
+    get(srv.path("/other/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 1}));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/proxy/unreachable.json")]
+async fn should_fail_with_bad_gateway_when_upstream_unreachable() {
+    get(stubr.uri()).await.expect_status(502);
+}
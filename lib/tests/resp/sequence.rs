@@ -0,0 +1,71 @@
+use asserhttp::*;
+use surf::get;
+
+use stubr::{Config, StubrError};
+
+use crate::utils::*;
+
+#[async_std::test]
+#[stubr::mock("resp/sequence/sequential.json")]
+async fn sequential_should_stick_on_last_response() {
+    get(stubr.path("/sequential")).await.expect_status(503);
+    get(stubr.path("/sequential")).await.expect_status(503);
+    get(stubr.path("/sequential"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("recovered");
+    get(stubr.path("/sequential"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("recovered");
+}
+
+#[async_std::test]
+#[stubr::mock("resp/sequence/cycle.json")]
+async fn cycle_should_start_over_after_last_response() {
+    get(stubr.path("/cycle")).await.expect_body_text_eq("first");
+    get(stubr.path("/cycle")).await.expect_body_text_eq("second");
+    get(stubr.path("/cycle")).await.expect_body_text_eq("first");
+}
+
+#[async_std::test]
+#[stubr::mock("resp/sequence/random.json")]
+async fn random_should_pick_according_to_weights() {
+    for _ in 0..10 {
+        get(stubr.path("/random")).await.expect_status_ok();
+    }
+}
+
+#[async_std::test]
+#[stubr::mock("resp/sequence/template.json")]
+async fn sequence_should_support_templating() {
+    get(stubr.path("/template/abc")).await.expect_status(202);
+    get(stubr.path("/template/abc"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("abc");
+}
+
+#[async_std::test]
+#[stubr::mock("resp/sequence/sequential.json")]
+async fn reset_should_start_sequences_over() {
+    get(stubr.path("/sequential")).await.expect_status(503);
+    get(stubr.path("/sequential")).await.expect_status(503);
+    get(stubr.path("/sequential")).await.expect_status_ok();
+    stubr.reset_sequences();
+    get(stubr.path("/sequential")).await.expect_status(503);
+    get(stubr.path("/sequential")).await.expect_status(503);
+    get(stubr.path("/sequential")).await.expect_status_ok();
+}
+
+#[async_std::test]
+async fn should_reject_invalid_sequences() {
+    let cfg = Config {
+        strict: true,
+        ..Default::default()
+    };
+    match Stubr::try_start_with("tests/stubs/resp/sequence/invalid", cfg).await {
+        Err(StubrError::RejectedStubs(rejected)) => assert_eq!(rejected.len(), 2),
+        _ => panic!("invalid sequences should have been rejected"),
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use asserhttp::*;
+use async_std::io;
+use surf::get;
+
+mod sse {
+    use super::*;
+
+    #[async_std::test]
+    #[stubr::mock("resp/stream/sse.json")]
+    async fn should_stream_events() {
+        let mut resp = get(stubr.path("/prices")).await.unwrap();
+        resp.expect_status_ok()
+            .expect_header("content-type", "text/event-stream")
+            .expect_header("cache-control", "no-cache");
+        assert!(resp.header("content-length").is_none());
+        let body = resp.body_string().await.unwrap();
+        assert_eq!(
+            body,
+            "id: 1\nevent: price\ndata: {\"price\":42}\n\nid: 2\nevent: price\ndata: 43\n\n"
+        );
+    }
+
+    #[async_std::test]
+    #[stubr::mock("resp/stream/sse.json")]
+    async fn should_wait_before_each_event() {
+        let begin = std::time::Instant::now();
+        let mut resp = get(stubr.path("/prices")).await.unwrap();
+        resp.expect_status_ok();
+        assert!(begin.elapsed() < Duration::from_millis(300));
+        let timeout = io::timeout(Duration::from_millis(100), async { Ok(resp.body_string().await.unwrap()) }).await;
+        assert!(timeout.is_err());
+    }
+
+    #[async_std::test]
+    #[stubr::mock("resp/stream/template.json")]
+    async fn should_template_events() {
+        get(stubr.path("/prices/stock?currency=EUR"))
+            .await
+            .expect_status_ok()
+            .expect_body_text_eq("event: stock\ndata: EUR\n\n");
+    }
+
+    #[async_std::test]
+    #[stubr::mock("resp/stream/template-multiline.json")]
+    async fn should_spread_templated_multiline_data() {
+        surf::post(stubr.path("/echo"))
+            .body_string("a\nb".to_string())
+            .await
+            .expect_status_ok()
+            .expect_body_text_eq("data: a\ndata: b\n\n");
+    }
+}
+
+mod chunks {
+    use super::*;
+
+    #[async_std::test]
+    #[stubr::mock("resp/stream/chunks.json")]
+    async fn should_stream_chunks() {
+        let begin = std::time::Instant::now();
+        let mut resp = get(stubr.path("/download")).await.unwrap();
+        resp.expect_status_ok()
+            .expect_header("content-type", "text/plain")
+            .expect_header("transfer-encoding", "chunked");
+        assert!(begin.elapsed() < Duration::from_millis(300));
+        assert_eq!(resp.body_string().await.unwrap(), "Hello world!");
+        assert!(begin.elapsed() >= Duration::from_millis(300));
+    }
+}
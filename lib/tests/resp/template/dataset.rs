@@ -0,0 +1,62 @@
+use asserhttp::*;
+use serde_json::json;
+use surf::get;
+
+#[async_std::test]
+#[stubr::mock("resp/template/dataset/json-body.json")]
+async fn should_look_row_up_in_csv() {
+    get(stubr.path("/users/2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"id": 2, "name": "bob", "team": "docs, cli"}));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/template/dataset/json-body.json")]
+async fn should_respond_fallback_status_when_row_missing() {
+    get(stubr.path("/users/42"))
+        .await
+        .expect_status_not_found()
+        .expect_body_absent();
+    get(stubr.path("/users/1")).await.expect_status_ok();
+}
+
+#[async_std::test]
+#[stubr::mock("resp/template/dataset/whole-row.json")]
+async fn should_template_whole_row_from_json_dataset() {
+    get(stubr.path("/orders/2"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"order": {"id": 2, "user": "bob", "total": 10, "items": ["pen", "ink"]}}));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/template/dataset/whole-row.json")]
+async fn should_render_nothing_without_fallback_status() {
+    get(stubr.path("/orders/42"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"order": ""}));
+}
+
+#[async_std::test]
+#[stubr::mock("resp/template/dataset/many-fields.json")]
+async fn should_look_row_up_by_many_fields() {
+    get(stubr.path("/users?team=core&name=carol"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("carol@stubr.io");
+    get(stubr.path("/users?team=core&name=bob"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("nobody");
+}
+
+#[async_std::test]
+#[stubr::mock("resp/template/dataset/field.json")]
+async fn should_template_field_of_json_dataset() {
+    get(stubr.path("/orders/1/items"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"user": "alice", "total": 42.5, "items": ["book"]}));
+}
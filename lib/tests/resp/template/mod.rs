@@ -1,6 +1,7 @@
 pub mod any;
 pub mod base64;
 pub mod body;
+pub mod dataset;
 pub mod datetime;
 pub mod headers;
 pub mod json_path_body;
@@ -9,6 +10,7 @@ pub mod numbers;
 pub mod query;
 pub mod size;
 pub mod smoke;
+pub mod state;
 pub mod string;
 pub mod trim;
 pub mod url;
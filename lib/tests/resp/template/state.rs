@@ -0,0 +1,68 @@
+use asserhttp::*;
+use serde_json::json;
+use surf::{delete, get, put};
+
+use crate::utils::*;
+
+const STUBS: &str = "tests/stubs/resp/template/state";
+
+#[async_std::test]
+async fn should_read_what_another_stub_stored() {
+    let stubr = Stubr::start(STUBS).await;
+    get(stubr.path("/users/1")).await.expect_status_not_found();
+    put(stubr.path("/users/1"))
+        .body(json!({"name": "john"}))
+        .await
+        .expect_status_created();
+    get(stubr.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"name": "john"}));
+    get(stubr.path("/users/2")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_delete_stored_value() {
+    let stubr = Stubr::start(STUBS).await;
+    put(stubr.path("/users/1")).body("john").await.expect_status_created();
+    get(stubr.path("/users/1")).await.expect_status_ok().expect_body_text_eq("john");
+    delete(stubr.path("/users/1")).await.expect_status(204);
+    get(stubr.path("/users/1")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn state_should_be_scoped_to_a_server() {
+    let (first, second) = (Stubr::start(STUBS).await, Stubr::start(STUBS).await);
+    put(first.path("/users/1")).body("john").await.expect_status_created();
+    get(first.path("/users/1")).await.expect_status_ok();
+    get(second.path("/users/1")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_seed_then_clear_state() {
+    let stubr = Stubr::start(STUBS).await;
+    stubr.set_state_value("user", "1", json!({"name": "jane"}));
+    get(stubr.path("/users/1"))
+        .await
+        .expect_status_ok()
+        .expect_body_json_eq(json!({"name": "jane"}));
+    put(stubr.path("/users/2")).body("john").await.expect_status_created();
+    assert_eq!(stubr.state_value("user", "2"), Some(json!("john")));
+    stubr.clear_state();
+    assert!(stubr.state_value("user", "1").is_none());
+    get(stubr.path("/users/1")).await.expect_status_not_found();
+}
+
+#[async_std::test]
+async fn should_use_state_in_conditions() {
+    let stubr = Stubr::start(STUBS).await;
+    get(stubr.path("/greeting"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("Hello stranger");
+    stubr.set_state_value("greeting", "name", "john");
+    get(stubr.path("/greeting"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("Hello john");
+}
@@ -1,4 +1,5 @@
 use asserhttp::*;
+use isahc::config::{Configurable, VersionNegotiation};
 use serde_json::json;
 
 use stubr::Config;
@@ -143,3 +144,20 @@ mod path_segment_types {
             .expect_body_json_eq(json!({ "path": null }));
     }
 }
+
+#[async_std::test]
+#[stubr::mock("resp/template/url/protocol.json")]
+async fn should_template_request_protocol() {
+    surf::get(stubr.path("/api/protocol"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("HTTP/1.1");
+    isahc::HttpClient::builder()
+        .version_negotiation(VersionNegotiation::http2())
+        .build()
+        .unwrap()
+        .get_async(stubr.path("/api/protocol"))
+        .await
+        .expect_status_ok()
+        .expect_body_text_eq("HTTP/2");
+}